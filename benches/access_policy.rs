@@ -0,0 +1,55 @@
+use abe_policy::AccessPolicy;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use std::hint::black_box;
+
+mod common;
+
+use common::{conjunction, conjunction_of_disjunctions, policy, SIZES};
+
+fn bench_parse_boolean_expression(c: &mut Criterion) {
+    let mut group = c.benchmark_group("from_boolean_expression");
+    for size in SIZES {
+        let expression = conjunction_of_disjunctions(size, usize::MAX, usize::MAX);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(size.name),
+            &expression,
+            |b, expression| {
+                b.iter(|| AccessPolicy::from_boolean_expression(black_box(expression)).unwrap());
+            },
+        );
+    }
+    group.finish();
+}
+
+fn bench_attribute_combinations(c: &mut Criterion) {
+    let mut group = c.benchmark_group("to_attribute_combinations");
+    for size in SIZES {
+        let policy = policy(size);
+        // at most 5^4 combinations, more when following the hierarchies
+        let disjunctions =
+            AccessPolicy::from_boolean_expression(&conjunction_of_disjunctions(size, 4, 5))
+                .unwrap();
+        let conjunction = AccessPolicy::from_boolean_expression(&conjunction(size)).unwrap();
+        for (name, access_policy, follow_hierarchical_axes) in [
+            ("disjunctions", &disjunctions, false),
+            ("disjunctions, hierarchy", &disjunctions, true),
+            ("conjunction", &conjunction, false),
+        ] {
+            group.bench_with_input(BenchmarkId::new(name, size.name), &policy, |b, policy| {
+                b.iter(|| {
+                    access_policy
+                        .to_attribute_combinations(black_box(policy), follow_hierarchical_axes)
+                        .unwrap()
+                });
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_parse_boolean_expression,
+    bench_attribute_combinations
+);
+criterion_main!(benches);
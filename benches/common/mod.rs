@@ -0,0 +1,93 @@
+//! Policies and access policies shared by the benchmarks.
+
+// each benchmark uses only some of these helpers
+#![allow(dead_code)]
+
+use abe_policy::{Attribute, EncryptionHint, Policy, PolicyAxis};
+
+/// Shape of a benchmarked policy: `axes` axes of `attributes_per_axis`
+/// attributes each, every other axis being hierarchical.
+#[derive(Clone, Copy)]
+pub struct Size {
+    pub name: &'static str,
+    pub axes: usize,
+    pub attributes_per_axis: usize,
+}
+
+pub const SIZES: [Size; 3] = [
+    Size {
+        name: "small",
+        axes: 2,
+        attributes_per_axis: 4,
+    },
+    Size {
+        name: "medium",
+        axes: 4,
+        attributes_per_axis: 5,
+    },
+    Size {
+        name: "huge",
+        axes: 10,
+        attributes_per_axis: 1000,
+    },
+];
+
+fn axis_name(axis: usize) -> String {
+    format!("Axis {axis}")
+}
+
+fn attribute_name(attribute: usize) -> String {
+    format!("Attribute {attribute}")
+}
+
+/// Returns the attribute of the given indices.
+pub fn attribute(axis: usize, attribute: usize) -> Attribute {
+    Attribute::new(&axis_name(axis), &attribute_name(attribute))
+}
+
+/// Builds an axis of the given number of attributes.
+pub fn axis(axis: usize, attributes: usize, hierarchical: bool) -> PolicyAxis {
+    let names = (0..attributes).map(attribute_name).collect::<Vec<_>>();
+    PolicyAxis::new(
+        &axis_name(axis),
+        names
+            .iter()
+            .map(|name| (name.as_str(), EncryptionHint::Classic))
+            .collect(),
+        hierarchical,
+    )
+}
+
+/// Builds the policy of the given size.
+pub fn policy(size: Size) -> Policy {
+    let mut policy = Policy::new(u32::MAX);
+    for i in 0..size.axes {
+        policy
+            .add_axis(axis(i, size.attributes_per_axis, i % 2 == 0))
+            .unwrap();
+    }
+    policy
+}
+
+/// Writes the conjunction over the first `axes` axes of the disjunction of
+/// their first `attributes` attributes.
+pub fn conjunction_of_disjunctions(size: Size, axes: usize, attributes: usize) -> String {
+    (0..size.axes.min(axes))
+        .map(|axis| {
+            let disjunction = (0..size.attributes_per_axis.min(attributes))
+                .map(|i| attribute(axis, i).to_string())
+                .collect::<Vec<_>>()
+                .join(" || ");
+            format!("({disjunction})")
+        })
+        .collect::<Vec<_>>()
+        .join(" && ")
+}
+
+/// Writes the conjunction of all the attributes of the policy.
+pub fn conjunction(size: Size) -> String {
+    (0..size.axes)
+        .flat_map(|axis| (0..size.attributes_per_axis).map(move |i| attribute(axis, i).to_string()))
+        .collect::<Vec<_>>()
+        .join(" && ")
+}
@@ -0,0 +1,106 @@
+use abe_policy::{Policy, PolicyRef};
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use std::hint::black_box;
+
+mod common;
+
+use common::{attribute, axis, policy, SIZES};
+
+fn bench_serialization(c: &mut Criterion) {
+    let mut group = c.benchmark_group("serialization");
+    for size in SIZES {
+        let policy = policy(size);
+        let json = policy.to_string();
+        let bytes = policy.to_bytes().unwrap();
+        group.bench_with_input(
+            BenchmarkId::new("to_json", size.name),
+            &policy,
+            |b, policy| {
+                b.iter(|| black_box(policy).to_string());
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("from_json", size.name),
+            &json,
+            |b, json| {
+                b.iter(|| Policy::parse_and_convert(black_box(json.as_bytes())).unwrap());
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("from_json_ref", size.name),
+            &json,
+            |b, json| {
+                b.iter(|| PolicyRef::from_json(black_box(json.as_bytes())).unwrap());
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("to_bytes", size.name),
+            &policy,
+            |b, policy| {
+                b.iter(|| black_box(policy).to_bytes().unwrap());
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("from_bytes", size.name),
+            &bytes,
+            |b, bytes| {
+                b.iter(|| Policy::from_bytes(black_box(bytes)).unwrap());
+            },
+        );
+    }
+    group.finish();
+}
+
+fn bench_rotation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("rotate");
+    for size in SIZES {
+        let policy = policy(size);
+        let attribute = attribute(0, size.attributes_per_axis - 1);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(size.name),
+            &policy,
+            |b, policy| {
+                b.iter_batched(
+                    || policy.clone(),
+                    |mut policy| {
+                        policy.rotate(black_box(&attribute)).unwrap();
+                        // dropped outside of the measurement
+                        policy
+                    },
+                    BatchSize::LargeInput,
+                );
+            },
+        );
+    }
+    group.finish();
+}
+
+fn bench_add_axis(c: &mut Criterion) {
+    let mut group = c.benchmark_group("add_axis");
+    for attributes in [1_000, 100_000] {
+        let axis = axis(0, attributes, false);
+        for case_insensitive in [false, true] {
+            let mut policy = Policy::new(u32::MAX);
+            policy.naming_policy.case_insensitive = case_insensitive;
+            let name = if case_insensitive {
+                "case_insensitive"
+            } else {
+                "case_sensitive"
+            };
+            group.bench_with_input(BenchmarkId::new(name, attributes), &axis, |b, axis| {
+                b.iter_batched(
+                    || (policy.clone(), axis.clone()),
+                    |(mut policy, axis)| {
+                        policy.add_axis(black_box(axis)).unwrap();
+                        policy
+                    },
+                    BatchSize::LargeInput,
+                );
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_serialization, bench_rotation, bench_add_axis);
+criterion_main!(benches);
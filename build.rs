@@ -0,0 +1,23 @@
+//! Generates the C header of the FFI when the `ffi` feature is enabled and
+//! sets up the linking of the Node.js addon when the `node` feature is.
+
+fn main() {
+    #[cfg(feature = "node")]
+    napi_build::setup();
+
+    #[cfg(feature = "ffi")]
+    {
+        let crate_dir = std::env::var("CARGO_MANIFEST_DIR").expect("cargo sets CARGO_MANIFEST_DIR");
+        println!("cargo:rerun-if-changed=src/interfaces/ffi");
+        println!("cargo:rerun-if-changed=cbindgen.toml");
+        cbindgen::Builder::new()
+            .with_crate(&crate_dir)
+            .with_config(
+                cbindgen::Config::from_file(format!("{crate_dir}/cbindgen.toml"))
+                    .expect("cbindgen.toml is valid"),
+            )
+            .generate()
+            .expect("the FFI is parsable by cbindgen")
+            .write_to_file(format!("{crate_dir}/include/abe_policy.h"));
+    }
+}
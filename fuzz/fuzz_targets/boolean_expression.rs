@@ -0,0 +1,24 @@
+#![no_main]
+
+use abe_policy::{AccessPolicy, AccessPolicyRef};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|expression: &str| {
+    let Ok(access_policy) = AccessPolicy::from_boolean_expression(expression) else {
+        return;
+    };
+    // the written expression reads back to the same access policy
+    if access_policy != AccessPolicy::All {
+        let written = access_policy.to_string();
+        assert_eq!(
+            access_policy,
+            AccessPolicy::from_boolean_expression(&written).unwrap(),
+            "{written}"
+        );
+    }
+    // serde_json rejects policies nested more than 128 levels deep
+    let json = serde_json::to_vec(&access_policy).unwrap();
+    if let Ok(access_policy_ref) = AccessPolicyRef::from_json(&json) {
+        assert_eq!(access_policy, access_policy_ref.to_access_policy());
+    }
+});
@@ -0,0 +1,30 @@
+#![no_main]
+
+use std::io::Read;
+
+use abe_policy::Policy;
+use libfuzzer_sys::fuzz_target;
+
+/// Reader returning at most one byte per read, to cut the input at every
+/// offset.
+struct ByteReader<'a>(&'a [u8]);
+
+impl Read for ByteReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = buf.len().min(1);
+        self.0.read(&mut buf[..n])
+    }
+}
+
+fuzz_target!(|bytes: &[u8]| {
+    let policy = Policy::from_bytes(bytes);
+    let streamed = Policy::deserialize_bytes_from_reader(ByteReader(bytes));
+    let Ok(policy) = policy else {
+        return;
+    };
+    // the streaming reader ignores the trailing bytes rejected by `from_bytes`
+    assert_eq!(policy, streamed.unwrap());
+    let written = policy.to_bytes().unwrap();
+    assert_eq!(policy, Policy::from_bytes(&written).unwrap());
+    assert_eq!(written, Policy::from_bytes(&written).unwrap().to_bytes().unwrap());
+});
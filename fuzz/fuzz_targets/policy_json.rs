@@ -0,0 +1,27 @@
+#![no_main]
+
+use abe_policy::{Policy, PolicyRef};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|bytes: &[u8]| {
+    let _ = PolicyRef::from_json(bytes);
+    let strict = Policy::parse_strict(bytes);
+    let Ok((policy, is_legacy)) = Policy::parse_and_detect_legacy(bytes) else {
+        assert!(strict.is_err());
+        return;
+    };
+    if let Ok(strict) = strict {
+        assert_eq!(policy, strict);
+    }
+    // the streaming reader only reads the current format
+    if !is_legacy {
+        assert_eq!(policy, Policy::deserialize_from_reader(bytes).unwrap());
+    }
+    let json = serde_json::to_vec(&policy).unwrap();
+    assert_eq!(policy, Policy::parse_and_convert(&json).unwrap());
+    // the binary format only writes the attributes of the axes
+    if policy.check_consistency().is_empty() {
+        let bytes = policy.to_bytes().unwrap();
+        assert_eq!(policy, Policy::from_bytes(&bytes).unwrap());
+    }
+});
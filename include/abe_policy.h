@@ -0,0 +1,1054 @@
+/* ABE policy C interface, generated by cbindgen: do not edit. */
+
+#ifndef ABE_POLICY_H
+#define ABE_POLICY_H
+
+/* Regenerate by building the crate with the `ffi` feature. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/* Nullability annotations, understood by clang and the Swift importer. */
+#if defined(__clang__)
+#define ABE_NULLABLE _Nullable
+#define ABE_NONNULL _Nonnull
+#else
+#define ABE_NULLABLE
+#define ABE_NONNULL
+#endif
+
+/**
+ * Maximum size of an input of the FFI functions, in bytes for buffers and
+ * UTF-8 strings and in code units for UTF-16 strings.
+ */
+#define FFI_MAX_INPUT_LEN (1 << 28)
+
+/**
+ * Status code returned by the FFI functions on success.
+ */
+#define FFI_OK 0
+
+/**
+ * Status code returned by the FFI functions on failure. The error message
+ * can be retrieved with `get_last_error`.
+ */
+#define FFI_ERROR 1
+
+/**
+ * Status code returned by the FFI functions when the output pointer is null
+ * or the output buffer is too small. The required size is written to the
+ * length out-parameter.
+ */
+#define FFI_BUFFER_TOO_SMALL 2
+
+/**
+ * Status code returned by the FFI functions when a panic was caught. The
+ * panic message can be retrieved with `get_last_error`.
+ */
+#define FFI_PANIC 3
+
+/**
+ * Log level of errors.
+ */
+#define FFI_LOG_ERROR 1
+
+/**
+ * Log level of warnings: legacy policy conversions and policy lint findings,
+ * such as a nearly exhausted attribute creation capacity.
+ */
+#define FFI_LOG_WARN 2
+
+/**
+ * Log level of informational messages.
+ */
+#define FFI_LOG_INFO 3
+
+/**
+ * Version of the C ABI exposed by this library.
+ *
+ * It is bumped on every incompatible change of an exported function
+ * signature or of a status code meaning. Callers should compare it to the
+ * value returned by `h_abi_version` when loading the library.
+ */
+#define ABE_POLICY_ABI_VERSION 1
+
+/**
+ * Feature flag set if encryption hints (hybridized or classic encryption)
+ * are supported.
+ */
+#define FFI_FEATURE_HYBRIDIZATION 1
+
+/**
+ * Feature flag set if the compact binary policy format is supported.
+ */
+#define FFI_FEATURE_BINARY_FORMAT (1 << 1)
+
+/**
+ * Feature flag set if the library was built with random policy generation.
+ */
+#define FFI_FEATURE_RANDOM (1 << 2)
+
+/**
+ * Opaque handle to a policy opened with `h_policy_open`. 0 is never a valid
+ * handle.
+ */
+typedef uint64_t PolicyHandle;
+
+/**
+ * Function called with the level and the NULL-terminated UTF-8 message of
+ * each diagnostic. The message is only valid during the call.
+ */
+typedef void (*ABE_NULLABLE LogCallback)(int level, const char *ABE_NULLABLE message);
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Converts a boolean expression into an access policy.
+ *
+ * - `access_policy_ptr`       : output access policy buffer
+ * - `access_policy_len`       : size of the output buffer
+ * - `boolean_expression_ptr`  : boolean access policy string
+ * # Safety
+ */
+int h_parse_boolean_access_policy(char *ABE_NULLABLE access_policy_ptr,
+                                  int *ABE_NULLABLE access_policy_len,
+                                  const char *ABE_NULLABLE boolean_expression_ptr);
+
+/**
+ * Converts an access policy into a boolean expression.
+ *
+ * - `boolean_expression_ptr`  : output boolean expression buffer
+ * - `boolean_expression_len`  : size of the output buffer
+ * - `access_policy_ptr`       : access policy (JSON)
+ * # Safety
+ */
+int h_access_policy_to_boolean_expression(char *ABE_NULLABLE boolean_expression_ptr,
+                                          int *ABE_NULLABLE boolean_expression_len,
+                                          const char *ABE_NULLABLE access_policy_ptr);
+
+/**
+ * Validates a boolean expression, against a policy if one is given.
+ *
+ * If the expression is valid, 0 is written to `error_len`. Otherwise, the
+ * error is written to `error_ptr` as a JSON object
+ * `{ "offset", "length", "expected", "message" }` where `offset` and
+ * `length` locate the erroneous part of the expression, in bytes, and
+ * `expected` lists the tokens expected there.
+ *
+ * - `error_ptr`               : output error buffer (JSON)
+ * - `error_len`               : size of the output buffer
+ * - `boolean_expression_ptr`  : boolean access policy string
+ * - `policy_ptr`              : policy (JSON), may be null
+ * - `policy_len`              : size of the policy, 0 if there is none
+ * # Safety
+ */
+int h_validate_boolean_expression(char *ABE_NULLABLE error_ptr,
+                                  int *ABE_NULLABLE error_len,
+                                  const char *ABE_NULLABLE boolean_expression_ptr,
+                                  const char *ABE_NULLABLE policy_ptr,
+                                  int policy_len);
+
+/**
+ * Converts a boolean expression into the combinations of attributes
+ * granting access: a JSON array of arrays of attributes, the access policy
+ * being the disjunction of the conjunction of the attributes of each array.
+ *
+ * - `combinations_ptr`        : output combinations buffer (JSON)
+ * - `combinations_len`        : size of the output buffer
+ * - `policy_ptr`              : policy (JSON)
+ * - `policy_len`              : size of the policy
+ * - `boolean_expression_ptr`  : boolean access policy string
+ * - `follow_hierarchical_axes`: if non-zero, the attributes below those of
+ *   the expression on hierarchical axes also grant access
+ * # Safety
+ */
+int h_access_policy_to_combinations(char *ABE_NULLABLE combinations_ptr,
+                                    int *ABE_NULLABLE combinations_len,
+                                    const char *ABE_NULLABLE policy_ptr,
+                                    int policy_len,
+                                    const char *ABE_NULLABLE boolean_expression_ptr,
+                                    int follow_hierarchical_axes);
+
+/**
+ * Checks whether a user holding the given attributes is granted access by
+ * a boolean expression. On a hierarchical axis, an attribute also grants
+ * access to the attributes below it.
+ *
+ * Returns 1 if access is granted, 0 if it is not and the opposite of a
+ * status code, e.g. `-FFI_ERROR`, on failure. The error message can be
+ * retrieved with `get_last_error`.
+ *
+ * - `policy_ptr`              : policy (JSON)
+ * - `policy_len`              : size of the policy
+ * - `boolean_expression_ptr`  : boolean access policy string
+ * - `attributes_ptr`          : user attributes, written `Axis::Name` and
+ *   separated by commas, e.g. `Department::HR,Security Level::Protected`
+ * # Safety
+ */
+int h_access_policy_is_satisfied(const char *ABE_NULLABLE policy_ptr,
+                                 int policy_len,
+                                 const char *ABE_NULLABLE boolean_expression_ptr,
+                                 const char *ABE_NULLABLE attributes_ptr);
+
+/**
+ * Releases a buffer allocated by an `_alloc` function. Does nothing if the
+ * pointer is null.
+ *
+ * - `buffer_ptr`  : buffer to release
+ * - `buffer_len`  : size of the buffer, as returned by the `_alloc` function
+ * # Safety
+ */
+void h_free_buffer(char *ABE_NULLABLE buffer_ptr, int buffer_len);
+
+/**
+ * Variant of `h_parse_boolean_access_policy` allocating the output buffer, to release with
+ * `h_free_buffer`.
+ *
+ * # Safety
+ */
+int h_parse_boolean_access_policy_alloc(char *ABE_NULLABLE *ABE_NULLABLE access_policy_ptr,
+                                        int *ABE_NULLABLE access_policy_len,
+                                        const char *ABE_NULLABLE boolean_expression_ptr);
+
+/**
+ * Variant of `h_access_policy_to_boolean_expression` allocating the output buffer, to release with
+ * `h_free_buffer`.
+ *
+ * # Safety
+ */
+int h_access_policy_to_boolean_expression_alloc(char *ABE_NULLABLE *ABE_NULLABLE boolean_expression_ptr,
+                                                int *ABE_NULLABLE boolean_expression_len,
+                                                const char *ABE_NULLABLE access_policy_ptr);
+
+/**
+ * Variant of `h_validate_boolean_expression` allocating the output buffer, to release with
+ * `h_free_buffer`.
+ *
+ * # Safety
+ */
+int h_validate_boolean_expression_alloc(char *ABE_NULLABLE *ABE_NULLABLE error_ptr,
+                                        int *ABE_NULLABLE error_len,
+                                        const char *ABE_NULLABLE boolean_expression_ptr,
+                                        const char *ABE_NULLABLE policy_ptr,
+                                        int policy_len);
+
+/**
+ * Variant of `h_access_policy_to_combinations` allocating the output buffer, to release with
+ * `h_free_buffer`.
+ *
+ * # Safety
+ */
+int h_access_policy_to_combinations_alloc(char *ABE_NULLABLE *ABE_NULLABLE combinations_ptr,
+                                          int *ABE_NULLABLE combinations_len,
+                                          const char *ABE_NULLABLE policy_ptr,
+                                          int policy_len,
+                                          const char *ABE_NULLABLE boolean_expression_ptr,
+                                          int follow_hierarchical_axes);
+
+/**
+ * Variant of `h_policy_new` allocating the output buffer, to release with
+ * `h_free_buffer`.
+ *
+ * # Safety
+ */
+int h_policy_new_alloc(char *ABE_NULLABLE *ABE_NULLABLE policy_ptr,
+                       int *ABE_NULLABLE policy_len,
+                       int max_attribute_creations);
+
+/**
+ * Variant of `h_policy_add_axis` allocating the output buffer, to release with
+ * `h_free_buffer`.
+ *
+ * # Safety
+ */
+int h_policy_add_axis_alloc(char *ABE_NULLABLE *ABE_NULLABLE updated_policy_ptr,
+                            int *ABE_NULLABLE updated_policy_len,
+                            const char *ABE_NULLABLE current_policy_ptr,
+                            int current_policy_len,
+                            const char *ABE_NULLABLE axis_ptr);
+
+/**
+ * Variant of `h_policy_rotate_attribute` allocating the output buffer, to release with
+ * `h_free_buffer`.
+ *
+ * # Safety
+ */
+int h_policy_rotate_attribute_alloc(char *ABE_NULLABLE *ABE_NULLABLE updated_policy_ptr,
+                                    int *ABE_NULLABLE updated_policy_len,
+                                    const char *ABE_NULLABLE current_policy_ptr,
+                                    int current_policy_len,
+                                    const char *ABE_NULLABLE attribute_ptr);
+
+/**
+ * Variant of `h_policy_to_json` allocating the output buffer, to release with
+ * `h_free_buffer`.
+ *
+ * # Safety
+ */
+int h_policy_to_json_alloc(char *ABE_NULLABLE *ABE_NULLABLE json_ptr,
+                           int *ABE_NULLABLE json_len,
+                           const char *ABE_NULLABLE policy_ptr,
+                           int policy_len);
+
+/**
+ * Variant of `h_policy_to_bytes` allocating the output buffer, to release with
+ * `h_free_buffer`.
+ *
+ * # Safety
+ */
+int h_policy_to_bytes_alloc(char *ABE_NULLABLE *ABE_NULLABLE bytes_ptr,
+                            int *ABE_NULLABLE bytes_len,
+                            const char *ABE_NULLABLE policy_ptr,
+                            int policy_len);
+
+/**
+ * Variant of `h_policy_from_bytes` allocating the output buffer, to release with
+ * `h_free_buffer`.
+ *
+ * # Safety
+ */
+int h_policy_from_bytes_alloc(char *ABE_NULLABLE *ABE_NULLABLE json_ptr,
+                              int *ABE_NULLABLE json_len,
+                              const char *ABE_NULLABLE bytes_ptr,
+                              int bytes_len);
+
+/**
+ * Variant of `h_policy_attribute_values` allocating the output buffer, to release with
+ * `h_free_buffer`.
+ *
+ * # Safety
+ */
+int h_policy_attribute_values_alloc(char *ABE_NULLABLE *ABE_NULLABLE values_ptr,
+                                    int *ABE_NULLABLE values_len,
+                                    const char *ABE_NULLABLE policy_ptr,
+                                    int policy_len,
+                                    const char *ABE_NULLABLE attribute_ptr);
+
+/**
+ * Variant of `h_policy_attributes` allocating the output buffer, to release with
+ * `h_free_buffer`.
+ *
+ * # Safety
+ */
+int h_policy_attributes_alloc(char *ABE_NULLABLE *ABE_NULLABLE attributes_ptr,
+                              int *ABE_NULLABLE attributes_len,
+                              const char *ABE_NULLABLE policy_ptr,
+                              int policy_len,
+                              const char *ABE_NULLABLE axis_ptr);
+
+/**
+ * Variant of `h_rotate_attributes` allocating the output buffer, to release with
+ * `h_free_buffer`.
+ *
+ * # Safety
+ */
+int h_rotate_attributes_alloc(char *ABE_NULLABLE *ABE_NULLABLE updated_policy_ptr,
+                              int *ABE_NULLABLE updated_policy_len,
+                              const char *ABE_NULLABLE current_policy_ptr,
+                              int current_policy_len,
+                              const char *ABE_NULLABLE attributes_ptr);
+
+/**
+ * Variant of `h_policy_handle_to_json` allocating the output buffer, to release with
+ * `h_free_buffer`.
+ *
+ * # Safety
+ */
+int h_policy_handle_to_json_alloc(char *ABE_NULLABLE *ABE_NULLABLE json_ptr,
+                                  int *ABE_NULLABLE json_len,
+                                  PolicyHandle handle);
+
+/**
+ * Externally set the last error recorded on the Rust side
+ *
+ * # Safety
+ * This function is meant to be called from the Foreign Function
+ * Interface
+ */
+int32_t set_error(const char *ABE_NULLABLE error_message_ptr);
+
+/**
+ * Get the most recent error as utf-8 bytes, clearing it in the process.
+ *
+ * If `error_msg_ptr` is null, the size of the buffer needed to hold the
+ * error message, including the final NULL byte, is written to `error_len`
+ * and `FFI_BUFFER_TOO_SMALL` is returned. The error is not cleared.
+ *
+ * Otherwise, the message is truncated to fit in the buffer.
+ * # Safety
+ * - `error_msg`: must be pre-allocated with a sufficient size
+ */
+int get_last_error(char *ABE_NULLABLE error_msg_ptr, int *ABE_NULLABLE error_len);
+
+/**
+ * Get the status code of the most recent error, `FFI_OK` if there is none.
+ *
+ * The error is not cleared, its message can still be retrieved with
+ * `get_last_error`.
+ */
+int h_get_last_error_code(void);
+
+/**
+ * Creates an empty policy allowing the given number of attribute creations
+ * (additions + rotations).
+ *
+ * - `policy_ptr`              : output policy buffer (JSON)
+ * - `policy_len`              : size of the output buffer
+ * - `max_attribute_creations` : maximum number of attribute creations
+ * - `error_ptr`               : output error message buffer
+ * - `error_len`               : size of the error buffer
+ * # Safety
+ */
+int h_flat_policy_new(char *ABE_NULLABLE policy_ptr,
+                      int *ABE_NULLABLE policy_len,
+                      unsigned int max_attribute_creations,
+                      char *ABE_NULLABLE error_ptr,
+                      int *ABE_NULLABLE error_len);
+
+/**
+ * Adds an axis to a policy.
+ *
+ * - `updated_policy_ptr`  : output policy buffer (JSON)
+ * - `updated_policy_len`  : size of the output buffer
+ * - `policy_ptr`          : policy to update (JSON)
+ * - `policy_len`          : size of the policy to update
+ * - `axis_ptr`            : axis to add (JSON)
+ * - `axis_len`            : size of the axis
+ * - `error_ptr`           : output error message buffer
+ * - `error_len`           : size of the error buffer
+ * # Safety
+ */
+int h_flat_policy_add_axis(char *ABE_NULLABLE updated_policy_ptr,
+                           int *ABE_NULLABLE updated_policy_len,
+                           const char *ABE_NULLABLE policy_ptr,
+                           int policy_len,
+                           const char *ABE_NULLABLE axis_ptr,
+                           int axis_len,
+                           char *ABE_NULLABLE error_ptr,
+                           int *ABE_NULLABLE error_len);
+
+/**
+ * Rotates an attribute of a policy, changing its underlying value with an
+ * unused value.
+ *
+ * - `updated_policy_ptr`  : output policy buffer (JSON)
+ * - `updated_policy_len`  : size of the output buffer
+ * - `policy_ptr`          : policy to update (JSON)
+ * - `policy_len`          : size of the policy to update
+ * - `attribute_ptr`       : attribute to rotate, written `Axis::Name`
+ * - `attribute_len`       : size of the attribute
+ * - `error_ptr`           : output error message buffer
+ * - `error_len`           : size of the error buffer
+ * # Safety
+ */
+int h_flat_policy_rotate_attribute(char *ABE_NULLABLE updated_policy_ptr,
+                                   int *ABE_NULLABLE updated_policy_len,
+                                   const char *ABE_NULLABLE policy_ptr,
+                                   int policy_len,
+                                   const char *ABE_NULLABLE attribute_ptr,
+                                   int attribute_len,
+                                   char *ABE_NULLABLE error_ptr,
+                                   int *ABE_NULLABLE error_len);
+
+/**
+ * Gets the current value of an attribute.
+ *
+ * - `value`           : output attribute value
+ * - `policy_ptr`      : policy (JSON)
+ * - `policy_len`      : size of the policy
+ * - `attribute_ptr`   : attribute, written `Axis::Name`
+ * - `attribute_len`   : size of the attribute
+ * - `error_ptr`       : output error message buffer
+ * - `error_len`       : size of the error buffer
+ * # Safety
+ */
+int h_flat_policy_attribute_current_value(unsigned int *ABE_NULLABLE value,
+                                          const char *ABE_NULLABLE policy_ptr,
+                                          int policy_len,
+                                          const char *ABE_NULLABLE attribute_ptr,
+                                          int attribute_len,
+                                          char *ABE_NULLABLE error_ptr,
+                                          int *ABE_NULLABLE error_len);
+
+/**
+ * Converts a boolean expression into a JSON access policy.
+ *
+ * - `access_policy_ptr`       : output access policy buffer (JSON)
+ * - `access_policy_len`       : size of the output buffer
+ * - `boolean_expression_ptr`  : boolean expression
+ * - `boolean_expression_len`  : size of the boolean expression
+ * - `error_ptr`               : output error message buffer
+ * - `error_len`               : size of the error buffer
+ * # Safety
+ */
+int h_flat_parse_boolean_access_policy(char *ABE_NULLABLE access_policy_ptr,
+                                       int *ABE_NULLABLE access_policy_len,
+                                       const char *ABE_NULLABLE boolean_expression_ptr,
+                                       int boolean_expression_len,
+                                       char *ABE_NULLABLE error_ptr,
+                                       int *ABE_NULLABLE error_len);
+
+/**
+ * Computes the attribute combinations granting access under a boolean
+ * expression, as a JSON array of arrays of attributes.
+ *
+ * - `combinations_ptr`        : output combinations buffer (JSON)
+ * - `combinations_len`        : size of the output buffer
+ * - `policy_ptr`              : policy (JSON)
+ * - `policy_len`              : size of the policy
+ * - `boolean_expression_ptr`  : boolean expression
+ * - `boolean_expression_len`  : size of the boolean expression
+ * - `follow_hierarchical_axes`: if non-zero, the attributes below those of
+ *   the expression on hierarchical axes also grant access
+ * - `error_ptr`               : output error message buffer
+ * - `error_len`               : size of the error buffer
+ * # Safety
+ */
+int h_flat_access_policy_to_combinations(char *ABE_NULLABLE combinations_ptr,
+                                         int *ABE_NULLABLE combinations_len,
+                                         const char *ABE_NULLABLE policy_ptr,
+                                         int policy_len,
+                                         const char *ABE_NULLABLE boolean_expression_ptr,
+                                         int boolean_expression_len,
+                                         int follow_hierarchical_axes,
+                                         char *ABE_NULLABLE error_ptr,
+                                         int *ABE_NULLABLE error_len);
+
+/**
+ * Opens a policy, returning a handle to use with the `h_policy_handle_*`
+ * functions. The policy must be closed with `h_policy_close`.
+ *
+ * - `handle`      : output handle
+ * - `policy_ptr`  : policy (JSON, current or legacy format)
+ * - `policy_len`  : size of the policy
+ * # Safety
+ */
+int h_policy_open(PolicyHandle *ABE_NULLABLE handle,
+                  const char *ABE_NULLABLE policy_ptr,
+                  int policy_len);
+
+/**
+ * Closes a policy opened with `h_policy_open`, invalidating its handle.
+ */
+int h_policy_close(PolicyHandle handle);
+
+/**
+ * Adds an axis to an opened policy.
+ *
+ * - `handle`      : policy handle
+ * - `axis_ptr`    : axis to add (JSON)
+ * # Safety
+ */
+int h_policy_handle_add_axis(PolicyHandle handle, const char *ABE_NULLABLE axis_ptr);
+
+/**
+ * Rotates an attribute of an opened policy.
+ *
+ * - `handle`          : policy handle
+ * - `attribute_ptr`   : attribute to rotate, written `Axis::Name`
+ * # Safety
+ */
+int h_policy_handle_rotate_attribute(PolicyHandle handle, const char *ABE_NULLABLE attribute_ptr);
+
+/**
+ * Retrieves the current value of an attribute of an opened policy.
+ *
+ * - `value`           : output value
+ * - `handle`          : policy handle
+ * - `attribute_ptr`   : attribute, written `Axis::Name`
+ * # Safety
+ */
+int h_policy_handle_attribute_current_value(unsigned int *ABE_NULLABLE value,
+                                            PolicyHandle handle,
+                                            const char *ABE_NULLABLE attribute_ptr);
+
+/**
+ * Writes an opened policy as JSON.
+ *
+ * - `json_ptr`    : output JSON buffer
+ * - `json_len`    : size of the output buffer
+ * - `handle`      : policy handle
+ * # Safety
+ */
+int h_policy_handle_to_json(char *ABE_NULLABLE json_ptr,
+                            int *ABE_NULLABLE json_len,
+                            PolicyHandle handle);
+
+/**
+ * Sets the function receiving the diagnostics of the library, replacing the
+ * previous one. Diagnostics are discarded if the callback is null, which is
+ * the default.
+ *
+ * The callback may be called from any thread calling the library.
+ */
+void h_set_log_callback(LogCallback callback);
+
+/**
+ * Creates an empty policy allowing the given number of attribute creations
+ * (additions + rotations).
+ *
+ * - `policy_ptr`                  : output policy buffer (JSON)
+ * - `policy_len`                  : size of the output buffer
+ * - `max_attribute_creations`     : maximum number of attribute creations
+ * # Safety
+ */
+int h_policy_new(char *ABE_NULLABLE policy_ptr,
+                 int *ABE_NULLABLE policy_len,
+                 int max_attribute_creations);
+
+/**
+ * Adds an axis to a policy.
+ *
+ * - `updated_policy_ptr`  : output policy buffer (JSON)
+ * - `updated_policy_len`  : size of the output buffer
+ * - `current_policy_ptr`  : policy to update (JSON)
+ * - `current_policy_len`  : size of the policy to update
+ * - `axis_ptr`            : axis to add (JSON)
+ * # Safety
+ */
+int h_policy_add_axis(char *ABE_NULLABLE updated_policy_ptr,
+                      int *ABE_NULLABLE updated_policy_len,
+                      const char *ABE_NULLABLE current_policy_ptr,
+                      int current_policy_len,
+                      const char *ABE_NULLABLE axis_ptr);
+
+/**
+ * Rotates an attribute of a policy, changing its underlying value with an
+ * unused value.
+ *
+ * - `updated_policy_ptr`  : output policy buffer (JSON)
+ * - `updated_policy_len`  : size of the output buffer
+ * - `current_policy_ptr`  : policy to update (JSON)
+ * - `current_policy_len`  : size of the policy to update
+ * - `attribute_ptr`       : attribute to rotate, written `Axis::Name`
+ * # Safety
+ */
+int h_policy_rotate_attribute(char *ABE_NULLABLE updated_policy_ptr,
+                              int *ABE_NULLABLE updated_policy_len,
+                              const char *ABE_NULLABLE current_policy_ptr,
+                              int current_policy_len,
+                              const char *ABE_NULLABLE attribute_ptr);
+
+/**
+ * Writes a policy as JSON in the current format. The given policy may use
+ * the legacy format.
+ *
+ * - `json_ptr`    : output JSON buffer
+ * - `json_len`    : size of the output buffer
+ * - `policy_ptr`  : policy (JSON, current or legacy format)
+ * - `policy_len`  : size of the policy
+ * # Safety
+ */
+int h_policy_to_json(char *ABE_NULLABLE json_ptr,
+                     int *ABE_NULLABLE json_len,
+                     const char *ABE_NULLABLE policy_ptr,
+                     int policy_len);
+
+/**
+ * Writes a policy in the compact binary format.
+ *
+ * - `bytes_ptr`   : output binary buffer
+ * - `bytes_len`   : size of the output buffer
+ * - `policy_ptr`  : policy (JSON, current or legacy format)
+ * - `policy_len`  : size of the policy
+ * # Safety
+ */
+int h_policy_to_bytes(char *ABE_NULLABLE bytes_ptr,
+                      int *ABE_NULLABLE bytes_len,
+                      const char *ABE_NULLABLE policy_ptr,
+                      int policy_len);
+
+/**
+ * Writes as JSON a policy given in the compact binary format.
+ *
+ * - `json_ptr`    : output JSON buffer
+ * - `json_len`    : size of the output buffer
+ * - `bytes_ptr`   : policy (binary)
+ * - `bytes_len`   : size of the policy
+ * # Safety
+ */
+int h_policy_from_bytes(char *ABE_NULLABLE json_ptr,
+                        int *ABE_NULLABLE json_len,
+                        const char *ABE_NULLABLE bytes_ptr,
+                        int bytes_len);
+
+/**
+ * Writes the values given to an attribute over rotations as a JSON array,
+ * the current value first.
+ *
+ * - `values_ptr`      : output values buffer (JSON)
+ * - `values_len`      : size of the output buffer
+ * - `policy_ptr`      : policy (JSON)
+ * - `policy_len`      : size of the policy
+ * - `attribute_ptr`   : attribute, written `Axis::Name`
+ * # Safety
+ */
+int h_policy_attribute_values(char *ABE_NULLABLE values_ptr,
+                              int *ABE_NULLABLE values_len,
+                              const char *ABE_NULLABLE policy_ptr,
+                              int policy_len,
+                              const char *ABE_NULLABLE attribute_ptr);
+
+/**
+ * Writes the attributes of a policy, or of one of its axes, as a JSON array
+ * of strings written `Axis::Name`, e.g.
+ * `["Department::R&D", "Department::HR"]`. Attributes are listed by axis,
+ * in the order the axes were added, then in axis order.
+ *
+ * - `attributes_ptr`  : output attributes buffer (JSON)
+ * - `attributes_len`  : size of the output buffer
+ * - `policy_ptr`      : policy (JSON)
+ * - `policy_len`      : size of the policy
+ * - `axis_ptr`        : name of the axis to list, null to list all axes
+ * # Safety
+ */
+int h_policy_attributes(char *ABE_NULLABLE attributes_ptr,
+                        int *ABE_NULLABLE attributes_len,
+                        const char *ABE_NULLABLE policy_ptr,
+                        int policy_len,
+                        const char *ABE_NULLABLE axis_ptr);
+
+/**
+ * Retrieves the current value of an attribute.
+ *
+ * - `value`           : output value
+ * - `policy_ptr`      : policy (JSON)
+ * - `policy_len`      : size of the policy
+ * - `attribute_ptr`   : attribute, written `Axis::Name`
+ * # Safety
+ */
+int h_policy_attribute_current_value(unsigned int *ABE_NULLABLE value,
+                                     const char *ABE_NULLABLE policy_ptr,
+                                     int policy_len,
+                                     const char *ABE_NULLABLE attribute_ptr);
+
+/**
+ * Retrieves the number of attribute creations (additions + rotations) still
+ * allowed by a policy.
+ *
+ * - `capacity`        : output number of remaining attribute creations
+ * - `policy_ptr`      : policy (JSON)
+ * - `policy_len`      : size of the policy
+ * # Safety
+ */
+int h_policy_remaining_capacity(unsigned int *ABE_NULLABLE capacity,
+                                const char *ABE_NULLABLE policy_ptr,
+                                int policy_len);
+
+/**
+ * Same as `h_policy_new`.
+ *
+ * # Safety
+ */
+int h_policy(char *ABE_NULLABLE policy_ptr,
+             int *ABE_NULLABLE policy_len,
+             int max_attribute_creations);
+
+/**
+ * Same as `h_policy_add_axis`.
+ *
+ * # Safety
+ */
+int h_add_policy_axis(char *ABE_NULLABLE updated_policy_ptr,
+                      int *ABE_NULLABLE updated_policy_len,
+                      const char *ABE_NULLABLE current_policy_ptr,
+                      int current_policy_len,
+                      char *ABE_NULLABLE axis_ptr);
+
+/**
+ * Same as `h_policy_rotate_attribute`.
+ *
+ * # Safety
+ */
+int h_rotate_attribute(char *ABE_NULLABLE updated_policy_ptr,
+                       int *ABE_NULLABLE updated_policy_len,
+                       const char *ABE_NULLABLE current_policy_ptr,
+                       int current_policy_len,
+                       const char *ABE_NULLABLE axis_ptr);
+
+/**
+ * Rotates a comma-separated list of attributes, e.g.
+ * `Department::FIN, Security Level::Top Secret`.
+ *
+ * # Safety
+ */
+int h_rotate_attributes(char *ABE_NULLABLE updated_policy_ptr,
+                        int *ABE_NULLABLE updated_policy_len,
+                        const char *ABE_NULLABLE current_policy_ptr,
+                        int current_policy_len,
+                        const char *ABE_NULLABLE attributes_ptr);
+
+/**
+ * Rotates a comma-separated list of attributes and writes, in addition to
+ * the updated policy, the new value of each rotated attribute as a JSON
+ * object, e.g. `{"Department::FIN": 12}`.
+ *
+ * If one of the output buffers is null or too small, the required sizes of
+ * both buffers are written and `FFI_BUFFER_TOO_SMALL` is returned.
+ *
+ * - `updated_policy_ptr`  : output policy buffer (JSON)
+ * - `updated_policy_len`  : size of the output policy buffer
+ * - `new_values_ptr`      : output new values buffer (JSON)
+ * - `new_values_len`      : size of the output new values buffer
+ * - `current_policy_ptr`  : policy to update (JSON)
+ * - `current_policy_len`  : size of the policy to update
+ * - `attributes_ptr`      : attributes to rotate, e.g.
+ *   `Department::FIN, Security Level::Top Secret`
+ * # Safety
+ */
+int h_policy_rotate_attributes(char *ABE_NULLABLE updated_policy_ptr,
+                               int *ABE_NULLABLE updated_policy_len,
+                               char *ABE_NULLABLE new_values_ptr,
+                               int *ABE_NULLABLE new_values_len,
+                               const char *ABE_NULLABLE current_policy_ptr,
+                               int current_policy_len,
+                               const char *ABE_NULLABLE attributes_ptr);
+
+/**
+ * Writes the differences between two policies as a JSON object listing the
+ * `added_axes`, `removed_axes`, `added_attributes`, `removed_attributes`,
+ * `rotated_attributes` and `hint_changes` from the first policy to the
+ * second one, and the new `max_attribute_creations` if it changed (`null`
+ * otherwise).
+ *
+ * - `diff_ptr`        : output diff buffer (JSON)
+ * - `diff_len`        : size of the output buffer
+ * - `policy_ptr`      : first policy (JSON)
+ * - `policy_len`      : size of the first policy
+ * - `other_ptr`       : second policy (JSON)
+ * - `other_len`       : size of the second policy
+ * # Safety
+ */
+int h_policy_diff(char *ABE_NULLABLE diff_ptr,
+                  int *ABE_NULLABLE diff_len,
+                  const char *ABE_NULLABLE policy_ptr,
+                  int policy_len,
+                  const char *ABE_NULLABLE other_ptr,
+                  int other_len);
+
+/**
+ * Merges two copies of a policy modified independently. See
+ * `Policy::merge` for the merge rules.
+ *
+ * - `merged_policy_ptr`   : output policy buffer (JSON)
+ * - `merged_policy_len`   : size of the output buffer
+ * - `policy_ptr`          : policy to merge into (JSON)
+ * - `policy_len`          : size of the policy to merge into
+ * - `other_ptr`           : policy to merge (JSON)
+ * - `other_len`           : size of the policy to merge
+ * # Safety
+ */
+int h_policy_merge(char *ABE_NULLABLE merged_policy_ptr,
+                   int *ABE_NULLABLE merged_policy_len,
+                   const char *ABE_NULLABLE policy_ptr,
+                   int policy_len,
+                   const char *ABE_NULLABLE other_ptr,
+                   int other_len);
+
+/**
+ * Converts a policy in the legacy format to the current format, and writes
+ * an upgrade report as a JSON object:
+ *
+ * - `legacy`      : `true` if the given policy used the legacy format
+ * - `axes`        : number of axes of the policy
+ * - `attributes`  : number of attributes of the policy
+ * - `changes`     : descriptions of the changes made by the conversion
+ * - `lints`       : warnings about the upgraded policy
+ *
+ * A policy already in the current format is written back unchanged. If one
+ * of the output buffers is null or too small, the required sizes of both
+ * buffers are written and `FFI_BUFFER_TOO_SMALL` is returned.
+ *
+ * - `upgraded_policy_ptr` : output policy buffer (JSON)
+ * - `upgraded_policy_len` : size of the output policy buffer
+ * - `report_ptr`          : output report buffer (JSON)
+ * - `report_len`          : size of the output report buffer
+ * - `legacy_policy_ptr`   : policy to upgrade (JSON)
+ * - `legacy_policy_len`   : size of the policy to upgrade
+ * # Safety
+ */
+int h_policy_upgrade_legacy(char *ABE_NULLABLE upgraded_policy_ptr,
+                            int *ABE_NULLABLE upgraded_policy_len,
+                            char *ABE_NULLABLE report_ptr,
+                            int *ABE_NULLABLE report_len,
+                            const char *ABE_NULLABLE legacy_policy_ptr,
+                            int legacy_policy_len);
+
+/**
+ * Get the version of the C ABI implemented by the loaded library.
+ */
+unsigned int h_abi_version(void);
+
+/**
+ * Writes the version of the library, e.g. `3.0.4`.
+ *
+ * - `version_ptr` : output version buffer
+ * - `version_len` : size of the output buffer
+ * # Safety
+ */
+int h_get_version(char *ABE_NULLABLE version_ptr, int *ABE_NULLABLE version_len);
+
+/**
+ * Get the features supported by the loaded library, as a combination of the
+ * `FFI_FEATURE_*` flags.
+ */
+unsigned int h_get_features(void);
+
+/**
+ * UTF-16 variant of `h_parse_boolean_access_policy`.
+ *
+ * # Safety
+ */
+int h_parse_boolean_access_policy_w(char *ABE_NULLABLE access_policy_ptr,
+                                    int *ABE_NULLABLE access_policy_len,
+                                    const uint16_t *ABE_NULLABLE boolean_expression_ptr);
+
+/**
+ * UTF-16 variant of `h_access_policy_to_boolean_expression`.
+ *
+ * # Safety
+ */
+int h_access_policy_to_boolean_expression_w(char *ABE_NULLABLE boolean_expression_ptr,
+                                            int *ABE_NULLABLE boolean_expression_len,
+                                            const uint16_t *ABE_NULLABLE access_policy_ptr);
+
+/**
+ * UTF-16 variant of `h_validate_boolean_expression`.
+ *
+ * The offset and length of the error still count bytes of the UTF-8
+ * encoding of the expression.
+ *
+ * # Safety
+ */
+int h_validate_boolean_expression_w(char *ABE_NULLABLE error_ptr,
+                                    int *ABE_NULLABLE error_len,
+                                    const uint16_t *ABE_NULLABLE boolean_expression_ptr,
+                                    const char *ABE_NULLABLE policy_ptr,
+                                    int policy_len);
+
+/**
+ * UTF-16 variant of `h_access_policy_to_combinations`.
+ *
+ * # Safety
+ */
+int h_access_policy_to_combinations_w(char *ABE_NULLABLE combinations_ptr,
+                                      int *ABE_NULLABLE combinations_len,
+                                      const char *ABE_NULLABLE policy_ptr,
+                                      int policy_len,
+                                      const uint16_t *ABE_NULLABLE boolean_expression_ptr,
+                                      int follow_hierarchical_axes);
+
+/**
+ * UTF-16 variant of `h_policy_add_axis`.
+ *
+ * # Safety
+ */
+int h_policy_add_axis_w(char *ABE_NULLABLE updated_policy_ptr,
+                        int *ABE_NULLABLE updated_policy_len,
+                        const char *ABE_NULLABLE current_policy_ptr,
+                        int current_policy_len,
+                        const uint16_t *ABE_NULLABLE axis_ptr);
+
+/**
+ * UTF-16 variant of `h_policy_rotate_attribute`.
+ *
+ * # Safety
+ */
+int h_policy_rotate_attribute_w(char *ABE_NULLABLE updated_policy_ptr,
+                                int *ABE_NULLABLE updated_policy_len,
+                                const char *ABE_NULLABLE current_policy_ptr,
+                                int current_policy_len,
+                                const uint16_t *ABE_NULLABLE attribute_ptr);
+
+/**
+ * UTF-16 variant of `h_policy_attribute_values`.
+ *
+ * # Safety
+ */
+int h_policy_attribute_values_w(char *ABE_NULLABLE values_ptr,
+                                int *ABE_NULLABLE values_len,
+                                const char *ABE_NULLABLE policy_ptr,
+                                int policy_len,
+                                const uint16_t *ABE_NULLABLE attribute_ptr);
+
+/**
+ * UTF-16 variant of `h_policy_attributes`.
+ *
+ * `axis_ptr` may be null to list all axes.
+ *
+ * # Safety
+ */
+int h_policy_attributes_w(char *ABE_NULLABLE attributes_ptr,
+                          int *ABE_NULLABLE attributes_len,
+                          const char *ABE_NULLABLE policy_ptr,
+                          int policy_len,
+                          const uint16_t *ABE_NULLABLE axis_ptr);
+
+/**
+ * UTF-16 variant of `h_policy_attribute_current_value`.
+ *
+ * # Safety
+ */
+int h_policy_attribute_current_value_w(unsigned int *ABE_NULLABLE value,
+                                       const char *ABE_NULLABLE policy_ptr,
+                                       int policy_len,
+                                       const uint16_t *ABE_NULLABLE attribute_ptr);
+
+/**
+ * UTF-16 variant of `h_rotate_attributes`.
+ *
+ * # Safety
+ */
+int h_rotate_attributes_w(char *ABE_NULLABLE updated_policy_ptr,
+                          int *ABE_NULLABLE updated_policy_len,
+                          const char *ABE_NULLABLE current_policy_ptr,
+                          int current_policy_len,
+                          const uint16_t *ABE_NULLABLE attributes_ptr);
+
+/**
+ * UTF-16 variant of `h_policy_rotate_attributes`.
+ *
+ * # Safety
+ */
+int h_policy_rotate_attributes_w(char *ABE_NULLABLE updated_policy_ptr,
+                                 int *ABE_NULLABLE updated_policy_len,
+                                 char *ABE_NULLABLE new_values_ptr,
+                                 int *ABE_NULLABLE new_values_len,
+                                 const char *ABE_NULLABLE current_policy_ptr,
+                                 int current_policy_len,
+                                 const uint16_t *ABE_NULLABLE attributes_ptr);
+
+/**
+ * UTF-16 variant of `h_policy_handle_add_axis`.
+ *
+ * # Safety
+ */
+int h_policy_handle_add_axis_w(PolicyHandle handle, const uint16_t *ABE_NULLABLE axis_ptr);
+
+/**
+ * UTF-16 variant of `h_policy_handle_rotate_attribute`.
+ *
+ * # Safety
+ */
+int h_policy_handle_rotate_attribute_w(PolicyHandle handle,
+                                       const uint16_t *ABE_NULLABLE attribute_ptr);
+
+/**
+ * UTF-16 variant of `h_policy_handle_attribute_current_value`.
+ *
+ * # Safety
+ */
+int h_policy_handle_attribute_current_value_w(unsigned int *ABE_NULLABLE value,
+                                              PolicyHandle handle,
+                                              const uint16_t *ABE_NULLABLE attribute_ptr);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* ABE_POLICY_H */
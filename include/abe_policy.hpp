@@ -0,0 +1,169 @@
+/* ABE policy C++ interface: RAII wrappers of the C interface declared in
+ * abe_policy.h. Requires C++11. */
+
+#ifndef ABE_POLICY_HPP
+#define ABE_POLICY_HPP
+
+#include <stdexcept>
+#include <string>
+#include <utility>
+
+#include "abe_policy.h"
+
+namespace abe_policy {
+
+/* Error reported by the library, carrying the status code of the failing
+ * call, e.g. FFI_ERROR or FFI_PANIC. */
+class Error : public std::runtime_error {
+public:
+    Error(int code, const std::string &message) : std::runtime_error(message), code_(code) {}
+
+    int code() const noexcept { return code_; }
+
+private:
+    int code_;
+};
+
+namespace detail {
+
+/* Throws the last error of this thread if `code` is not FFI_OK. */
+inline void check(int code) {
+    if (code == FFI_OK) {
+        return;
+    }
+    int len = 0;
+    std::string message;
+    if (get_last_error(nullptr, &len) == FFI_BUFFER_TOO_SMALL && len > 0) {
+        message.resize(static_cast<std::string::size_type>(len));
+        if (get_last_error(&message[0], &len) == FFI_OK) {
+            message.resize(message.find('\0'));
+        } else {
+            message.clear();
+        }
+    }
+    throw Error(code, message.empty() ? "unknown error" : message);
+}
+
+/* Calls an `_alloc` function, copying the buffer it allocates before
+ * releasing it. */
+template <typename F>
+std::string allocating(F &&f) {
+    char *buffer = nullptr;
+    int len = 0;
+    check(f(&buffer, &len));
+    std::string bytes;
+    if (buffer != nullptr) {
+        bytes.assign(buffer, static_cast<std::string::size_type>(len));
+        h_free_buffer(buffer, len);
+    }
+    return bytes;
+}
+
+}  // namespace detail
+
+/* Access policy, held as its JSON serialization and combined with the `&`
+ * and `|` operators. */
+class AccessPolicy {
+public:
+    /* Parses a boolean expression such as `Department::HR && Level::Low`. */
+    static AccessPolicy parse(const std::string &boolean_expression) {
+        return AccessPolicy(detail::allocating([&](char **ptr, int *len) {
+            return h_parse_boolean_access_policy_alloc(ptr, len, boolean_expression.c_str());
+        }));
+    }
+
+    /* Wraps a JSON access policy. */
+    static AccessPolicy from_json(std::string json) { return AccessPolicy(std::move(json)); }
+
+    const std::string &json() const noexcept { return json_; }
+
+    std::string to_boolean_expression() const {
+        return detail::allocating([&](char **ptr, int *len) {
+            return h_access_policy_to_boolean_expression_alloc(ptr, len, json_.c_str());
+        });
+    }
+
+    friend AccessPolicy operator&(const AccessPolicy &lhs, const AccessPolicy &rhs) {
+        return AccessPolicy("{\"And\":[" + lhs.json_ + "," + rhs.json_ + "]}");
+    }
+
+    friend AccessPolicy operator|(const AccessPolicy &lhs, const AccessPolicy &rhs) {
+        return AccessPolicy("{\"Or\":[" + lhs.json_ + "," + rhs.json_ + "]}");
+    }
+
+private:
+    explicit AccessPolicy(std::string json) : json_(std::move(json)) {}
+
+    std::string json_;
+};
+
+/* Policy opened in the library, closed when the object is destroyed. */
+class Policy {
+public:
+    /* Creates an empty policy allowing the given number of attribute
+     * creations (additions + rotations). */
+    explicit Policy(int max_attribute_creations) : handle_(0) {
+        open(detail::allocating([&](char **ptr, int *len) {
+            return h_policy_new_alloc(ptr, len, max_attribute_creations);
+        }));
+    }
+
+    /* Opens a JSON policy, converting it from a legacy format if needed. */
+    static Policy from_json(const std::string &json) {
+        Policy policy;
+        policy.open(json);
+        return policy;
+    }
+
+    Policy(const Policy &) = delete;
+    Policy &operator=(const Policy &) = delete;
+
+    Policy(Policy &&other) noexcept : handle_(other.handle_) { other.handle_ = 0; }
+
+    Policy &operator=(Policy &&other) noexcept {
+        std::swap(handle_, other.handle_);
+        return *this;
+    }
+
+    ~Policy() {
+        if (handle_ != 0) {
+            h_policy_close(handle_);
+        }
+    }
+
+    /* Adds an axis given as JSON. */
+    void add_axis(const std::string &axis) {
+        detail::check(h_policy_handle_add_axis(handle_, axis.c_str()));
+    }
+
+    /* Rotates an attribute, written `Axis::Name`. */
+    void rotate(const std::string &attribute) {
+        detail::check(h_policy_handle_rotate_attribute(handle_, attribute.c_str()));
+    }
+
+    unsigned int attribute_current_value(const std::string &attribute) const {
+        unsigned int value = 0;
+        detail::check(h_policy_handle_attribute_current_value(&value, handle_, attribute.c_str()));
+        return value;
+    }
+
+    std::string to_json() const {
+        return detail::allocating(
+            [&](char **ptr, int *len) { return h_policy_handle_to_json_alloc(ptr, len, handle_); });
+    }
+
+    PolicyHandle handle() const noexcept { return handle_; }
+
+private:
+    Policy() : handle_(0) {}
+
+    void open(const std::string &json) {
+        detail::check(h_policy_open(&handle_, json.data(), static_cast<int>(json.size())));
+    }
+
+    PolicyHandle handle_;
+};
+
+}  // namespace abe_policy
+
+#endif /* ABE_POLICY_HPP */
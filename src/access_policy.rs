@@ -1,15 +1,27 @@
-use crate::{policy::Policy, Attribute, Error};
-use serde::{Deserialize, Serialize};
-use std::{
-    collections::HashMap,
-    fmt::Debug,
+use crate::{
+    expression::{parse_attribute, Escaped, Token, Tokens},
+    policy::Policy,
+    Attribute, AttributeId, Error, HashMap,
+};
+use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::{
+    fmt::{Debug, Display},
     ops::{BitAnd, BitOr},
 };
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 /// An `AccessPolicy` is a boolean expression over attributes.
 ///
 /// Only `positive` literals are allowed (no negation).
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum AccessPolicy {
     Attr(Attribute),
     And(Box<AccessPolicy>, Box<AccessPolicy>),
@@ -66,8 +78,13 @@ impl AccessPolicy {
                     max
                 }
             }
-            Self::And(l, r) => l.to_u32(attribute_mapping) * r.to_u32(attribute_mapping),
-            Self::Or(l, r) => l.to_u32(attribute_mapping) + r.to_u32(attribute_mapping),
+            // large expressions wrap around
+            Self::And(l, r) => l
+                .to_u32(attribute_mapping)
+                .wrapping_mul(r.to_u32(attribute_mapping)),
+            Self::Or(l, r) => l
+                .to_u32(attribute_mapping)
+                .wrapping_add(r.to_u32(attribute_mapping)),
             Self::All => 0,
         }
     }
@@ -80,13 +97,10 @@ impl AccessPolicy {
     /// use std::collections::HashMap;
     /// use abe_policy::AccessPolicy;
     ///
-    /// let axes = serde_json::from_str(
-    ///     r#"{
-    ///     "Department": ["HR","FIN"],
-    ///     "Level": ["level_2"]
-    /// }"#
-    /// )
-    /// .unwrap();
+    /// let axes = HashMap::from([
+    ///     ("Department".to_string(), vec!["HR".to_string(), "FIN".to_string()]),
+    ///     ("Level".to_string(), vec!["level_2".to_string()]),
+    /// ]);
     ///
     /// let access_policy = AccessPolicy::from_axes(&axes);
     /// assert_eq!(
@@ -116,130 +130,14 @@ impl AccessPolicy {
         Ok(access_policy)
     }
 
-    /// This function is finding the right closing parenthesis in the boolean
-    /// expression given as a string
-    fn find_next_parenthesis(boolean_expression: &str) -> Result<usize, Error> {
-        let mut count = 0;
-        let mut right_closing_parenthesis = None;
-        for (index, c) in boolean_expression.chars().enumerate() {
-            match c {
-                '(' => count += 1,
-                ')' => count -= 1,
-                _ => {}
-            };
-            if count < 0 {
-                right_closing_parenthesis = Some(index);
-                break;
-            }
-        }
-
-        right_closing_parenthesis.ok_or_else(|| {
-            Error::InvalidBooleanExpression(format!(
-                "Missing closing parenthesis in boolean expression {boolean_expression}"
-            ))
-        })
-    }
-
-    /// Sanitizes spaces in boolean expression around parenthesis and operators
-    /// but keep spaces inside axis & attribute names.
+    /// Converts a boolean expression into `AccessPolicy`.
     ///
-    /// Useless spaces are removed:
-    /// - before and after operator. Example: `A && B` --> `A&&B`
-    /// - before and after parenthesis. Example: `(A && B)` --> `(A&&B)`
-    /// - But keep these spaces: `(A::b c || d e::F)` --> `(A::b c||d e::F)`
-    fn sanitize_spaces(boolean_expression: &str) -> String {
-        let trim_closure = |expr: &str, separator: &str| -> String {
-            let expression = expr
-                .split(separator)
-                .collect::<Vec<_>>()
-                .into_iter()
-                .map(str::trim)
-                .collect::<Vec<_>>();
-            let mut expression_chars = Vec::<char>::new();
-            for (i, s) in expression.iter().enumerate() {
-                if i == 0 && s.is_empty() {
-                    expression_chars.append(&mut separator.chars().collect::<Vec<_>>());
-                } else {
-                    expression_chars.append(&mut s.chars().collect::<Vec<_>>());
-                    if i != expression.len() - 1 {
-                        expression_chars.append(&mut separator.chars().collect::<Vec<_>>());
-                    }
-                }
-            }
-            expression_chars.iter().collect::<String>()
-        };
-
-        // Remove successively spaces around `special` substrings
-        let mut output = boolean_expression.to_string();
-        for sep in ["(", ")", "||", "&&", "::"] {
-            output = trim_closure(output.as_str(), sep);
-        }
-
-        output
-    }
-
-    /// This function takes a boolean expression and splits it into a left part,
-    /// an operator and a right part.
+    /// Operators have the same precedence and are applied from right to left:
+    /// `A && B || C` is `A && (B || C)`. The expression is parsed in a single
+    /// pass, in linear time.
     ///
-    /// Example: "`Department::HR` && `Level::level_2`" will be decomposed in:
-    /// - `Department::HR`
-    /// - &&
-    /// - `Level::level_2`
-    fn decompose_expression(
-        boolean_expression: &str,
-        split_position: usize,
-    ) -> Result<(String, Option<String>, Option<String>), Error> {
-        /// Number of characters of an `AccessPolicy` operator.
-        /// Possible operators are: '||' and '&&'.
-        const OPERATOR_SIZE: usize = 2;
-
-        if split_position > boolean_expression.len() {
-            return Err(Error::InvalidBooleanExpression(format!(
-                "Cannot split boolean expression {boolean_expression} at position \
-                 {split_position} since {split_position} is greater than the size of \
-                 {boolean_expression}"
-            )));
-        }
-
-        // Put aside `Department::HR` from `Department::HR && Level::level_2`
-        let left_part = &boolean_expression[..split_position];
-        if split_position == boolean_expression.len() {
-            return Ok((left_part.to_string(), None, None));
-        }
-
-        // Put aside `&&` from `Department::HR && Level::level_2`
-        let next_char = boolean_expression
-            .chars()
-            .nth(split_position)
-            .unwrap_or_default();
-        let mut split_position = split_position;
-        if next_char == ')' {
-            split_position += 1;
-        }
-        if split_position == boolean_expression.len() {
-            return Ok((left_part.to_string(), None, None));
-        }
-        if split_position + OPERATOR_SIZE > boolean_expression.len() {
-            return Err(Error::InvalidBooleanExpression(format!(
-                "Cannot split boolean expression {boolean_expression} at position \
-                 {} since it is greater than the size of \
-                 {boolean_expression}",
-                split_position + OPERATOR_SIZE
-            )));
-        }
-
-        let operator = &boolean_expression[split_position..split_position + OPERATOR_SIZE];
-
-        // Put aside `Level::level_2` from `Department::HR && Level::level_2`
-        let right_part = &boolean_expression[split_position + OPERATOR_SIZE..];
-        Ok((
-            left_part.to_string(),
-            Some(operator.to_string()),
-            Some(right_part.to_string()),
-        ))
-    }
-
-    /// Converts a boolean expression into `AccessPolicy`.
+    /// In attributes, a `\` escapes the next parenthesis, `&`, `|`, `:` or
+    /// `\`, as written by `Display`.
     ///
     /// # Arguments
     ///
@@ -266,99 +164,49 @@ impl AccessPolicy {
     /// Missing parenthesis or bad operators
     pub fn from_boolean_expression(boolean_expression: &str) -> Result<Self, Error> {
         let boolean_expression_example = "(Department::HR || Department::RnD) && Level::level_2";
+        let invalid = |message: String| {
+            Error::InvalidBooleanExpression(format!(
+                "{message} in '{boolean_expression}'. Example: {boolean_expression_example}"
+            ))
+        };
 
-        // Remove spaces around parenthesis and operators
-        let boolean_expression = Self::sanitize_spaces(boolean_expression);
-
-        if !boolean_expression.contains("::") {
-            return Err(Error::InvalidBooleanExpression(format!(
-                "'{boolean_expression}' does not contain any attribute separator '::'. Example: \
-                 {boolean_expression_example}"
-            )));
-        }
-
-        // if first char is parenthesis
-        let first_char = boolean_expression.chars().next().unwrap_or_default();
-        if first_char == '(' {
-            // Skip first parenthesis
-            let boolean_expression = &boolean_expression[1..];
-            // Check if formula contains a closing parenthesis
-            let c = boolean_expression.matches(')').count();
-            if c == 0 {
-                return Err(Error::InvalidBooleanExpression(format!(
-                    "closing parenthesis missing in {boolean_expression}"
-                )));
-            }
-            // Search right closing parenthesis, avoiding false positive
-            let matching_closing_parenthesis = Self::find_next_parenthesis(boolean_expression)?;
-            let (left_part, operator, right_part) =
-                Self::decompose_expression(boolean_expression, matching_closing_parenthesis)?;
-            if operator.is_none() {
-                return Self::from_boolean_expression(left_part.as_str());
-            }
-
-            let operator = operator.unwrap_or_default();
-            let right_part = right_part.unwrap_or_default();
-            let ap1 = Box::new(Self::from_boolean_expression(left_part.as_str())?);
-            let ap2 = Box::new(Self::from_boolean_expression(right_part.as_str())?);
-            let ap = match operator.as_str() {
-                "&&" => Ok(Self::And(ap1, ap2)),
-                "||" => Ok(Self::Or(ap1, ap2)),
-                _ => Err(Error::UnsupportedOperator(operator.to_string())),
-            }?;
-            Ok(ap)
-        } else {
-            let or_position = boolean_expression.find("||");
-            let and_position = boolean_expression.find("&&");
-
-            // Get position of next operator
-            let position = if or_position.is_none() && and_position.is_none() {
-                0
-            } else if or_position.is_none() {
-                and_position.unwrap_or_default()
-            } else if and_position.is_none() {
-                or_position.unwrap_or_default()
-            } else {
-                std::cmp::min(
-                    or_position.unwrap_or_default(),
-                    and_position.unwrap_or_default(),
-                )
-            };
-
-            if position == 0 {
-                let attribute_vec = boolean_expression.split("::").collect::<Vec<_>>();
-
-                if attribute_vec.len() != 2
-                    || attribute_vec[0].is_empty()
-                    || attribute_vec[1].is_empty()
-                {
-                    return Err(Error::InvalidBooleanExpression(format!(
-                        "'{boolean_expression}' does not respect the format <axis::name>. \
-                         Example: {boolean_expression_example}"
-                    )));
+        // groups of the opened parentheses, the innermost one last
+        let mut groups = vec![Group::default()];
+        let mut expect_operand = true;
+        for (offset, text, token) in Tokens::new(boolean_expression) {
+            // `groups` is never empty: its first group is not closed
+            let group = groups.len() - 1;
+            match (token, expect_operand) {
+                (Token::Open, true) => groups.push(Group::default()),
+                (Token::Attribute, true) => {
+                    let attribute = parse_attribute(text).map_err(|_| {
+                        invalid(format!("'{text}' does not respect the format <axis::name>"))
+                    })?;
+                    groups[group].operands.push(Self::Attr(attribute));
+                    expect_operand = false;
                 }
-                return Ok(Self::new(attribute_vec[0], attribute_vec[1]));
-            }
-
-            // Remove operator from input string
-            let (left_part, operator, right_part) =
-                Self::decompose_expression(&boolean_expression, position)?;
-            if operator.is_none() {
-                return Self::from_boolean_expression(left_part.as_str());
+                (Token::And, false) => {
+                    groups[group].operators.push(Self::And);
+                    expect_operand = true;
+                }
+                (Token::Or, false) => {
+                    groups[group].operators.push(Self::Or);
+                    expect_operand = true;
+                }
+                (Token::Close, false) if group > 0 => {
+                    let access_policy = groups.remove(group).fold();
+                    groups[group - 1].operands.push(access_policy);
+                }
+                _ => return Err(invalid(format!("unexpected '{text}' at offset {offset}"))),
             }
-            let operator = operator.unwrap_or_default();
-            let right_part = right_part.unwrap_or_default();
-
-            let ap1 = Box::new(Self::from_boolean_expression(left_part.as_str())?);
-            let ap2 = Box::new(Self::from_boolean_expression(right_part.as_str())?);
-            let ap = match operator.as_str() {
-                "&&" => Ok(Self::And(ap1, ap2)),
-                "||" => Ok(Self::Or(ap1, ap2)),
-                _ => Err(Error::UnsupportedOperator(operator.to_string())),
-            }?;
-
-            Ok(ap)
         }
+        if expect_operand {
+            return Err(invalid("missing attribute".to_string()));
+        }
+        if groups.len() > 1 {
+            return Err(invalid("closing parenthesis missing".to_string()));
+        }
+        Ok(groups.remove(0).fold())
     }
 
     /// Retrieves all the attributes present in this access policy.
@@ -366,22 +214,23 @@ impl AccessPolicy {
     /// The attributes are sorted. This is useful for comparisons.
     #[must_use]
     pub fn attributes(&self) -> Vec<Attribute> {
-        let mut attributes = self._attributes();
+        let mut attributes = Vec::new();
+        self.attributes_into(&mut attributes);
         attributes.sort();
         attributes
     }
 
-    fn _attributes(&self) -> Vec<Attribute> {
-        match self {
-            Self::Attr(att) => vec![att.clone()],
-            Self::And(a1, a2) | Self::Or(a1, a2) => {
-                let mut v = Self::_attributes(a1);
-                v.extend(Self::_attributes(a2));
-                v
-            }
+    /// Appends all the attributes present in this access policy to the given
+    /// buffer, in their order of appearance, so that the buffer can be reused
+    /// across access policies.
+    pub fn attributes_into(&self, attributes: &mut Vec<Attribute>) {
+        attributes.reserve(self.iter_attributes().count());
+        attributes.extend(self.iter_attributes().cloned());
+    }
 
-            Self::All => vec![],
-        }
+    /// Iterates over the attributes of this access policy, from left to right.
+    fn iter_attributes(&self) -> AttributeIter<'_> {
+        AttributeIter { stack: vec![self] }
     }
 
     /// Returns the list of attribute combinations that can be built from the
@@ -396,38 +245,35 @@ impl AccessPolicy {
     ) -> Result<Vec<Vec<Attribute>>, Error> {
         match self {
             Self::Attr(attr) => {
-                let axis_parameters = policy
-                    .axes
-                    .get(&attr.axis)
-                    .ok_or_else(|| Error::InvalidAxis(attr.axis.clone()))?;
-                let mut res = vec![vec![attr.clone()]];
-                if axis_parameters.is_hierarchical && follow_hierarchical_axes {
+                let axis = policy
+                    .axis(&attr.axis)
+                    .ok_or_else(|| Error::InvalidAxis(attr.axis.to_string()))?;
+                let attribute = policy.find_attribute(attr.as_attribute_ref());
+                let id = attribute.map(|attribute| attribute.id());
+                let attr = attribute.map_or_else(|| attr.clone(), |attr| attr.to_attribute());
+                let mut res = vec![vec![attr]];
+                if axis.is_hierarchical() && follow_hierarchical_axes {
                     // add attribute values for all attributes below the given one
-                    for name in &axis_parameters.attribute_names {
-                        if *name == attr.name {
-                            break;
-                        }
-                        res.push(vec![Attribute::new(&attr.axis, name)]);
-                    }
+                    res.extend(
+                        axis.attributes()
+                            .take_while(|lower| Some(lower.id()) != id)
+                            .map(|lower| vec![lower.to_attribute()]),
+                    );
                 }
                 Ok(res)
             }
-            Self::And(ap_left, ap_right) => {
-                let combinations_left =
-                    ap_left.to_attribute_combinations(policy, follow_hierarchical_axes)?;
-                let combinations_right =
-                    ap_right.to_attribute_combinations(policy, follow_hierarchical_axes)?;
-                let mut res =
-                    Vec::with_capacity(combinations_left.len() * combinations_right.len());
-                for value_left in combinations_left {
-                    for value_right in &combinations_right {
-                        let mut combined = Vec::with_capacity(value_left.len() + value_right.len());
-                        combined.extend_from_slice(&value_left);
-                        combined.extend_from_slice(value_right);
-                        res.push(combined)
-                    }
-                }
-                Ok(res)
+            Self::And(..) => {
+                // combine all the operands of a chain of conjunctions at once,
+                // instead of copying the combinations of each intermediate one
+                let mut operands = Vec::new();
+                self.push_and_operands(&mut operands);
+                let operands = operands
+                    .iter()
+                    .map(|operand| {
+                        operand.to_attribute_combinations(policy, follow_hierarchical_axes)
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(concatenate_combinations(&operands))
             }
             Self::Or(ap_left, ap_right) => {
                 let combinations_left =
@@ -443,6 +289,241 @@ impl AccessPolicy {
             Self::All => Ok(vec![vec![]]),
         }
     }
+
+    /// Pushes the operands of the conjunctions of this access policy, from left
+    /// to right.
+    fn push_and_operands<'a>(&'a self, operands: &mut Vec<&'a Self>) {
+        match self {
+            Self::And(ap_left, ap_right) => {
+                ap_left.push_and_operands(operands);
+                ap_right.push_and_operands(operands);
+            }
+            _ => operands.push(self),
+        }
+    }
+
+    /// Returns `true` if a user holding the given attributes is granted
+    /// access by this access policy.
+    ///
+    /// On a hierarchical axis, an attribute also grants access to the
+    /// attributes below it. The attributes of the access policy and the given
+    /// ones must belong to the policy.
+    pub fn is_satisfied_by(
+        &self,
+        policy: &Policy,
+        attributes: &[Attribute],
+    ) -> Result<bool, Error> {
+        let attributes = attributes
+            .iter()
+            .map(|attribute| policy.attribute_id(attribute))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.is_satisfied(policy, &attributes)
+    }
+
+    fn is_satisfied(&self, policy: &Policy, attributes: &[AttributeId]) -> Result<bool, Error> {
+        match self {
+            Self::Attr(attr) => {
+                let id = policy.attribute_id(attr)?;
+                let axis = policy
+                    .axis_by_id(id.axis)
+                    .ok_or_else(|| Error::InvalidAxis(attr.axis.to_string()))?;
+                if !axis.is_hierarchical() {
+                    return Ok(attributes.contains(&id));
+                }
+                let rank =
+                    |id: &AttributeId| axis.attribute_ids().iter().position(|other| other == id);
+                let required_rank = rank(&id);
+                Ok(attributes
+                    .iter()
+                    .any(|attribute| attribute.axis == id.axis && rank(attribute) >= required_rank))
+            }
+            // Both operands are evaluated to report unknown attributes.
+            Self::And(ap_left, ap_right) => Ok(ap_left.is_satisfied(policy, attributes)?
+                & ap_right.is_satisfied(policy, attributes)?),
+            Self::Or(ap_left, ap_right) => Ok(ap_left.is_satisfied(policy, attributes)?
+                | ap_right.is_satisfied(policy, attributes)?),
+            Self::All => Ok(true),
+        }
+    }
+}
+
+/// Depth-first iterator over the attributes of an access policy, whose stack
+/// holds the right operands left to visit.
+struct AttributeIter<'a> {
+    stack: Vec<&'a AccessPolicy>,
+}
+
+impl<'a> Iterator for AttributeIter<'a> {
+    type Item = &'a Attribute;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut node = self.stack.pop()?;
+        loop {
+            match node {
+                AccessPolicy::Attr(attribute) => return Some(attribute),
+                AccessPolicy::And(left, right) | AccessPolicy::Or(left, right) => {
+                    self.stack.push(right);
+                    node = left;
+                }
+                AccessPolicy::All => node = self.stack.pop()?,
+            }
+        }
+    }
+}
+
+/// Constructor of a binary access policy, `AccessPolicy::And` or
+/// `AccessPolicy::Or`.
+type Operator = fn(Box<AccessPolicy>, Box<AccessPolicy>) -> AccessPolicy;
+
+/// Operands of a parenthesized part of a boolean expression and the operators
+/// between them.
+#[derive(Default)]
+struct Group {
+    operands: Vec<AccessPolicy>,
+    operators: Vec<Operator>,
+}
+
+impl Group {
+    /// Applies the operators from right to left, with the same precedence.
+    ///
+    /// The group must hold one more operand than operators.
+    fn fold(mut self) -> AccessPolicy {
+        let mut access_policy = self.operands.pop().unwrap_or(AccessPolicy::All);
+        while let (Some(operator), Some(operand)) = (self.operators.pop(), self.operands.pop()) {
+            access_policy = operator(Box::new(operand), Box::new(access_policy));
+        }
+        access_policy
+    }
+}
+
+/// Returns every concatenation of one combination of each operand, the
+/// combinations of the last operand varying fastest.
+///
+/// The concatenation of the combinations of the first operands is kept and
+/// only updated from the first operand whose combination changes.
+fn concatenate_combinations(operands: &[Vec<Vec<Attribute>>]) -> Vec<Vec<Attribute>> {
+    let len = operands.iter().map(Vec::len).product();
+    #[cfg(feature = "parallel")]
+    if len >= PARALLEL_THRESHOLD {
+        return concatenate_combinations_in_parallel(operands, len);
+    }
+    let mut res = Vec::with_capacity(len);
+    if len == 0 {
+        return res;
+    }
+    let Some((last, operands)) = operands.split_last() else {
+        res.push(Vec::new());
+        return res;
+    };
+
+    let mut indices = vec![0; operands.len()];
+    let mut prefix = Vec::new();
+    // length of the prefix after the combination of each first operand
+    let mut ends = Vec::with_capacity(operands.len());
+    let mut changed = 0;
+    loop {
+        prefix.truncate(if changed == 0 { 0 } else { ends[changed - 1] });
+        ends.truncate(changed);
+        for (index, combinations) in indices.iter().zip(operands).skip(changed) {
+            prefix.extend_from_slice(&combinations[*index]);
+            ends.push(prefix.len());
+        }
+        for combination in last {
+            let mut combined = Vec::with_capacity(prefix.len() + combination.len());
+            combined.extend_from_slice(&prefix);
+            combined.extend_from_slice(combination);
+            res.push(combined);
+        }
+
+        // move to the next combination of the last first operand, wrapping
+        // around to the next combination of the previous ones
+        changed = operands.len();
+        loop {
+            if changed == 0 {
+                return res;
+            }
+            changed -= 1;
+            indices[changed] += 1;
+            if indices[changed] < operands[changed].len() {
+                break;
+            }
+            indices[changed] = 0;
+        }
+    }
+}
+
+/// Number of combinations from which they are built in parallel.
+#[cfg(feature = "parallel")]
+const PARALLEL_THRESHOLD: usize = 1 << 12;
+
+/// Builds the same combinations as `concatenate_combinations()`, each one
+/// from its index in the result.
+#[cfg(feature = "parallel")]
+fn concatenate_combinations_in_parallel(
+    operands: &[Vec<Vec<Attribute>>],
+    len: usize,
+) -> Vec<Vec<Attribute>> {
+    use rayon::prelude::*;
+
+    // number of consecutive combinations sharing the same combination of
+    // each operand, the last operand varying fastest
+    let mut strides = vec![1; operands.len()];
+    for i in (1..operands.len()).rev() {
+        strides[i - 1] = strides[i] * operands[i].len();
+    }
+    (0..len)
+        .into_par_iter()
+        .map(|index| {
+            let selected = || {
+                operands
+                    .iter()
+                    .zip(&strides)
+                    .map(move |(combinations, stride)| {
+                        &combinations[index / stride % combinations.len()]
+                    })
+            };
+            let mut combined = Vec::with_capacity(selected().map(Vec::len).sum());
+            for combination in selected() {
+                combined.extend_from_slice(combination);
+            }
+            combined
+        })
+        .collect()
+}
+
+/// Writes the access policy as a boolean expression parsable by
+/// `AccessPolicy::from_boolean_expression`, e.g.
+/// `(Department::HR || Department::FIN) && Security Level::Confidential`.
+///
+/// Compound left operands are parenthesized, as are right operands using a
+/// different operator than their parent. Parentheses, operators, `:` and `\`
+/// in axes and names are escaped with a `\`. `AccessPolicy::All` is written
+/// `*`, which is not a valid boolean expression.
+impl Display for AccessPolicy {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let (left, operator, right) = match self {
+            Self::Attr(attribute) => {
+                return write!(
+                    f,
+                    "{}::{}",
+                    Escaped(&attribute.axis),
+                    Escaped(&attribute.name)
+                )
+            }
+            Self::All => return write!(f, "*"),
+            Self::And(left, right) => (left, "&&", right),
+            Self::Or(left, right) => (left, "||", right),
+        };
+        match **left {
+            Self::And(..) | Self::Or(..) => write!(f, "({left})")?,
+            _ => write!(f, "{left}")?,
+        }
+        write!(f, " {operator} ")?;
+        match (self, &**right) {
+            (Self::And(..), Self::Or(..)) | (Self::Or(..), Self::And(..)) => write!(f, "({right})"),
+            _ => write!(f, "{right}"),
+        }
+    }
 }
 
 // use A & B to construct And(A, B)
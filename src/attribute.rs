@@ -1,81 +1,535 @@
-use crate::Error;
-use serde::{Deserialize, Serialize};
-use std::{convert::TryFrom, fmt::Debug, ops::Deref};
+use crate::{AxisName, Error};
+use alloc::{
+    borrow::Cow,
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    sync::Arc,
+    vec::Vec,
+};
+use core::{
+    borrow::Borrow,
+    convert::TryFrom,
+    fmt::Debug,
+    hash::{Hash, Hasher},
+    ops::Deref,
+    str::FromStr,
+};
+#[cfg(feature = "serde")]
+use serde::{
+    de::{self, MapAccess, SeqAccess, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
 
 /// An attribute in a policy group is characterized by the axis policy name
 /// and its unique name within this axis.
-#[derive(Hash, PartialEq, Eq, Clone, PartialOrd, Ord, Serialize, Deserialize)]
-#[serde(try_from = "&str", into = "String")]
+///
+/// The axis and the name are reference-counted: cloning an attribute does not
+/// copy them, and the attributes of a policy share the name of their axis.
+///
+/// An attribute is serialized as an `Axis::Name` string, and can be
+/// deserialized either from this string or from a `{ "axis": ..., "name": ...
+/// }` structure. See `attribute_serde` to serialize it as a structure.
+#[derive(Hash, PartialEq, Eq, Clone, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(into = "String"))]
 pub struct Attribute {
-    pub axis: String,
-    pub name: String,
+    pub axis: AxisName,
+    pub name: Arc<str>,
 }
 
 impl Attribute {
     /// Create a Policy Attribute.
     ///
+    /// The axis and the name are not checked: this is meant to look up the
+    /// attributes of a policy. Use `try_new` to create an attribute from user
+    /// input.
+    ///
     /// - `axis`    : policy axis the attributes belongs to
     /// - `name`    : unique attribute name within this axis
     #[must_use]
     pub fn new(axis: &str, name: &str) -> Self {
         Self {
-            axis: axis.to_owned(),
-            name: name.to_owned(),
+            axis: AxisName::new_unchecked(axis),
+            name: name.into(),
+        }
+    }
+
+    /// Create a Policy Attribute sharing the given axis name.
+    ///
+    /// - `axis`    : policy axis the attributes belongs to
+    /// - `name`    : unique attribute name within this axis
+    #[must_use]
+    pub fn with_shared_axis(axis: &AxisName, name: &str) -> Self {
+        Self {
+            axis: axis.clone(),
+            name: name.into(),
         }
     }
+
+    /// Creates an attribute from serialized data, whose axis and name are
+    /// kept as is but must not be empty.
+    #[cfg(feature = "serde")]
+    pub(crate) fn from_serialized(axis: &str, name: &str) -> Result<Self, Error> {
+        if axis.trim().is_empty() || name.trim().is_empty() {
+            return Err(Error::InvalidAttribute(format!(
+                "empty axis or empty name in {axis}::{name}"
+            )));
+        }
+        Ok(Self::new(axis, name))
+    }
+
+    /// Create a Policy Attribute from user input, checking that both the axis
+    /// and the name are non-empty and do not contain the separator `::`.
+    ///
+    /// The axis and the name are canonicalized: surrounding whitespace is
+    /// removed and each sequence of whitespace characters is replaced by a
+    /// single space. Use `new` to keep them as is.
+    ///
+    /// Use `FromStr` to create an attribute containing an escaped separator.
+    ///
+    /// - `axis`    : policy axis the attributes belongs to
+    /// - `name`    : unique attribute name within this axis
+    pub fn try_new(axis: &str, name: &str) -> Result<Self, Error> {
+        let (axis, name) = (canonicalize(axis), canonicalize(name));
+        if axis.is_empty() || name.is_empty() {
+            return Err(Error::InvalidAttribute(format!(
+                "empty axis or empty name in {axis}::{name}"
+            )));
+        }
+        if axis.contains("::") || name.contains("::") {
+            return Err(Error::InvalidAttribute(format!(
+                "separator '::' expected only once in {axis}::{name}"
+            )));
+        }
+        Ok(Self::new(&axis, &name))
+    }
+
+    /// Creates an attribute whose axis and name are kept as is, checking that
+    /// the axis name is valid and the name is not empty.
+    fn try_new_exact(axis: &str, name: &str) -> Result<Self, Error> {
+        if name.trim().is_empty() {
+            return Err(Error::InvalidAttribute(format!(
+                "empty name in {axis}::{name}"
+            )));
+        }
+        Ok(Self {
+            axis: AxisName::new_exact(axis)?,
+            name: name.into(),
+        })
+    }
+
+    /// Parses an attribute written `Axis::Name` as `FromStr` does, without
+    /// canonicalizing the axis and the name.
+    ///
+    /// This is the inverse of `Display`.
+    pub fn parse_exact(s: &str) -> Result<Self, Error> {
+        Self::parse(s, false)
+    }
+
+    fn parse(s: &str, canonical: bool) -> Result<Self, Error> {
+        let mut parts = [String::new(), String::new()];
+        let mut part = 0;
+        let mut chars = s.chars().peekable();
+        while let Some(c) = chars.next() {
+            match (c, chars.peek()) {
+                ('\\', Some(&next)) if next == '\\' || next == ':' => {
+                    parts[part].push(next);
+                    chars.next();
+                }
+                (':', Some(':')) => {
+                    if part == 1 {
+                        return Err(Error::InvalidAttribute(format!(
+                            "separator '::' expected only once in {s}"
+                        )));
+                    }
+                    part = 1;
+                    chars.next();
+                }
+                _ => parts[part].push(c),
+            }
+        }
+        if part == 0 {
+            return Err(Error::InvalidAttribute(format!(
+                "at least one separator '::' expected in {s}"
+            )));
+        }
+        let [axis, name] = parts;
+        let (axis, name) = if canonical {
+            (
+                canonicalize(&axis).into_owned(),
+                canonicalize(&name).into_owned(),
+            )
+        } else {
+            (axis, name)
+        };
+        if axis.trim().is_empty() || name.trim().is_empty() {
+            return Err(Error::InvalidAttribute(format!(
+                "empty axis or empty name in {s}"
+            )));
+        }
+        Ok(Self {
+            axis: AxisName::new_unchecked(&axis),
+            name: name.into(),
+        })
+    }
+}
+
+/// Removes the whitespace surrounding the given axis or attribute name and
+/// replaces each sequence of whitespace characters by a single space.
+pub(crate) fn canonicalize(s: &str) -> Cow<'_, str> {
+    let s = s.trim();
+    let mut previous_is_whitespace = false;
+    let is_canonical = s.chars().all(|c| {
+        let ok = !c.is_whitespace() || (c == ' ' && !previous_is_whitespace);
+        previous_is_whitespace = c.is_whitespace();
+        ok
+    });
+    if is_canonical {
+        return Cow::Borrowed(s);
+    }
+    let mut canonical = String::with_capacity(s.len());
+    for word in s.split_whitespace() {
+        if !canonical.is_empty() {
+            canonical.push(' ');
+        }
+        canonical.push_str(word);
+    }
+    Cow::Owned(canonical)
+}
+
+impl Attribute {
+    /// Borrows this attribute.
+    #[must_use]
+    pub fn as_attribute_ref(&self) -> AttributeRef<'_> {
+        AttributeRef {
+            axis: &self.axis,
+            name: &self.name,
+        }
+    }
+}
+
+/// A borrowed `Attribute`.
+///
+/// Policy lookups accept an `AttributeRef`, which avoids allocating an
+/// `Attribute` for each query.
+#[derive(Hash, PartialEq, Eq, Clone, Copy, PartialOrd, Ord)]
+pub struct AttributeRef<'a> {
+    pub axis: &'a str,
+    pub name: &'a str,
+}
+
+impl<'a> AttributeRef<'a> {
+    /// Create a borrowed Policy Attribute.
+    ///
+    /// - `axis`    : policy axis the attributes belongs to
+    /// - `name`    : unique attribute name within this axis
+    #[must_use]
+    pub const fn new(axis: &'a str, name: &'a str) -> Self {
+        Self { axis, name }
+    }
+
+    /// Copies the axis and the name into an owned `Attribute`.
+    #[must_use]
+    pub fn to_attribute(&self) -> Attribute {
+        Attribute::new(self.axis, self.name)
+    }
 }
 
+impl Debug for AttributeRef<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_fmt(format_args!("{}::{}", self.axis, self.name))
+    }
+}
+
+/// Writes the attribute as `Axis::Name`, with the same escaping as
+/// `Attribute`.
+impl core::fmt::Display for AttributeRef<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write_escaped(f, self.axis)?;
+        write!(f, "::")?;
+        write_escaped(f, self.name)
+    }
+}
+
+impl<'a> From<&'a Attribute> for AttributeRef<'a> {
+    fn from(attribute: &'a Attribute) -> Self {
+        attribute.as_attribute_ref()
+    }
+}
+
+impl<'a> From<(&'a str, &'a str)> for AttributeRef<'a> {
+    fn from((axis, name): (&'a str, &'a str)) -> Self {
+        Self { axis, name }
+    }
+}
+
+impl From<AttributeRef<'_>> for Attribute {
+    fn from(attribute: AttributeRef<'_>) -> Self {
+        attribute.to_attribute()
+    }
+}
+
+/// Common view of `Attribute` and `AttributeRef`, used to query maps keyed by
+/// `Attribute` with an `AttributeRef`.
+pub(crate) trait AttributeKey {
+    fn key(&self) -> AttributeRef<'_>;
+}
+
+impl AttributeKey for Attribute {
+    fn key(&self) -> AttributeRef<'_> {
+        self.as_attribute_ref()
+    }
+}
+
+impl AttributeKey for AttributeRef<'_> {
+    fn key(&self) -> AttributeRef<'_> {
+        *self
+    }
+}
+
+impl<'a> Borrow<dyn AttributeKey + 'a> for Attribute {
+    fn borrow(&self) -> &(dyn AttributeKey + 'a) {
+        self
+    }
+}
+
+// Must hash as `Attribute` does: both hash the axis, then the name.
+impl Hash for dyn AttributeKey + '_ {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
+    }
+}
+
+impl PartialEq for dyn AttributeKey + '_ {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for dyn AttributeKey + '_ {}
+
 impl Debug for Attribute {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.write_fmt(format_args!("{}::{}", &self.axis, &self.name))
     }
 }
 
-impl From<(&str, &str)> for Attribute {
-    fn from(input: (&str, &str)) -> Self {
-        Self {
-            axis: input.0.to_owned(),
-            name: input.1.to_owned(),
+impl TryFrom<(&str, &str)> for Attribute {
+    type Error = Error;
+
+    fn try_from((axis, name): (&str, &str)) -> Result<Self, Self::Error> {
+        Self::try_new_exact(axis, name)
+    }
+}
+
+impl TryFrom<(String, String)> for Attribute {
+    type Error = Error;
+
+    fn try_from((axis, name): (String, String)) -> Result<Self, Self::Error> {
+        Self::try_new_exact(&axis, &name)
+    }
+}
+
+impl TryFrom<[&str; 2]> for Attribute {
+    type Error = Error;
+
+    fn try_from([axis, name]: [&str; 2]) -> Result<Self, Self::Error> {
+        Self::try_new_exact(axis, name)
+    }
+}
+
+impl TryFrom<&[&str]> for Attribute {
+    type Error = Error;
+
+    fn try_from(input: &[&str]) -> Result<Self, Self::Error> {
+        match input {
+            [axis, name] => Self::try_new_exact(axis, name),
+            _ => Err(Error::InvalidAttribute(format!(
+                "an axis and a name expected, got {input:?}"
+            ))),
         }
     }
 }
 
-impl From<(String, String)> for Attribute {
-    fn from(input: (String, String)) -> Self {
-        Self {
-            axis: input.0,
-            name: input.1,
+impl From<Attribute> for (String, String) {
+    fn from(attribute: Attribute) -> Self {
+        (attribute.axis.to_string(), attribute.name.to_string())
+    }
+}
+
+impl<'a> From<&'a Attribute> for (&'a str, &'a str) {
+    fn from(attribute: &'a Attribute) -> Self {
+        (&attribute.axis, &attribute.name)
+    }
+}
+
+#[cfg(feature = "serde")]
+struct AttributeVisitor;
+
+#[cfg(feature = "serde")]
+impl<'de> Visitor<'de> for AttributeVisitor {
+    type Value = Attribute;
+
+    fn expecting(&self, formatter: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        formatter.write_str("an `Axis::Name` string or an `{ axis, name }` structure")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        Attribute::parse_exact(v).map_err(E::custom)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let (mut axis, mut name) = (None::<String>, None::<String>);
+        while let Some(key) = map.next_key::<String>()? {
+            let (field, value) = match key.as_str() {
+                "axis" => ("axis", &mut axis),
+                "name" => ("name", &mut name),
+                _ => return Err(de::Error::unknown_field(&key, &["axis", "name"])),
+            };
+            if value.is_some() {
+                return Err(de::Error::duplicate_field(field));
+            }
+            *value = Some(map.next_value()?);
         }
+        let axis = axis.ok_or_else(|| de::Error::missing_field("axis"))?;
+        let name = name.ok_or_else(|| de::Error::missing_field("name"))?;
+        Attribute::from_serialized(&axis, &name).map_err(de::Error::custom)
     }
 }
 
-impl TryFrom<&str> for Attribute {
-    type Error = Error;
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Attribute {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(AttributeVisitor)
+        } else {
+            deserializer.deserialize_str(AttributeVisitor)
+        }
+    }
+}
 
-    fn try_from(s: &str) -> Result<Self, Self::Error> {
-        let (axis, name) = s.trim().split_once("::").ok_or_else(|| {
-            Error::InvalidAttribute(format!("at least one separator '::' expected in {s}"))
-        })?;
+/// Serialization of an `Attribute` in a given form, to be used with
+/// `#[serde(with = "...")]`. Both forms deserialize either form.
+///
+/// ```
+/// use abe_policy::Attribute;
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Message {
+///     #[serde(with = "abe_policy::attribute_serde::structured")]
+///     attribute: Attribute,
+/// }
+/// ```
+#[cfg(feature = "serde")]
+pub mod attribute_serde {
+    /// `Axis::Name` string form, used by default.
+    pub mod compact {
+        use crate::Attribute;
+        use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-        if name.contains("::") {
-            return Err(Error::InvalidAttribute(format!(
-                "separator '::' expected only once in {s}"
-            )));
+        pub fn serialize<S: Serializer>(
+            attribute: &Attribute,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            attribute.serialize(serializer)
         }
 
-        if axis.is_empty() || name.is_empty() {
-            return Err(Error::InvalidAttribute(format!(
-                "empty axis or empty name in {s}"
-            )));
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Attribute, D::Error> {
+            Attribute::deserialize(deserializer)
+        }
+    }
+
+    /// `{ "axis": ..., "name": ... }` structure form.
+    pub mod structured {
+        use crate::Attribute;
+        use serde::{ser::SerializeStruct, Deserialize, Deserializer, Serializer};
+
+        pub fn serialize<S: Serializer>(
+            attribute: &Attribute,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            let mut state = serializer.serialize_struct("Attribute", 2)?;
+            state.serialize_field("axis", &*attribute.axis)?;
+            state.serialize_field("name", &*attribute.name)?;
+            state.end()
         }
 
-        Ok(Self::new(axis, name))
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Attribute, D::Error> {
+            Attribute::deserialize(deserializer)
+        }
+    }
+}
+
+/// Writes `s`, escaping `\` and `:` with a `\`.
+fn write_escaped(f: &mut core::fmt::Formatter<'_>, s: &str) -> core::fmt::Result {
+    for c in s.chars() {
+        if c == '\\' || c == ':' {
+            write!(f, "\\")?;
+        }
+        write!(f, "{c}")?;
     }
+    Ok(())
 }
 
-impl std::fmt::Display for Attribute {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}::{}", self.axis, self.name)
+#[cfg(feature = "schema")]
+impl schemars::JsonSchema for Attribute {
+    fn schema_name() -> Cow<'static, str> {
+        "Attribute".into()
+    }
+
+    fn json_schema(_: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "type": "string",
+            "description": "Attribute, written `Axis::Name`",
+            "pattern": "^.+::.+$",
+        })
+    }
+}
+
+impl FromStr for Attribute {
+    type Err = Error;
+
+    /// Parses an attribute written `Axis::Name`.
+    ///
+    /// A `:` or a `\` escaped with a `\` is part of the axis or the name, so
+    /// that any attribute written with `Display` can be parsed back. Any other
+    /// `\` is kept as is.
+    ///
+    /// The axis and the name are canonicalized as in `try_new`, so that
+    /// `Department :: FIN ` and `Department::FIN` are the same attribute. Use
+    /// `parse_exact` to keep them as is.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s, true)
+    }
+}
+
+impl TryFrom<&str> for Attribute {
+    type Error = Error;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl TryFrom<String> for Attribute {
+    type Error = Error;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+/// Writes the attribute as `Axis::Name`, escaping `:` and `\` in the axis and
+/// the name so that the result can be parsed back with `FromStr`.
+impl core::fmt::Display for Attribute {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        core::fmt::Display::fmt(&self.as_attribute_ref(), f)
     }
 }
 
@@ -87,11 +541,96 @@ impl From<Attribute> for String {
 
 /// The `Attributes` struct is used to simplify the parsing of a list of
 /// `Attribute`s.
-#[derive(Debug, PartialEq, Eq)]
+///
+/// It is a set: an attribute is only inserted once. Attributes are kept in
+/// insertion order.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct Attributes {
     attributes: Vec<Attribute>,
 }
 
+impl Attributes {
+    /// Creates an empty set of attributes.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            attributes: Vec::new(),
+        }
+    }
+
+    /// Inserts the given attribute. Returns `false` if it was already in the
+    /// set.
+    pub fn insert(&mut self, attribute: Attribute) -> bool {
+        if self.contains(&attribute) {
+            false
+        } else {
+            self.attributes.push(attribute);
+            true
+        }
+    }
+
+    /// Removes the given attribute. Returns `false` if it was not in the set.
+    pub fn remove<'a>(&mut self, attribute: impl Into<AttributeRef<'a>>) -> bool {
+        let attribute = attribute.into();
+        let len = self.attributes.len();
+        self.attributes
+            .retain(|attr| attr.as_attribute_ref() != attribute);
+        self.attributes.len() != len
+    }
+
+    /// Returns `true` if the set contains the given attribute.
+    #[must_use]
+    pub fn contains<'a>(&self, attribute: impl Into<AttributeRef<'a>>) -> bool {
+        let attribute = attribute.into();
+        self.attributes
+            .iter()
+            .any(|attr| attr.as_attribute_ref() == attribute)
+    }
+
+    /// Returns the attributes of `self` or `other`.
+    #[must_use]
+    pub fn union(&self, other: &Self) -> Self {
+        self.iter().chain(other.iter()).cloned().collect()
+    }
+
+    /// Returns the attributes of both `self` and `other`.
+    #[must_use]
+    pub fn intersection(&self, other: &Self) -> Self {
+        self.iter()
+            .filter(|attribute| other.contains(*attribute))
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the attributes of `self` that are not in `other`.
+    #[must_use]
+    pub fn difference(&self, other: &Self) -> Self {
+        self.iter()
+            .filter(|attribute| !other.contains(*attribute))
+            .cloned()
+            .collect()
+    }
+
+    /// Groups the attributes by axis. Axes are sorted by name and the
+    /// attributes of an axis are kept in insertion order.
+    #[must_use]
+    pub fn by_axis(&self) -> BTreeMap<&str, Vec<&Attribute>> {
+        let mut axes = BTreeMap::<_, Vec<_>>::new();
+        for attribute in &self.attributes {
+            axes.entry(attribute.axis.as_ref())
+                .or_default()
+                .push(attribute);
+        }
+        axes
+    }
+
+    /// Returns the attributes in insertion order.
+    #[must_use]
+    pub fn into_vec(self) -> Vec<Attribute> {
+        self.attributes
+    }
+}
+
 impl Deref for Attributes {
     type Target = Vec<Attribute>;
 
@@ -100,9 +639,82 @@ impl Deref for Attributes {
     }
 }
 
+/// Duplicated attributes are removed.
 impl From<Vec<Attribute>> for Attributes {
     fn from(attributes: Vec<Attribute>) -> Self {
-        Self { attributes }
+        attributes.into_iter().collect()
+    }
+}
+
+impl From<Attributes> for Vec<Attribute> {
+    fn from(attributes: Attributes) -> Self {
+        attributes.attributes
+    }
+}
+
+impl FromIterator<Attribute> for Attributes {
+    fn from_iter<T: IntoIterator<Item = Attribute>>(iter: T) -> Self {
+        let mut attributes = Self::new();
+        attributes.extend(iter);
+        attributes
+    }
+}
+
+impl Extend<Attribute> for Attributes {
+    fn extend<T: IntoIterator<Item = Attribute>>(&mut self, iter: T) {
+        for attribute in iter {
+            self.insert(attribute);
+        }
+    }
+}
+
+impl IntoIterator for Attributes {
+    type IntoIter = alloc::vec::IntoIter<Attribute>;
+    type Item = Attribute;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.attributes.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Attributes {
+    type IntoIter = core::slice::Iter<'a, Attribute>;
+    type Item = &'a Attribute;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.attributes.iter()
+    }
+}
+
+impl FromStr for Attributes {
+    type Err = Error;
+
+    /// Parses a comma-separated list of attributes written `Axis::Name`,
+    /// e.g. `Department::FIN, Security Level::Top Secret`. Whitespace around
+    /// the attributes is ignored.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim().is_empty() {
+            return Err(Error::InvalidAttribute(format!(
+                "empty list of attributes '{s}'"
+            )));
+        }
+        s.split(',')
+            .enumerate()
+            .map(|(i, attribute)| {
+                if attribute.trim().is_empty() {
+                    return Err(Error::InvalidAttribute(format!(
+                        "attribute {} of '{s}' is empty",
+                        i + 1
+                    )));
+                }
+                attribute.parse::<Attribute>().map_err(|e| match e {
+                    Error::InvalidAttribute(e) => {
+                        Error::InvalidAttribute(format!("attribute {} of '{s}': {e}", i + 1))
+                    }
+                    e => e,
+                })
+            })
+            .collect()
     }
 }
 
@@ -110,19 +722,66 @@ impl TryFrom<&str> for Attributes {
     type Error = Error;
 
     fn try_from(attributes_str: &str) -> Result<Self, Self::Error> {
-        if attributes_str.is_empty() {
-            return Err(Error::InvalidAttribute(attributes_str.to_string()));
+        attributes_str.parse()
+    }
+}
+
+/// Attributes are serialized as an array of `Axis::Name` strings.
+#[cfg(feature = "serde")]
+impl Serialize for Attributes {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(&self.attributes)
+    }
+}
+
+#[cfg(feature = "serde")]
+struct AttributesVisitor;
+
+#[cfg(feature = "serde")]
+impl<'de> Visitor<'de> for AttributesVisitor {
+    type Value = Attributes;
+
+    fn expecting(&self, formatter: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        formatter.write_str("an array of attributes or a comma-separated list of attributes")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        v.parse().map_err(E::custom)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut attributes = Attributes::new();
+        while let Some(attribute) = seq.next_element()? {
+            attributes.insert(attribute);
         }
+        Ok(attributes)
+    }
+}
 
-        // Convert a Vec<Result<Attribute,FormatErr>> into a Result<Vec<Attribute>>
-        let attributes: Result<Vec<_>, _> = attributes_str
-            .trim()
-            .split(',')
-            .map(Attribute::try_from)
-            .collect();
+/// Attributes are deserialized from an array of attributes, written either
+/// as `Axis::Name` strings or as `{ "axis": ..., "name": ... }` structures,
+/// or from a single comma-separated string of attributes.
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Attributes {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(AttributesVisitor)
+        } else {
+            deserializer.deserialize_seq(AttributesVisitor)
+        }
+    }
+}
 
-        Ok(Self {
-            attributes: attributes?,
-        })
+/// Writes the attributes as a comma-separated list that can be parsed back
+/// with `FromStr`.
+impl core::fmt::Display for Attributes {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for (i, attribute) in self.attributes.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{attribute}")?;
+        }
+        Ok(())
     }
 }
@@ -0,0 +1,149 @@
+use crate::{attribute::canonicalize, Error};
+use alloc::{format, string::String, sync::Arc};
+use core::{
+    borrow::Borrow,
+    fmt::{Debug, Display},
+    ops::Deref,
+    str::FromStr,
+};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Name of a policy axis.
+///
+/// A valid axis name is non-empty and does not contain the separator `::`.
+/// The name is reference-counted: the attributes of an axis share its name.
+#[derive(Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct AxisName(Arc<str>);
+
+impl AxisName {
+    /// Creates an axis name from user input.
+    ///
+    /// The name is canonicalized as attributes are: surrounding whitespace is
+    /// removed and each sequence of whitespace characters is replaced by a
+    /// single space.
+    pub fn new(name: &str) -> Result<Self, Error> {
+        Self::new_exact(&canonicalize(name))
+    }
+
+    /// Creates an axis name, keeping the given name as is.
+    pub fn new_exact(name: &str) -> Result<Self, Error> {
+        if name.trim().is_empty() {
+            return Err(Error::InvalidAxis(format!("empty axis name '{name}'")));
+        }
+        if name.contains("::") {
+            return Err(Error::InvalidAxis(format!(
+                "separator '::' in axis name '{name}'"
+            )));
+        }
+        Ok(Self(name.into()))
+    }
+
+    /// Creates an axis name without checking it.
+    pub(crate) fn new_unchecked(name: &str) -> Self {
+        Self(name.into())
+    }
+
+    /// Returns the name as a string slice.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Deref for AxisName {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl AsRef<str> for AxisName {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Borrow<str> for AxisName {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl PartialEq<str> for AxisName {
+    fn eq(&self, other: &str) -> bool {
+        &*self.0 == other
+    }
+}
+
+impl PartialEq<&str> for AxisName {
+    fn eq(&self, other: &&str) -> bool {
+        &*self.0 == *other
+    }
+}
+
+impl Debug for AxisName {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        Debug::fmt(&*self.0, f)
+    }
+}
+
+impl Display for AxisName {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromStr for AxisName {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s)
+    }
+}
+
+impl TryFrom<&str> for AxisName {
+    type Error = Error;
+
+    fn try_from(name: &str) -> Result<Self, Self::Error> {
+        Self::new(name)
+    }
+}
+
+impl From<AxisName> for String {
+    fn from(name: AxisName) -> Self {
+        name.0.as_ref().into()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for AxisName {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+/// Serialized names are checked but kept as is.
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for AxisName {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Self::new_exact(&name).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(feature = "schema")]
+impl schemars::JsonSchema for AxisName {
+    fn schema_name() -> alloc::borrow::Cow<'static, str> {
+        "AxisName".into()
+    }
+
+    fn json_schema(_: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "type": "string",
+            "minLength": 1,
+            "not": { "pattern": "::" },
+        })
+    }
+}
@@ -0,0 +1,188 @@
+//! Command line tool managing policies stored in JSON files.
+
+use std::{error::Error, fs, path::PathBuf};
+
+use abe_policy::{AccessPolicy, Attribute, EncryptionHint, Policy, PolicyAxis};
+use clap::{Args, Parser, Subcommand};
+
+#[derive(Parser)]
+#[command(name = "abe-policy", version, about = "Manage ABE policies")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+/// Policy file read by a command and the file the updated policy is written
+/// to.
+#[derive(Args)]
+struct PolicyFiles {
+    /// Policy file
+    #[arg(short, long)]
+    policy: PathBuf,
+    /// File to write the updated policy to, defaults to the policy file
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+impl PolicyFiles {
+    fn read(&self) -> Result<Policy, Box<dyn Error>> {
+        read_policy(&self.policy)
+    }
+
+    fn write(&self, policy: &Policy) -> Result<(), Box<dyn Error>> {
+        write_policy(self.output.as_ref().unwrap_or(&self.policy), policy)
+    }
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create an empty policy
+    New {
+        /// Maximum number of attribute creations (additions + rotations)
+        #[arg(short, long)]
+        max_creations: u32,
+        /// File to write the policy to, defaults to the standard output
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Add an axis to a policy
+    AddAxis {
+        #[command(flatten)]
+        files: PolicyFiles,
+        /// Axis name
+        #[arg(short, long)]
+        name: String,
+        /// Attribute names, from the lowest to the highest for a hierarchical
+        /// axis
+        #[arg(short, long = "attribute", required = true)]
+        attributes: Vec<String>,
+        /// Attribute names for which hybridized encryption should be used
+        #[arg(long)]
+        hybridized: Vec<String>,
+        /// Make the axis hierarchical
+        #[arg(long)]
+        hierarchical: bool,
+    },
+    /// Add an attribute to an existing axis of a policy
+    AddAttribute {
+        #[command(flatten)]
+        files: PolicyFiles,
+        /// Attribute, written `Axis::Name`
+        attribute: String,
+        /// Use hybridized encryption for this attribute
+        #[arg(long)]
+        hybridized: bool,
+    },
+    /// Rotate attributes of a policy
+    Rotate {
+        #[command(flatten)]
+        files: PolicyFiles,
+        /// Attributes, written `Axis::Name`
+        #[arg(required = true)]
+        attributes: Vec<String>,
+    },
+    /// Print the content of a policy
+    Inspect {
+        /// Policy file
+        #[arg(short, long)]
+        policy: PathBuf,
+    },
+    /// Convert a boolean expression into a JSON access policy
+    ParseExpression {
+        /// Boolean expression, e.g. `Department::HR && Security Level::Protected`
+        expression: String,
+        /// Policy file used to check the expression attributes
+        #[arg(short, long)]
+        policy: Option<PathBuf>,
+    },
+}
+
+fn read_policy(path: &PathBuf) -> Result<Policy, Box<dyn Error>> {
+    let bytes = fs::read(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    Ok(Policy::parse_and_convert(&bytes)?)
+}
+
+fn write_policy(path: &PathBuf, policy: &Policy) -> Result<(), Box<dyn Error>> {
+    fs::write(path, serde_json::to_vec(policy)?).map_err(|e| format!("{}: {e}", path.display()))?;
+    Ok(())
+}
+
+fn run(command: Command) -> Result<(), Box<dyn Error>> {
+    match command {
+        Command::New {
+            max_creations,
+            output,
+        } => {
+            let policy = Policy::new(max_creations);
+            match output {
+                Some(path) => write_policy(&path, &policy)?,
+                None => println!("{policy}"),
+            }
+        }
+        Command::AddAxis {
+            files,
+            name,
+            attributes,
+            hybridized,
+            hierarchical,
+        } => {
+            let mut policy = files.read()?;
+            let properties = attributes
+                .iter()
+                .map(|attribute| {
+                    let hint = if hybridized.contains(attribute) {
+                        EncryptionHint::Hybridized
+                    } else {
+                        EncryptionHint::Classic
+                    };
+                    (attribute.as_str(), hint)
+                })
+                .collect();
+            policy.add_axis(PolicyAxis::new(&name, properties, hierarchical))?;
+            files.write(&policy)?;
+        }
+        Command::AddAttribute {
+            files,
+            attribute,
+            hybridized,
+        } => {
+            let mut policy = files.read()?;
+            let hint = if hybridized {
+                EncryptionHint::Hybridized
+            } else {
+                EncryptionHint::Classic
+            };
+            policy.add_attribute(&attribute.parse::<Attribute>()?, hint)?;
+            files.write(&policy)?;
+        }
+        Command::Rotate { files, attributes } => {
+            let mut policy = files.read()?;
+            for attribute in &attributes {
+                let attribute = attribute.parse::<Attribute>()?;
+                policy.rotate(&attribute)?;
+                println!(
+                    "{}: {}",
+                    policy.canonical_attribute(&attribute)?,
+                    policy.attribute_current_value(&attribute)?
+                );
+            }
+            files.write(&policy)?;
+        }
+        Command::Inspect { policy } => print!("{}", read_policy(&policy)?.to_pretty_string()),
+        Command::ParseExpression { expression, policy } => {
+            let access_policy = match policy {
+                Some(path) => read_policy(&path)?.parse_access_policy(&expression)?,
+                None => AccessPolicy::from_boolean_expression(&expression)?,
+            };
+            println!("{}", serde_json::to_string(&access_policy)?);
+        }
+    }
+    Ok(())
+}
+
+fn main() {
+    if let Err(e) = run(Cli::parse().command) {
+        eprintln!("error: {e}");
+        std::process::exit(1);
+    }
+}
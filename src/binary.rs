@@ -0,0 +1,485 @@
+//! Compact binary serialization of policies.
+//!
+//! A serialized policy starts with the `ABEP` magic bytes followed by the
+//! format version. Versions 2 and 3 are followed by the width of the attribute
+//! values, which are varints in version 1. Other integers are LEB128 varints and
+//! strings are length prefixed UTF-8. Axes are written in name order, each followed by its
+//! attributes in axis order. The naming policy and the unknown fields, which
+//! are seldom set, are written as length-prefixed JSON, empty if unset.
+//! Version 3 also gives the number of retired epochs of each attribute, after
+//! its values, and ends with the reclaimed values. The other versions are
+//! written for policies which never reclaimed values.
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use serde_json::Map;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    consistency::check_capacity,
+    policy::{PolicyAttributesParameters, PolicyAxesParameters, PolicyVersion, UncheckedPolicy},
+    Attribute, AxisName, AxisView, EncryptionHint, Error, IndexMap, NamingPolicy, Policy,
+    PolicyProblem,
+};
+
+/// Magic bytes starting a binary policy.
+const MAGIC: &[u8; 4] = b"ABEP";
+
+/// Version of the binary format whose attribute values are varints.
+const VARINT_FORMAT_VERSION: u8 = 1;
+
+/// Version of the binary format giving the width of the attribute values.
+const FORMAT_VERSION: u8 = 2;
+
+/// Version of the binary format also giving the reclaimed values.
+const RECLAIMED_FORMAT_VERSION: u8 = 3;
+
+/// Encoding of the attribute values in the compact binary format.
+///
+/// Fixed widths give attribute values a constant size, e.g. for embedded
+/// ciphertext headers: values below 128 take a single byte as varints, but
+/// values above 16383 take three.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ValueWidth {
+    /// LEB128 varints
+    #[default]
+    Varint,
+    /// Little-endian 16-bit integers, for policies whose values all fit
+    U16,
+    /// Little-endian 32-bit integers
+    U32,
+}
+
+impl ValueWidth {
+    fn to_byte(self) -> u8 {
+        match self {
+            Self::Varint => 0,
+            Self::U16 => 2,
+            Self::U32 => 4,
+        }
+    }
+
+    fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(Self::Varint),
+            2 => Some(Self::U16),
+            4 => Some(Self::U32),
+            _ => None,
+        }
+    }
+}
+
+/// Size of the chunks written to a writer.
+#[cfg(feature = "std")]
+const CHUNK_SIZE: usize = 1 << 13;
+
+fn write_varint(bytes: &mut Vec<u8>, mut n: u64) {
+    while n >= 0x80 {
+        bytes.push((n as u8) | 0x80);
+        n >>= 7;
+    }
+    bytes.push(n as u8);
+}
+
+fn write_value(bytes: &mut Vec<u8>, width: ValueWidth, value: u32) -> Result<(), Error> {
+    match width {
+        ValueWidth::Varint => write_varint(bytes, u64::from(value)),
+        ValueWidth::U16 => {
+            let value = u16::try_from(value).map_err(|_| {
+                Error::InvalidBinaryPolicy(alloc::format!(
+                    "attribute value {value} does not fit in 16 bits"
+                ))
+            })?;
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        ValueWidth::U32 => bytes.extend_from_slice(&value.to_le_bytes()),
+    }
+    Ok(())
+}
+
+fn write_bytes(bytes: &mut Vec<u8>, s: &[u8]) {
+    write_varint(bytes, s.len() as u64);
+    bytes.extend_from_slice(s);
+}
+
+/// Source of the `Reader`.
+trait Input {
+    /// Returns the next `n` bytes, `None` if there are fewer.
+    fn take(&mut self, n: usize) -> Result<Option<&[u8]>, Error>;
+
+    /// Returns the number of bytes left, if known.
+    fn remaining(&self) -> Option<usize>;
+}
+
+impl Input for &[u8] {
+    fn take(&mut self, n: usize) -> Result<Option<&[u8]>, Error> {
+        if n > self.len() {
+            return Ok(None);
+        }
+        let (head, tail) = self.split_at(n);
+        *self = tail;
+        Ok(Some(head))
+    }
+
+    fn remaining(&self) -> Option<usize> {
+        Some(self.len())
+    }
+}
+
+#[cfg(feature = "std")]
+struct IoInput<R> {
+    reader: R,
+    buffer: Vec<u8>,
+}
+
+#[cfg(feature = "std")]
+impl<R: std::io::Read> Input for IoInput<R> {
+    fn take(&mut self, n: usize) -> Result<Option<&[u8]>, Error> {
+        use std::io::Read;
+
+        self.buffer.clear();
+        // reads by chunks: the length may come from a corrupted input
+        (&mut self.reader)
+            .take(n as u64)
+            .read_to_end(&mut self.buffer)
+            .map_err(Error::IoError)?;
+        Ok((self.buffer.len() == n).then_some(&self.buffer))
+    }
+
+    fn remaining(&self) -> Option<usize> {
+        None
+    }
+}
+
+/// Maximum number of items allocated in advance when the input size is
+/// unknown.
+const MAX_PREALLOCATION: usize = 1 << 12;
+
+/// Reads the items written by the `write_*` functions.
+struct Reader<I> {
+    input: I,
+}
+
+impl<I: Input> Reader<I> {
+    fn error(what: &str) -> Error {
+        Error::InvalidBinaryPolicy(alloc::format!("truncated or invalid {what}"))
+    }
+
+    fn take(&mut self, n: usize, what: &str) -> Result<&[u8], Error> {
+        self.input.take(n)?.ok_or_else(|| Self::error(what))
+    }
+
+    fn byte(&mut self, what: &str) -> Result<u8, Error> {
+        Ok(self.take(1, what)?[0])
+    }
+
+    fn varint(&mut self, what: &str) -> Result<u64, Error> {
+        let mut n = 0_u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte(what)?;
+            n |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(n);
+            }
+        }
+        Err(Self::error(what))
+    }
+
+    fn u32(&mut self, what: &str) -> Result<u32, Error> {
+        u32::try_from(self.varint(what)?).map_err(|_| Self::error(what))
+    }
+
+    fn value(&mut self, width: ValueWidth, what: &str) -> Result<u32, Error> {
+        match width {
+            ValueWidth::Varint => self.u32(what),
+            ValueWidth::U16 => {
+                let bytes = self.take(2, what)?;
+                Ok(u32::from(u16::from_le_bytes([bytes[0], bytes[1]])))
+            }
+            ValueWidth::U32 => {
+                let bytes = self.take(4, what)?;
+                Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            }
+        }
+    }
+
+    fn len(&mut self, what: &str) -> Result<usize, Error> {
+        let len = usize::try_from(self.varint(what)?).map_err(|_| Self::error(what))?;
+        // Each item takes at least one byte: this bounds the allocations made
+        // for lengths read from corrupted inputs.
+        if self
+            .input
+            .remaining()
+            .is_some_and(|remaining| len > remaining)
+        {
+            return Err(Self::error(what));
+        }
+        Ok(len)
+    }
+
+    /// Returns the capacity to allocate for `len` items.
+    fn capacity(&self, len: usize) -> usize {
+        match self.input.remaining() {
+            Some(_) => len,
+            None => len.min(MAX_PREALLOCATION),
+        }
+    }
+
+    fn str(&mut self, what: &str) -> Result<&str, Error> {
+        let len = self.len(what)?;
+        core::str::from_utf8(self.take(len, what)?).map_err(|_| Self::error(what))
+    }
+
+    /// Reads a policy written by `Policy::write_binary`.
+    fn policy(&mut self) -> Result<Policy, Error> {
+        if self.take(MAGIC.len(), "magic bytes")? != MAGIC {
+            return Err(Error::InvalidBinaryPolicy("wrong magic bytes".into()));
+        }
+        let version = self.byte("format version")?;
+        let width = match version {
+            VARINT_FORMAT_VERSION => ValueWidth::Varint,
+            FORMAT_VERSION | RECLAIMED_FORMAT_VERSION => {
+                let width = self.byte("value width")?;
+                ValueWidth::from_byte(width).ok_or_else(|| {
+                    Error::InvalidBinaryPolicy(alloc::format!("unsupported value width {width}"))
+                })?
+            }
+            _ => {
+                return Err(Error::InvalidBinaryPolicy(alloc::format!(
+                    "unsupported format version {version}"
+                )))
+            }
+        };
+        let max_attribute_creations = self.u32("maximum attribute creations")?;
+        let last_attribute_value = self.u32("last attribute value")?;
+        check_capacity(last_attribute_value, max_attribute_creations)
+            .map_err(|problem| Error::InvalidBinaryPolicy(problem.to_string()))?;
+
+        let mut axes = IndexMap::default();
+        let mut attributes = IndexMap::default();
+        for _ in 0..self.len("axis count")? {
+            let axis = AxisName::new_exact(self.str("axis name")?)?;
+            let is_hierarchical = self.byte("axis hierarchy")? != 0;
+            let nb_attributes = self.len("attribute count")?;
+            let mut attribute_names = Vec::with_capacity(self.capacity(nb_attributes));
+            for _ in 0..nb_attributes {
+                let name = String::from(self.str("attribute name")?);
+                let encryption_hint = if self.byte("encryption hint")? == 0 {
+                    EncryptionHint::Classic
+                } else {
+                    EncryptionHint::Hybridized
+                };
+                let values = (0..self.len("value count")?)
+                    .map(|_| self.value(width, "attribute value"))
+                    .collect::<Result<_, _>>()?;
+                let retired_epochs = if version == RECLAIMED_FORMAT_VERSION {
+                    self.u32("retired epochs")?
+                } else {
+                    0
+                };
+                attributes.insert(
+                    Attribute::with_shared_axis(&axis, &name),
+                    PolicyAttributesParameters {
+                        values,
+                        encryption_hint,
+                        retired_epochs,
+                    },
+                );
+                attribute_names.push(name);
+            }
+            axes.insert(
+                axis,
+                PolicyAxesParameters {
+                    attribute_names,
+                    is_hierarchical,
+                },
+            );
+        }
+
+        let len = self.len("naming policy")?;
+        let naming_policy = match self.take(len, "naming policy")? {
+            [] => NamingPolicy::default(),
+            json => serde_json::from_slice(json).map_err(Error::DeserializationError)?,
+        };
+        let len = self.len("unknown fields")?;
+        let unknown_fields = match self.take(len, "unknown fields")? {
+            [] => Map::new(),
+            json => serde_json::from_slice(json).map_err(Error::DeserializationError)?,
+        };
+        let reclaimed_values = if version == RECLAIMED_FORMAT_VERSION {
+            (0..self.len("reclaimed value count")?)
+                .map(|_| self.value(width, "reclaimed value"))
+                .collect::<Result<_, _>>()?
+        } else {
+            Vec::new()
+        };
+
+        UncheckedPolicy {
+            version: PolicyVersion::V1,
+            last_attribute_value,
+            max_attribute_creations,
+            axes,
+            attributes,
+            naming_policy,
+            reclaimed_values,
+            unknown_fields,
+        }
+        .try_into()
+        .map_err(|problem: PolicyProblem| Error::InvalidBinaryPolicy(problem.to_string()))
+    }
+}
+
+impl Policy {
+    /// Serializes this policy in the compact binary format.
+    ///
+    /// The result is deterministic: it does not depend on the iteration order
+    /// of the policy maps.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        self.to_bytes_with(ValueWidth::Varint)
+    }
+
+    /// Serializes this policy in the compact binary format, writing the
+    /// attribute values with the given width.
+    ///
+    /// Fails if a value does not fit in this width.
+    pub fn to_bytes_with(&self, width: ValueWidth) -> Result<Vec<u8>, Error> {
+        let mut bytes = Vec::new();
+        self.write_binary(&mut bytes, width, |_| Ok(()))?;
+        Ok(bytes)
+    }
+
+    /// Writes this policy in the compact binary format to the given writer,
+    /// as `Policy::to_bytes_with` does, by chunks of a few kilobytes.
+    #[cfg(feature = "std")]
+    pub fn serialize_bytes_to_writer(
+        &self,
+        mut writer: impl std::io::Write,
+        width: ValueWidth,
+    ) -> Result<(), Error> {
+        let mut bytes = Vec::with_capacity(2 * CHUNK_SIZE);
+        let mut write = |bytes: &mut Vec<u8>| {
+            writer.write_all(bytes).map_err(Error::IoError)?;
+            bytes.clear();
+            Ok(())
+        };
+        self.write_binary(&mut bytes, width, |bytes| {
+            if bytes.len() >= CHUNK_SIZE {
+                write(bytes)?;
+            }
+            Ok(())
+        })?;
+        write(&mut bytes)
+    }
+
+    /// Writes this policy to `bytes`, calling `flush` after each attribute
+    /// to let it empty them.
+    fn write_binary(
+        &self,
+        bytes: &mut Vec<u8>,
+        width: ValueWidth,
+        mut flush: impl FnMut(&mut Vec<u8>) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        bytes.extend_from_slice(MAGIC);
+        // policies which never reclaimed values keep the previous formats,
+        // and the same fingerprint
+        let has_reclaimed = !self.reclaimed_values.is_empty()
+            || self
+                .attribute_views()
+                .any(|attribute| attribute.retired_epochs() > 0);
+        if has_reclaimed {
+            bytes.push(RECLAIMED_FORMAT_VERSION);
+            bytes.push(width.to_byte());
+        } else if width == ValueWidth::Varint {
+            bytes.push(VARINT_FORMAT_VERSION);
+        } else {
+            bytes.push(FORMAT_VERSION);
+            bytes.push(width.to_byte());
+        }
+        write_varint(bytes, u64::from(self.max_attribute_creations));
+        write_varint(bytes, u64::from(self.last_attribute_value));
+
+        let mut axes = self.axes().collect::<Vec<_>>();
+        axes.sort_by_key(AxisView::name);
+        write_varint(bytes, axes.len() as u64);
+        for axis in axes {
+            write_bytes(bytes, axis.name().as_bytes());
+            bytes.push(u8::from(axis.is_hierarchical()));
+            write_varint(bytes, axis.len() as u64);
+            for attribute in axis.attributes() {
+                write_bytes(bytes, attribute.name().as_bytes());
+                bytes.push(u8::from(
+                    attribute.encryption_hint() == EncryptionHint::Hybridized,
+                ));
+                write_varint(bytes, attribute.values().len() as u64);
+                for value in attribute.values() {
+                    write_value(bytes, width, *value)?;
+                }
+                if has_reclaimed {
+                    write_varint(bytes, u64::from(attribute.retired_epochs()));
+                }
+                flush(bytes)?;
+            }
+        }
+
+        let naming_policy = if self.naming_policy.is_unconstrained() {
+            Vec::new()
+        } else {
+            serde_json::to_vec(&self.naming_policy).map_err(Error::DeserializationError)?
+        };
+        write_bytes(bytes, &naming_policy);
+
+        let unknown_fields = if self.unknown_fields.is_empty() {
+            Vec::new()
+        } else {
+            serde_json::to_vec(&self.unknown_fields).map_err(Error::DeserializationError)?
+        };
+        write_bytes(bytes, &unknown_fields);
+
+        if has_reclaimed {
+            write_varint(bytes, self.reclaimed_values.len() as u64);
+            for value in &self.reclaimed_values {
+                write_value(bytes, width, *value)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Deserializes a policy written by `Policy::to_bytes` or
+    /// `Policy::to_bytes_with`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let mut reader = Reader { input: bytes };
+        let policy = reader.policy()?;
+        if !reader.input.is_empty() {
+            return Err(Error::InvalidBinaryPolicy("trailing bytes".into()));
+        }
+        Ok(policy)
+    }
+
+    /// Reads a policy in the compact binary format from the given reader, as
+    /// `Policy::from_bytes` does, without reading it whole in memory.
+    ///
+    /// Only the bytes of the policy are read: the reader may hold other data
+    /// after it. Small reads are made: the reader should be buffered, e.g. by
+    /// a `BufReader`.
+    #[cfg(feature = "std")]
+    pub fn deserialize_bytes_from_reader(reader: impl std::io::Read) -> Result<Self, Error> {
+        Reader {
+            input: IoInput {
+                reader,
+                buffer: Vec::new(),
+            },
+        }
+        .policy()
+    }
+
+    /// Returns the SHA-256 hash of the binary serialization of this policy.
+    ///
+    /// Equal policies have the same fingerprint, whatever the iteration order
+    /// of their maps.
+    pub fn fingerprint(&self) -> Result<[u8; 32], Error> {
+        Ok(Sha256::digest(self.to_bytes()?).into())
+    }
+}
@@ -0,0 +1,141 @@
+use crate::{AccessPolicy, Attribute};
+use core::{
+    fmt::Display,
+    hash::{Hash, Hasher},
+};
+use std::hash::DefaultHasher;
+
+/// Access policy whose canonical form, attributes and hash are computed once,
+/// for caches comparing and hashing the same access policies repeatedly.
+///
+/// The canonical form flattens the chains of conjunctions and of disjunctions
+/// and sorts their operands: access policies differing only by the order or
+/// the grouping of their operands have the same canonical form, and are
+/// equal.
+///
+/// ```
+/// use abe_policy::{AccessPolicy, CanonicalAccessPolicy};
+///
+/// let left = AccessPolicy::from_boolean_expression("(Level::Low && Department::HR) && Department::FIN")?;
+/// let right = AccessPolicy::from_boolean_expression("Department::FIN && Department::HR && Level::Low")?;
+/// assert_eq!(CanonicalAccessPolicy::new(left), CanonicalAccessPolicy::new(right));
+/// # Ok::<(), abe_policy::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct CanonicalAccessPolicy {
+    access_policy: AccessPolicy,
+    /// Boolean expression of the canonical form, which identifies it
+    expression: String,
+    /// Sorted attributes
+    attributes: Vec<Attribute>,
+    hash: u64,
+}
+
+impl CanonicalAccessPolicy {
+    /// Computes the canonical form of the given access policy.
+    #[must_use]
+    pub fn new(access_policy: AccessPolicy) -> Self {
+        let (access_policy, expression) = canonicalize(access_policy);
+        let attributes = access_policy.attributes();
+        let mut hasher = DefaultHasher::new();
+        expression.hash(&mut hasher);
+        Self {
+            access_policy,
+            expression,
+            attributes,
+            hash: hasher.finish(),
+        }
+    }
+
+    /// Returns the canonical form, which is equivalent to the access policy
+    /// given at creation.
+    #[must_use]
+    pub fn access_policy(&self) -> &AccessPolicy {
+        &self.access_policy
+    }
+
+    /// Returns the boolean expression of the canonical form.
+    #[must_use]
+    pub fn boolean_expression(&self) -> &str {
+        &self.expression
+    }
+
+    /// Returns the sorted attributes of the access policy, as
+    /// `AccessPolicy::attributes()` does.
+    #[must_use]
+    pub fn attributes(&self) -> &[Attribute] {
+        &self.attributes
+    }
+
+    #[must_use]
+    pub fn into_access_policy(self) -> AccessPolicy {
+        self.access_policy
+    }
+}
+
+/// Returns the canonical form of the given access policy and its boolean
+/// expression.
+fn canonicalize(access_policy: AccessPolicy) -> (AccessPolicy, String) {
+    let operator: fn(AccessPolicy, AccessPolicy) -> AccessPolicy = match access_policy {
+        AccessPolicy::And(..) => |left, right| left & right,
+        AccessPolicy::Or(..) => |left, right| left | right,
+        AccessPolicy::Attr(_) | AccessPolicy::All => {
+            let expression = access_policy.to_string();
+            return (access_policy, expression);
+        }
+    };
+    let mut operands = Vec::new();
+    push_operands(access_policy, &mut operands);
+    let mut operands = operands.into_iter().map(canonicalize).collect::<Vec<_>>();
+    operands.sort_unstable_by(|(_, left), (_, right)| left.cmp(right));
+
+    // nests the operands to the right, as the parser does
+    let access_policy = operands
+        .into_iter()
+        .map(|(operand, _)| operand)
+        .rev()
+        .reduce(|right, left| operator(left, right))
+        .unwrap_or(AccessPolicy::All);
+    let expression = access_policy.to_string();
+    (access_policy, expression)
+}
+
+/// Pushes the operands of the chain of conjunctions or of disjunctions
+/// starting at the given access policy, from left to right.
+fn push_operands(access_policy: AccessPolicy, operands: &mut Vec<AccessPolicy>) {
+    let mut stack = vec![access_policy];
+    let is_and = matches!(stack[0], AccessPolicy::And(..));
+    while let Some(access_policy) = stack.pop() {
+        match access_policy {
+            AccessPolicy::And(left, right) if is_and => stack.extend([*right, *left]),
+            AccessPolicy::Or(left, right) if !is_and => stack.extend([*right, *left]),
+            operand => operands.push(operand),
+        }
+    }
+}
+
+impl From<AccessPolicy> for CanonicalAccessPolicy {
+    fn from(access_policy: AccessPolicy) -> Self {
+        Self::new(access_policy)
+    }
+}
+
+impl PartialEq for CanonicalAccessPolicy {
+    fn eq(&self, other: &Self) -> bool {
+        self.hash == other.hash && self.expression == other.expression
+    }
+}
+
+impl Eq for CanonicalAccessPolicy {}
+
+impl Hash for CanonicalAccessPolicy {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.hash);
+    }
+}
+
+impl Display for CanonicalAccessPolicy {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&self.expression)
+    }
+}
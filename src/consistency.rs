@@ -0,0 +1,420 @@
+use crate::{
+    attribute::AttributeKey,
+    policy::{UncheckedPolicy, MAX_AXES},
+    Attribute, AttributeRef, AxisName, HashMap, HashSet, Policy,
+};
+use alloc::{string::String, vec::Vec};
+use core::fmt::Display;
+
+/// Inconsistency making a policy unusable, e.g. after a manual edition of its
+/// JSON serialization.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[non_exhaustive]
+pub enum PolicyProblem {
+    /// The attribute is not listed by its axis.
+    OrphanAttribute { attribute: Attribute },
+    /// The axis lists an attribute which is not defined.
+    UndefinedAttribute { axis: AxisName, name: String },
+    /// The axis lists the same attribute several times.
+    DuplicateAttribute { axis: AxisName, name: String },
+    /// The attribute has no value.
+    NoValue { attribute: Attribute },
+    /// The value of the attribute is above the last attribute value.
+    ValueOutOfRange { attribute: Attribute, value: u32 },
+    /// The value is given to several attributes.
+    SharedValue {
+        value: u32,
+        first: Attribute,
+        second: Attribute,
+    },
+    /// The last attribute value is above the maximum number of attribute
+    /// creations.
+    CapacityExceeded { last: u32, max: u32 },
+    /// The reclaimed value is above the last attribute value, given to an
+    /// attribute, or not above the previous reclaimed value.
+    InvalidReclaimedValue { value: u32 },
+    /// The policy has more axes than their 16-bit identifiers can number.
+    TooManyAxes { count: usize },
+}
+
+impl Display for PolicyProblem {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::OrphanAttribute { attribute } => {
+                write!(f, "attribute {attribute} is not listed by its axis")
+            }
+            Self::UndefinedAttribute { axis, name } => {
+                write!(f, "axis {axis} lists undefined attribute {name}")
+            }
+            Self::DuplicateAttribute { axis, name } => {
+                write!(f, "axis {axis} lists attribute {name} several times")
+            }
+            Self::NoValue { attribute } => write!(f, "attribute {attribute} has no value"),
+            Self::ValueOutOfRange { attribute, value } => write!(
+                f,
+                "value {value} of attribute {attribute} is above the last attribute value"
+            ),
+            Self::SharedValue {
+                value,
+                first,
+                second,
+            } => write!(f, "value {value} is given to both {first} and {second}"),
+            Self::CapacityExceeded { last, max } => write!(
+                f,
+                "last attribute value {last} is above the maximum number of attribute creations {max}"
+            ),
+            Self::InvalidReclaimedValue { value } => write!(
+                f,
+                "reclaimed value {value} is out of range, in use or out of order"
+            ),
+            Self::TooManyAxes { count } => {
+                write!(f, "policy has {count} axes, more than the {MAX_AXES} allowed")
+            }
+        }
+    }
+}
+
+/// Checks that the last attribute value of a policy is not above its maximum
+/// number of attribute creations.
+pub(crate) fn check_capacity(last: u32, max: u32) -> Result<(), PolicyProblem> {
+    if last > max {
+        return Err(PolicyProblem::CapacityExceeded { last, max });
+    }
+    Ok(())
+}
+
+/// Checks the values of the given attributes and the reclaimed values of
+/// their policy, whose last attribute value is `last`.
+fn check_values<'a>(
+    attributes: impl IntoIterator<Item = (Attribute, &'a [u32])>,
+    last: u32,
+    max: u32,
+    reclaimed_values: &[u32],
+    problems: &mut Vec<PolicyProblem>,
+) {
+    let mut owners = HashMap::new();
+    for (attribute, values) in attributes {
+        if values.is_empty() {
+            problems.push(PolicyProblem::NoValue {
+                attribute: attribute.clone(),
+            });
+        }
+        for value in values {
+            if *value > last {
+                problems.push(PolicyProblem::ValueOutOfRange {
+                    attribute: attribute.clone(),
+                    value: *value,
+                });
+            }
+            if let Some(owner) = owners.insert(*value, attribute.clone()) {
+                let (first, second) = if owner < attribute {
+                    (owner, attribute.clone())
+                } else {
+                    (attribute.clone(), owner)
+                };
+                problems.push(PolicyProblem::SharedValue {
+                    value: *value,
+                    first,
+                    second,
+                });
+            }
+        }
+    }
+
+    for (i, value) in reclaimed_values.iter().enumerate() {
+        if *value > last
+            || owners.contains_key(value)
+            || (i > 0 && reclaimed_values[i - 1] >= *value)
+        {
+            problems.push(PolicyProblem::InvalidReclaimedValue { value: *value });
+        }
+    }
+
+    if let Err(problem) = check_capacity(last, max) {
+        problems.push(problem);
+    }
+}
+
+impl Policy {
+    /// Returns the inconsistencies of this policy, which is consistent if
+    /// none is returned. Policies modified through this library are always
+    /// consistent.
+    ///
+    /// The axes always list the attributes of this policy: only the values
+    /// of the attributes may be inconsistent, e.g. if the maximum number of
+    /// attribute creations is lowered. Problems are sorted by kind.
+    #[must_use]
+    pub fn check_consistency(&self) -> Vec<PolicyProblem> {
+        let mut problems = Vec::new();
+        check_values(
+            self.attribute_views()
+                .map(|attribute| (attribute.to_attribute(), attribute.values())),
+            self.last_attribute_value,
+            self.max_attribute_creations,
+            &self.reclaimed_values,
+            &mut problems,
+        );
+        problems.sort();
+        problems
+    }
+}
+
+impl UncheckedPolicy {
+    /// Returns the inconsistencies of this policy, as
+    /// `Policy::check_consistency` does, also checking that its axes list its
+    /// attributes.
+    pub(crate) fn check_consistency(&self) -> Vec<PolicyProblem> {
+        let mut problems = Vec::new();
+
+        if self.axes.len() > MAX_AXES {
+            problems.push(PolicyProblem::TooManyAxes {
+                count: self.axes.len(),
+            });
+        }
+
+        // attributes listed by their axis, by axis and name
+        let mut listed = HashSet::with_capacity(self.attributes.len());
+        for (axis, axis_parameters) in &self.axes {
+            for name in &axis_parameters.attribute_names {
+                if !listed.insert((axis.as_str(), name.as_str())) {
+                    problems.push(PolicyProblem::DuplicateAttribute {
+                        axis: axis.clone(),
+                        name: name.clone(),
+                    });
+                } else if !self
+                    .attributes
+                    .contains_key(&AttributeRef::new(axis, name) as &dyn AttributeKey)
+                {
+                    problems.push(PolicyProblem::UndefinedAttribute {
+                        axis: axis.clone(),
+                        name: name.clone(),
+                    });
+                }
+            }
+        }
+
+        for attribute in self.attributes.keys() {
+            if !listed.contains(&(attribute.axis.as_str(), &*attribute.name)) {
+                problems.push(PolicyProblem::OrphanAttribute {
+                    attribute: attribute.clone(),
+                });
+            }
+        }
+
+        check_values(
+            self.attributes
+                .iter()
+                .map(|(attribute, parameters)| (attribute.clone(), &parameters.values[..])),
+            self.last_attribute_value,
+            self.max_attribute_creations,
+            &self.reclaimed_values,
+            &mut problems,
+        );
+
+        problems.sort();
+        problems
+    }
+
+    /// Returns the first problem of this policy, which is checked before it
+    /// is converted so that a corrupted policy is not loaded.
+    pub(crate) fn check_structure(&self) -> Result<(), PolicyProblem> {
+        match self.check_consistency().into_iter().next() {
+            Some(problem) => Err(problem),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{tests::policy, Error};
+
+    #[test]
+    fn test_check_consistency() -> Result<(), Error> {
+        let policy = policy()?;
+        assert!(policy.check_consistency().is_empty());
+
+        let hr = Attribute::new("Department", "HR");
+        let fin = Attribute::new("Department", "FIN");
+        let hr_value = policy.attribute_current_value(&hr)?;
+        let mut policy = policy.to_unchecked();
+        assert!(policy.check_consistency().is_empty());
+        policy
+            .attributes
+            .get_mut(&fin)
+            .unwrap()
+            .values
+            .extend([hr_value, 42]);
+        policy
+            .axes
+            .get_mut("Department")
+            .unwrap()
+            .attribute_names
+            .push("Sales".into());
+        assert_eq!(
+            vec![
+                PolicyProblem::UndefinedAttribute {
+                    axis: AxisName::new("Department")?,
+                    name: "Sales".into(),
+                },
+                PolicyProblem::ValueOutOfRange {
+                    attribute: fin.clone(),
+                    value: 42
+                },
+                PolicyProblem::SharedValue {
+                    value: hr_value,
+                    first: fin,
+                    second: hr,
+                },
+            ],
+            policy.check_consistency()
+        );
+        Ok(())
+    }
+    #[test]
+    fn test_capacity_exceeded() -> Result<(), Error> {
+        let mut policy = policy()?;
+        policy.max_attribute_creations = 5;
+        assert_eq!(
+            vec![PolicyProblem::CapacityExceeded { last: 7, max: 5 }],
+            policy.check_consistency()
+        );
+        assert_eq!(0, policy.remaining_attribute_creations());
+        assert!(matches!(
+            policy.rotate(&Attribute::new("Department", "HR")),
+            Err(Error::CapacityOverflow)
+        ));
+        Ok(())
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_capacity_exceeded_rejected() -> Result<(), Error> {
+        use crate::PolicyRef;
+
+        let mut policy = policy()?;
+        policy.max_attribute_creations = 5;
+        let message = "last attribute value 7 is above the maximum number of attribute creations 5";
+        let json = policy.to_string();
+        for error in [
+            Policy::parse_and_convert(json.as_bytes()).unwrap_err(),
+            PolicyRef::from_json(json.as_bytes()).unwrap_err(),
+            Policy::from_bytes(&policy.to_bytes()?).unwrap_err(),
+        ] {
+            assert!(error.to_string().contains(message), "{error}");
+        }
+
+        let legacy_policy = include_str!("../tests/legacy_policy.json").replace(
+            r#""max_attribute_creations":100"#,
+            r#""max_attribute_creations":5"#,
+        );
+        let error = Policy::parse_and_convert(legacy_policy.as_bytes()).unwrap_err();
+        assert!(error.to_string().contains(message), "{error}");
+        Ok(())
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_corrupted_policy_rejected() -> Result<(), Error> {
+        let hr = Attribute::new("Department", "HR");
+        let fin = Attribute::new("Department", "FIN");
+
+        let mut shared = policy()?;
+        let hr_value = shared.attribute_current_value(&hr)?;
+        let fin_id = shared.attribute_id(&fin)?;
+        shared.attributes[&fin_id].parameters.values.push(hr_value);
+        let mut orphan = policy()?.to_unchecked();
+        orphan
+            .axes
+            .get_mut("Department")
+            .unwrap()
+            .attribute_names
+            .retain(|name| name != "FIN");
+        let mut undefined = policy()?.to_unchecked();
+        undefined
+            .axes
+            .get_mut("Department")
+            .unwrap()
+            .attribute_names
+            .push("Sales".into());
+        for (policy, message) in [
+            (&shared.to_unchecked(), "is given to both"),
+            (&orphan, "is not listed by its axis"),
+            (&undefined, "lists undefined attribute Sales"),
+        ] {
+            let json = serde_json::to_string(policy).map_err(Error::DeserializationError)?;
+            for error in [
+                Policy::parse_and_convert(json.as_bytes()).unwrap_err(),
+                Policy::parse_strict(json.as_bytes()).unwrap_err(),
+                serde_json::from_str::<Policy>(&json)
+                    .map_err(Error::DeserializationError)
+                    .unwrap_err(),
+            ] {
+                assert!(error.to_string().contains(message), "{error}");
+            }
+        }
+        let error = Policy::from_bytes(&shared.to_bytes()?).unwrap_err();
+        assert!(error.to_string().contains("is given to both"), "{error}");
+
+        let legacy_policy = include_str!("../tests/legacy_policy.json")
+            .replace(r#""Department::FIN":[7]"#, r#""Department::FIN":[7,1]"#);
+        let error = Policy::parse_and_convert(legacy_policy.as_bytes()).unwrap_err();
+        assert!(error.to_string().contains("is given to both"), "{error}");
+
+        // values given again after being reclaimed are not ordered
+        let mut rotated = policy()?;
+        rotated.rotate(&hr)?;
+        let mut unordered = rotated.to_unchecked();
+        unordered.attributes.get_mut(&hr).unwrap().values.reverse();
+        let json = serde_json::to_string(&unordered).map_err(Error::DeserializationError)?;
+        let policy = Policy::parse_and_convert(json.as_bytes())?;
+        assert_eq!(Ok(policy), Policy::try_from(unordered));
+        Ok(())
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_large_axis_checked() -> Result<(), Error> {
+        use alloc::format;
+
+        use crate::{EncryptionHint, PolicyAxis};
+
+        let names = (0..40_000)
+            .map(|i| format!("Attribute {i}"))
+            .collect::<Vec<_>>();
+        let mut policy = Policy::new(40_000);
+        policy.add_axis(PolicyAxis::new(
+            "Large",
+            names
+                .iter()
+                .map(|name| (name.as_str(), EncryptionHint::Classic))
+                .collect(),
+            false,
+        ))?;
+        let json = policy.to_string();
+        assert_eq!(policy, Policy::parse_and_convert(json.as_bytes())?);
+        assert_eq!(policy, Policy::from_bytes(&policy.to_bytes()?)?);
+
+        let mut duplicate = policy.to_unchecked();
+        let axis = duplicate.axes.get_mut("Large").unwrap();
+        axis.attribute_names.push("Attribute 0".into());
+        assert_eq!(
+            vec![PolicyProblem::DuplicateAttribute {
+                axis: AxisName::new("Large")?,
+                name: "Attribute 0".into(),
+            }],
+            duplicate.check_consistency()
+        );
+        let mut orphan = policy.to_unchecked();
+        let axis = orphan.axes.get_mut("Large").unwrap();
+        axis.attribute_names.pop();
+        assert_eq!(
+            vec![PolicyProblem::OrphanAttribute {
+                attribute: Attribute::new("Large", "Attribute 39999"),
+            }],
+            orphan.check_consistency()
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,238 @@
+//! Comparison and reconciliation of policies.
+
+use alloc::{format, string::ToString, vec::Vec};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    policy::MAX_AXES, storage::AxisEntry, Attribute, AttributeId, AxisId, AxisName, Error, HashMap,
+    HashSet, Policy,
+};
+
+/// Differences between two policies, as returned by `Policy::diff`.
+///
+/// All lists are sorted.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PolicyDiff {
+    /// Axes only present in the other policy
+    pub added_axes: Vec<AxisName>,
+    /// Axes only present in the original policy
+    pub removed_axes: Vec<AxisName>,
+    /// Attributes only present in the other policy
+    pub added_attributes: Vec<Attribute>,
+    /// Attributes only present in the original policy
+    pub removed_attributes: Vec<Attribute>,
+    /// Attributes present in both policies with different values
+    pub rotated_attributes: Vec<Attribute>,
+    /// Attributes present in both policies with different encryption hints
+    pub hint_changes: Vec<Attribute>,
+    /// Maximum number of attribute creations of the other policy, if it
+    /// differs
+    pub max_attribute_creations: Option<u32>,
+}
+
+impl PolicyDiff {
+    /// Returns `true` if the compared policies have the same axes and
+    /// attributes.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+/// Checks that no value is given to several attributes of the policy.
+fn check_unique_values(policy: &Policy) -> Result<(), Error> {
+    let mut owners = HashMap::new();
+    for attribute in policy.attribute_views() {
+        for value in attribute.values() {
+            if let Some(owner) = owners.insert(*value, attribute.as_attribute_ref()) {
+                return Err(Error::MergeConflict(format!(
+                    "value {value} is given to both {owner} and {}",
+                    attribute.as_attribute_ref()
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+impl Policy {
+    /// Returns the differences between this policy and the given one.
+    #[must_use]
+    pub fn diff(&self, other: &Self) -> PolicyDiff {
+        let mut diff = PolicyDiff {
+            added_axes: other
+                .axes()
+                .map(|axis| axis.name())
+                .filter(|axis| !self.axes.contains_key(axis.as_str()))
+                .cloned()
+                .collect(),
+            removed_axes: self
+                .axes()
+                .map(|axis| axis.name())
+                .filter(|axis| !other.axes.contains_key(axis.as_str()))
+                .cloned()
+                .collect(),
+            added_attributes: other
+                .attribute_views()
+                .filter(|attribute| self.exact_attribute(attribute.as_attribute_ref()).is_none())
+                .map(|attribute| attribute.to_attribute())
+                .collect(),
+            removed_attributes: self
+                .attribute_views()
+                .filter(|attribute| {
+                    other
+                        .exact_attribute(attribute.as_attribute_ref())
+                        .is_none()
+                })
+                .map(|attribute| attribute.to_attribute())
+                .collect(),
+            max_attribute_creations: (self.max_attribute_creations
+                != other.max_attribute_creations)
+                .then_some(other.max_attribute_creations),
+            ..PolicyDiff::default()
+        };
+        for attribute in self.attribute_views() {
+            if let Some(other_attribute) = other.exact_attribute(attribute.as_attribute_ref()) {
+                if attribute.values() != other_attribute.values() {
+                    diff.rotated_attributes.push(attribute.to_attribute());
+                }
+                if attribute.encryption_hint() != other_attribute.encryption_hint() {
+                    diff.hint_changes.push(attribute.to_attribute());
+                }
+            }
+        }
+        diff.added_axes.sort();
+        diff.removed_axes.sort();
+        diff.added_attributes.sort();
+        diff.removed_attributes.sort();
+        diff.rotated_attributes.sort();
+        diff.hint_changes.sort();
+        diff
+    }
+
+    /// Merges the given policy into this one.
+    ///
+    /// The merged policy has the axes and attributes of both policies. An
+    /// attribute present in both policies keeps the longest history of
+    /// values, which must extend the other one: the policies are expected to
+    /// be copies of a common policy modified independently. Hybridized
+    /// encryption is hinted if it is in either policy. The values reclaimed
+    /// in either policy stay reclaimed unless an attribute was given them.
+    ///
+    /// Fails, leaving this policy unchanged, if an axis is hierarchical in
+    /// only one of the policies, if an attribute has diverging histories or
+    /// if a value is given to different attributes.
+    pub fn merge(&mut self, other: &Self) -> Result<(), Error> {
+        let mut merged = self.clone();
+        for axis in other.axes() {
+            match merged.axes.get(axis.name().as_str()) {
+                None => {
+                    if merged.axes.len() >= MAX_AXES {
+                        return Err(Error::CapacityOverflow);
+                    }
+                    merged.axes.insert(
+                        axis.name().clone(),
+                        AxisEntry {
+                            is_hierarchical: axis.is_hierarchical(),
+                            attributes: Vec::new(),
+                        },
+                    );
+                }
+                Some(merged_axis) => {
+                    if merged_axis.is_hierarchical != axis.is_hierarchical() {
+                        return Err(Error::MergeConflict(format!(
+                            "axis {} is hierarchical in only one of the policies",
+                            axis.name()
+                        )));
+                    }
+                }
+            }
+        }
+
+        for attribute in other.attribute_views() {
+            match merged.exact_attribute(attribute.as_attribute_ref()) {
+                None => {
+                    let axis = merged
+                        .axes
+                        .get_index_of(attribute.axis().as_str())
+                        .ok_or_else(|| Error::InvalidAxis(attribute.axis().to_string()))?;
+                    let id = AttributeId {
+                        axis: AxisId::from_index(axis),
+                        value: attribute.id().value,
+                    };
+                    if let Some(owner) = merged.attribute_view(id) {
+                        return Err(Error::MergeConflict(format!(
+                            "value {} is given to both {} and {}",
+                            id.value,
+                            owner.as_attribute_ref(),
+                            attribute.as_attribute_ref()
+                        )));
+                    }
+                    merged.attributes.insert(id, attribute.entry.clone());
+                    merged.names.insert(&merged.attributes, id);
+                }
+                Some(merged_attribute) => {
+                    let id = merged_attribute.id();
+                    let parameters = &attribute.entry.parameters;
+                    let merged_parameters = &mut merged.attributes[&id].parameters;
+                    merged_parameters.encryption_hint =
+                        merged_parameters.encryption_hint | parameters.encryption_hint;
+                    // Values are stored from the first one to the current one.
+                    if parameters.values.starts_with(&merged_parameters.values) {
+                        merged_parameters.values.clone_from(&parameters.values);
+                        merged_parameters.retired_epochs = parameters.retired_epochs;
+                    } else if !merged_parameters.values.starts_with(&parameters.values) {
+                        return Err(Error::MergeConflict(format!(
+                            "attribute {} has diverging histories",
+                            attribute.as_attribute_ref()
+                        )));
+                    }
+                }
+            }
+        }
+
+        // attributes only listed by the other policy are listed last
+        for axis in other.axes() {
+            let Some(index) = merged.axes.get_index_of(axis.name().as_str()) else {
+                continue;
+            };
+            for attribute in axis.attributes() {
+                let id = merged.names.find(
+                    &merged.attributes,
+                    AxisId::from_index(index),
+                    attribute.name(),
+                );
+                if let (Some(id), Some((_, merged_axis))) = (id, merged.axes.get_index_mut(index)) {
+                    if !merged_axis.attributes.contains(&id) {
+                        merged_axis.attributes.push(id);
+                    }
+                }
+            }
+        }
+
+        check_unique_values(&merged)?;
+
+        // values reclaimed in either policy, unless given again by the other
+        merged.reclaimed_values.extend(&other.reclaimed_values);
+        merged.reclaimed_values.sort_unstable();
+        merged.reclaimed_values.dedup();
+        let used = merged
+            .attributes
+            .values()
+            .flat_map(|entry| entry.parameters.values.iter().copied())
+            .collect::<HashSet<_>>();
+        merged
+            .reclaimed_values
+            .retain(|value| !used.contains(value));
+
+        merged.last_attribute_value = merged.last_attribute_value.max(other.last_attribute_value);
+        merged.max_attribute_creations = merged
+            .max_attribute_creations
+            .max(other.max_attribute_creations);
+        *self = merged;
+        Ok(())
+    }
+}
@@ -1,14 +1,30 @@
 //! Define this crate error type.
 
+use alloc::{
+    format,
+    string::{String, ToString},
+};
+#[cfg(feature = "node")]
+use napi::Status;
+#[cfg(feature = "python")]
+use pyo3::{
+    exceptions::{PyKeyError, PyRuntimeError, PyValueError},
+    PyErr,
+};
 use thiserror::Error;
 #[cfg(feature = "wasm_bindgen")]
 use wasm_bindgen::JsValue;
 
+#[cfg(feature = "wasm_bindgen")]
+use crate::interfaces::wasm_bindgen::PolicyError;
+
 /// Crate error type.
 #[derive(Error, Debug)]
 pub enum Error {
     #[error("attribute not found: {0}")]
     AttributeNotFound(String),
+    #[error("attribute {0} has no value at epoch {1}")]
+    UnknownEpoch(String, u32),
     #[error("{} is missing{}",
         .item.clone().unwrap_or_else(|| "attribute".to_string()),
         match .axis_name {
@@ -27,19 +43,151 @@ pub enum Error {
     CapacityOverflow,
     #[error("policy {0} already exists")]
     ExistingPolicy(String),
+    #[error("policy {0} not found")]
+    PolicyNotFound(String),
+    #[error("policy version mismatch: expected {expected}, found {actual}")]
+    VersionMismatch { expected: u64, actual: u64 },
+    #[error("storage error: {0}")]
+    StorageError(String),
     #[error("invalid boolean expression: {0}")]
     InvalidBooleanExpression(String),
     #[error("invalid attribute: {0}")]
     InvalidAttribute(String),
+    #[error("invalid name: {0}")]
+    InvalidName(String),
     #[error("invalid axis: {0}")]
     InvalidAxis(String),
+    #[error("merge conflict: {0}")]
+    MergeConflict(String),
+    #[error("invalid binary policy: {0}")]
+    InvalidBinaryPolicy(String),
+    #[cfg(feature = "json")]
     #[error("deserialization error: {0}")]
     DeserializationError(serde_json::Error),
+    #[cfg(feature = "serde")]
+    #[error("deserialization error: {0}")]
+    ValueDeserializationError(String),
+    #[cfg(feature = "std")]
+    #[error("I/O error: {0}")]
+    IoError(std::io::Error),
+}
+
+/// JSON deserialization error, located by the JSON pointer of the value it
+/// occurred in, e.g. `/axes/Department/attribute_names/3`.
+#[cfg(feature = "json")]
+pub(crate) struct LocatedJsonError {
+    pointer: String,
+    error: serde_json::Error,
+}
+
+#[cfg(feature = "json")]
+impl core::fmt::Display for LocatedJsonError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if self.pointer.is_empty() {
+            write!(f, "{}", self.error)
+        } else {
+            write!(f, "{}: {}", self.pointer, self.error)
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+impl LocatedJsonError {
+    /// Reports this error of a policy in the current format, along with
+    /// the one reading it in the legacy format.
+    pub(crate) fn or_legacy(self, legacy: Self) -> Error {
+        Error::DeserializationError(serde::de::Error::custom(format!(
+            "{self}; not a legacy policy either: {legacy}"
+        )))
+    }
+}
+
+#[cfg(feature = "json")]
+impl From<LocatedJsonError> for Error {
+    fn from(e: LocatedJsonError) -> Self {
+        Self::DeserializationError(serde::de::Error::custom(e))
+    }
+}
+
+/// Deserializes the given JSON. On failure, it is deserialized again to
+/// locate the error: tracking the path costs an allocation per map key,
+/// which successful calls do not pay.
+#[cfg(feature = "json")]
+pub(crate) fn from_json_slice<'a, T: serde::Deserialize<'a>>(
+    bytes: &'a [u8],
+) -> Result<T, LocatedJsonError> {
+    serde_json::from_slice(bytes).map_err(|error| {
+        let mut deserializer = serde_json::Deserializer::from_slice(bytes);
+        match serde_path_to_error::deserialize::<_, T>(&mut deserializer) {
+            Err(e) => LocatedJsonError {
+                pointer: json_pointer(e.path()),
+                error: e.into_inner(),
+            },
+            // trailing characters
+            Ok(_) => LocatedJsonError {
+                pointer: String::new(),
+                error,
+            },
+        }
+    })
+}
+
+/// Writes the given path as a JSON pointer (RFC 6901).
+#[cfg(feature = "json")]
+fn json_pointer(path: &serde_path_to_error::Path) -> String {
+    use serde_path_to_error::Segment;
+
+    let mut pointer = String::new();
+    for segment in path.iter() {
+        pointer.push('/');
+        match segment {
+            Segment::Seq { index } => pointer.push_str(&index.to_string()),
+            Segment::Map { key } | Segment::Enum { variant: key } => {
+                pointer.push_str(&key.replace('~', "~0").replace('/', "~1"));
+            }
+            Segment::Unknown => pointer.push('?'),
+        }
+    }
+    pointer
 }
 
 #[cfg(feature = "wasm_bindgen")]
 impl From<Error> for JsValue {
     fn from(e: Error) -> Self {
-        Self::from_str(&e.to_string())
+        PolicyError::from(e).into()
+    }
+}
+
+#[cfg(feature = "python")]
+impl From<Error> for PyErr {
+    fn from(e: Error) -> Self {
+        match e {
+            Error::AttributeNotFound(_) | Error::UnknownEpoch(..) | Error::PolicyNotFound(_) => {
+                PyKeyError::new_err(e.to_string())
+            }
+            Error::CapacityOverflow
+            | Error::ExistingPolicy(_)
+            | Error::VersionMismatch { .. }
+            | Error::MergeConflict(_)
+            | Error::StorageError(_)
+            | Error::IoError(_) => PyRuntimeError::new_err(e.to_string()),
+            _ => PyValueError::new_err(e.to_string()),
+        }
+    }
+}
+
+#[cfg(feature = "node")]
+impl From<Error> for napi::Error {
+    fn from(e: Error) -> Self {
+        let status = match e {
+            Error::CapacityOverflow
+            | Error::ExistingPolicy(_)
+            | Error::VersionMismatch { .. }
+            | Error::MergeConflict(_)
+            | Error::StorageError(_)
+            | Error::IoError(_) => Status::GenericFailure,
+            _ => Status::InvalidArg,
+        };
+        Self::new(status, e.to_string())
     }
 }
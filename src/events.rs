@@ -0,0 +1,282 @@
+use crate::{Attribute, AxisName, EncryptionHint, Error, Policy, PolicyAxis};
+use alloc::{boxed::Box, vec::Vec};
+use core::{fmt::Debug, ops::Deref};
+
+/// Modification applied to a policy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PolicyEvent {
+    /// An axis has been added to the policy.
+    AxisAdded {
+        /// Name of the new axis
+        axis: AxisName,
+        /// Attributes of the new axis with their initial value
+        attributes: Vec<(Attribute, u32)>,
+    },
+    /// An attribute has been given a new value.
+    AttributeRotated {
+        /// Rotated attribute
+        attribute: Attribute,
+        /// New value of the attribute
+        value: u32,
+    },
+    /// An attribute has been added to an existing axis.
+    AttributeAdded {
+        /// New attribute
+        attribute: Attribute,
+        /// Initial value of the attribute
+        value: u32,
+    },
+    /// An attribute has been removed from the policy.
+    AttributeRemoved {
+        /// Removed attribute
+        attribute: Attribute,
+    },
+    /// An attribute has been renamed, keeping its values.
+    AttributeRenamed {
+        /// Attribute before its renaming
+        attribute: Attribute,
+        /// Attribute after its renaming
+        renamed: Attribute,
+    },
+}
+
+/// Observer notified of the modifications applied to a policy.
+pub type PolicyObserver = Box<dyn Fn(&PolicyEvent) + Send + Sync>;
+
+/// A `Policy` notifying registered observers of each of its modifications.
+///
+/// Read-only access to the underlying policy is given through `Deref`.
+pub struct ObservedPolicy {
+    policy: Policy,
+    observers: Vec<PolicyObserver>,
+}
+
+impl ObservedPolicy {
+    /// Wraps the given policy.
+    #[must_use]
+    pub fn new(policy: Policy) -> Self {
+        Self {
+            policy,
+            observers: Vec::new(),
+        }
+    }
+
+    /// Registers an observer called after each modification of the policy.
+    pub fn subscribe(&mut self, observer: impl Fn(&PolicyEvent) + Send + Sync + 'static) {
+        self.observers.push(Box::new(observer));
+    }
+
+    /// Returns the underlying policy, dropping the observers.
+    #[must_use]
+    pub fn into_inner(self) -> Policy {
+        self.policy
+    }
+
+    fn notify(&self, event: &PolicyEvent) {
+        for observer in &self.observers {
+            observer(event);
+        }
+    }
+
+    /// Adds the given policy axis to the policy and notifies the observers.
+    pub fn add_axis(&mut self, axis: PolicyAxis) -> Result<(), Error> {
+        let axis_name = self.policy.naming_policy.apply(&axis.name)?.into_owned();
+        self.policy.add_axis(axis)?;
+
+        let axis = self
+            .policy
+            .axis(&axis_name)
+            .ok_or_else(|| Error::InvalidAxis(axis_name.clone()))?;
+        let attributes = axis
+            .attributes()
+            .map(|attribute| (attribute.to_attribute(), attribute.current_value()))
+            .collect();
+        self.notify(&PolicyEvent::AxisAdded {
+            axis: axis.name().clone(),
+            attributes,
+        });
+        Ok(())
+    }
+
+    /// Rotates the given attribute and notifies the observers.
+    pub fn rotate(&mut self, attr: &Attribute) -> Result<(), Error> {
+        self.policy.rotate(attr)?;
+        let attribute = self.policy.canonical_attribute(attr)?;
+        let value = self.policy.attribute_current_value(&attribute)?;
+        self.notify(&PolicyEvent::AttributeRotated { attribute, value });
+        Ok(())
+    }
+
+    /// Adds the given attribute to its axis and notifies the observers.
+    pub fn add_attribute(
+        &mut self,
+        attribute: &Attribute,
+        encryption_hint: EncryptionHint,
+    ) -> Result<(), Error> {
+        let name = self
+            .policy
+            .naming_policy
+            .apply(&attribute.name)?
+            .into_owned();
+        self.policy.add_attribute(attribute, encryption_hint)?;
+        let attribute = self
+            .policy
+            .canonical_attribute(&Attribute::with_shared_axis(&attribute.axis, &name))?;
+        let value = self.policy.attribute_current_value(&attribute)?;
+        self.notify(&PolicyEvent::AttributeAdded { attribute, value });
+        Ok(())
+    }
+
+    /// Removes the given attribute from the policy and notifies the
+    /// observers.
+    pub fn remove_attribute(&mut self, attribute: &Attribute) -> Result<(), Error> {
+        let attribute = self.policy.canonical_attribute(attribute)?;
+        self.policy.remove_attribute(&attribute)?;
+        self.notify(&PolicyEvent::AttributeRemoved { attribute });
+        Ok(())
+    }
+
+    /// Renames the given attribute and notifies the observers.
+    pub fn rename_attribute(&mut self, attribute: &Attribute, new_name: &str) -> Result<(), Error> {
+        let attribute = self.policy.canonical_attribute(attribute)?;
+        let new_name = self.policy.naming_policy.apply(new_name)?.into_owned();
+        self.policy.rename_attribute(&attribute, &new_name)?;
+        let renamed = self
+            .policy
+            .canonical_attribute(&Attribute::with_shared_axis(&attribute.axis, &new_name))?;
+        self.notify(&PolicyEvent::AttributeRenamed { attribute, renamed });
+        Ok(())
+    }
+}
+
+impl Deref for ObservedPolicy {
+    type Target = Policy;
+
+    fn deref(&self) -> &Self::Target {
+        &self.policy
+    }
+}
+
+impl From<Policy> for ObservedPolicy {
+    fn from(policy: Policy) -> Self {
+        Self::new(policy)
+    }
+}
+
+impl Debug for ObservedPolicy {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ObservedPolicy")
+            .field("policy", &self.policy)
+            .field("observers", &self.observers.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{tests::policy, TrimmingRule};
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_observed_policy() -> Result<(), Error> {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let mut policy = ObservedPolicy::new(policy()?);
+        let sink = events.clone();
+        policy.subscribe(move |event| sink.lock().unwrap().push(event.clone()));
+
+        policy.add_axis(PolicyAxis::new(
+            "Country",
+            vec![("France", EncryptionHint::Classic)],
+            false,
+        ))?;
+        let france = Attribute::new("Country", "France");
+        policy.rotate(&france)?;
+        // failed modifications are not notified
+        assert!(policy.rotate(&Attribute::new("Country", "Spain")).is_err());
+
+        assert_eq!(
+            vec![
+                PolicyEvent::AxisAdded {
+                    axis: AxisName::new("Country")?,
+                    attributes: vec![(france.clone(), 8)],
+                },
+                PolicyEvent::AttributeRotated {
+                    attribute: france,
+                    value: 9,
+                },
+            ],
+            *events.lock().unwrap()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_observed_attributes() -> Result<(), Error> {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let mut policy = ObservedPolicy::new(policy()?);
+        let sink = events.clone();
+        policy.subscribe(move |event| sink.lock().unwrap().push(event.clone()));
+
+        let sales = Attribute::new("Department", "Sales");
+        policy.add_attribute(&sales, EncryptionHint::Classic)?;
+        assert!(policy
+            .add_attribute(&sales, EncryptionHint::Classic)
+            .is_err());
+        let hr = Attribute::new("Department", "HR");
+        policy.remove_attribute(&hr)?;
+        assert!(policy.remove_attribute(&hr).is_err());
+        let fin = Attribute::new("Department", "FIN");
+        policy.rename_attribute(&fin, "Finance")?;
+        assert!(policy.rename_attribute(&fin, "Finance").is_err());
+
+        assert_eq!(
+            vec![
+                PolicyEvent::AttributeAdded {
+                    attribute: sales,
+                    value: 8,
+                },
+                PolicyEvent::AttributeRemoved { attribute: hr },
+                PolicyEvent::AttributeRenamed {
+                    attribute: fin,
+                    renamed: Attribute::new("Department", "Finance"),
+                },
+            ],
+            *events.lock().unwrap()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_observed_trimmed_names() -> Result<(), Error> {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let mut policy = policy()?;
+        policy.naming_policy.trimming = TrimmingRule::Trim;
+        let mut policy = ObservedPolicy::new(policy);
+        let sink = events.clone();
+        policy.subscribe(move |event| sink.lock().unwrap().push(event.clone()));
+
+        let sales = Attribute::new("Department", "Sales");
+        policy.add_attribute(
+            &Attribute::new("Department", " Sales "),
+            EncryptionHint::Classic,
+        )?;
+        policy.rename_attribute(&sales, " Marketing ")?;
+
+        assert_eq!(
+            vec![
+                PolicyEvent::AttributeAdded {
+                    attribute: sales.clone(),
+                    value: 8,
+                },
+                PolicyEvent::AttributeRenamed {
+                    attribute: sales,
+                    renamed: Attribute::new("Department", "Marketing"),
+                },
+            ],
+            *events.lock().unwrap()
+        );
+        Ok(())
+    }
+}
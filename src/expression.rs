@@ -0,0 +1,320 @@
+use crate::{AccessPolicy, Attribute, Error, Policy};
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::fmt::Display;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+const ATTRIBUTE: &str = "<axis>::<name>";
+
+/// Error found when validating a boolean expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct ExpressionError {
+    /// Offset in bytes of the erroneous part of the expression
+    pub offset: usize,
+    /// Length in bytes of the erroneous part of the expression, 0 if a token
+    /// is missing at `offset`
+    pub length: usize,
+    /// Tokens that were expected at `offset`
+    pub expected: Vec<String>,
+    /// Description of the error
+    pub message: String,
+}
+
+impl ExpressionError {
+    fn new(offset: usize, length: usize, expected: &[&str], message: String) -> Self {
+        Self {
+            offset,
+            length,
+            expected: expected.iter().map(ToString::to_string).collect(),
+            message,
+        }
+    }
+}
+
+impl Display for ExpressionError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{} at offset {}", self.message, self.offset)?;
+        if !self.expected.is_empty() {
+            write!(f, ", expected one of: {}", self.expected.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Kind of a token of a boolean expression.
+#[derive(Clone, Copy)]
+pub(crate) enum Token {
+    Open,
+    Close,
+    And,
+    Or,
+    Attribute,
+}
+
+/// Splits a boolean expression into tokens in a single pass, yielding their
+/// offset, their text and their kind.
+///
+/// An attribute spans up to the next parenthesis or operator which is not
+/// escaped with a `\`, without its surrounding whitespace.
+pub(crate) struct Tokens<'a> {
+    expression: &'a str,
+    position: usize,
+}
+
+impl<'a> Tokens<'a> {
+    pub(crate) fn new(expression: &'a str) -> Self {
+        Self {
+            expression,
+            position: 0,
+        }
+    }
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = (usize, &'a str, Token);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let bytes = self.expression.as_bytes();
+        let is_operator = |i: usize| matches!(bytes.get(i..i + 2), Some(b"&&" | b"||"));
+        let start = self.position
+            + bytes[self.position..]
+                .iter()
+                .take_while(|byte| byte.is_ascii_whitespace())
+                .count();
+        let (end, token) = match &bytes[start..] {
+            [] => return None,
+            [b'(', ..] => (start + 1, Token::Open),
+            [b')', ..] => (start + 1, Token::Close),
+            [b'&', b'&', ..] => (start + 2, Token::And),
+            [b'|', b'|', ..] => (start + 2, Token::Or),
+            _ => {
+                let mut end = start;
+                while end < bytes.len()
+                    && bytes[end] != b'('
+                    && bytes[end] != b')'
+                    && !is_operator(end)
+                {
+                    // skip the escaped character, whose UTF-8 continuation
+                    // bytes cannot be taken for a parenthesis or an operator
+                    end = if bytes[end] == b'\\' {
+                        (end + 2).min(bytes.len())
+                    } else {
+                        end + 1
+                    };
+                }
+                self.position = end;
+                let text = self.expression[start..end].trim_end();
+                return Some((start, text, Token::Attribute));
+            }
+        };
+        self.position = end;
+        Some((start, &self.expression[start..end], token))
+    }
+}
+
+/// Writes an axis or an attribute name so that it is read back as is in a
+/// boolean expression: `\` and `:` are escaped with a `\` as in `Attribute`,
+/// and so are the parentheses and the characters of the operators.
+pub(crate) struct Escaped<'a>(pub &'a str);
+
+impl Display for Escaped<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut chars = self.0.chars().peekable();
+        while let Some(c) = chars.next() {
+            let is_escaped = match c {
+                '\\' | ':' | '(' | ')' => true,
+                '&' | '|' => chars.peek() == Some(&c),
+                _ => false,
+            };
+            if is_escaped {
+                write!(f, "\\")?;
+            }
+            write!(f, "{c}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses an attribute of a boolean expression, written `Axis::Name` where
+/// the parentheses and the characters of the operators can be escaped with a
+/// `\`, in addition to the escapes of `Attribute::from_str`.
+pub(crate) fn parse_attribute(text: &str) -> Result<Attribute, Error> {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('\\', Some(&next @ ('(' | ')' | '&' | '|'))) => {
+                unescaped.push(next);
+                chars.next();
+            }
+            // left for `Attribute::from_str`
+            ('\\', Some(&next @ ('\\' | ':'))) => {
+                unescaped.push(c);
+                unescaped.push(next);
+                chars.next();
+            }
+            _ => unescaped.push(c),
+        }
+    }
+    unescaped.parse()
+}
+
+/// Returns the span of the attributes of the given boolean expression, or the
+/// first syntax error.
+fn attribute_spans(expression: &str) -> Result<Vec<(usize, usize)>, ExpressionError> {
+    let mut spans = Vec::new();
+    let mut depth = 0_usize;
+    let mut expect_operand = true;
+    for (offset, text, token) in Tokens::new(expression) {
+        match (token, expect_operand) {
+            (Token::Open, true) => depth += 1,
+            (Token::Close, false) if depth > 0 => depth -= 1,
+            (Token::And | Token::Or, false) => expect_operand = true,
+            (Token::Attribute, true) => {
+                if parse_attribute(text).is_err() {
+                    return Err(ExpressionError::new(
+                        offset,
+                        text.len(),
+                        &[ATTRIBUTE],
+                        format!("invalid attribute '{text}'"),
+                    ));
+                }
+                spans.push((offset, offset + text.len()));
+                expect_operand = false;
+            }
+            _ => {
+                let expected: &[&str] = match (expect_operand, depth > 0) {
+                    (true, _) => &["(", ATTRIBUTE],
+                    (false, true) => &["&&", "||", ")"],
+                    (false, false) => &["&&", "||"],
+                };
+                return Err(ExpressionError::new(
+                    offset,
+                    text.len(),
+                    expected,
+                    format!("unexpected '{text}'"),
+                ));
+            }
+        }
+    }
+    if expect_operand {
+        return Err(ExpressionError::new(
+            expression.len(),
+            0,
+            &["(", ATTRIBUTE],
+            "unexpected end of expression".to_string(),
+        ));
+    }
+    if depth > 0 {
+        return Err(ExpressionError::new(
+            expression.len(),
+            0,
+            &[")"],
+            "unclosed parenthesis".to_string(),
+        ));
+    }
+    Ok(spans)
+}
+
+impl AccessPolicy {
+    /// Checks the syntax of the given boolean expression.
+    ///
+    /// On failure, the returned error gives the position of the erroneous
+    /// part of the expression and the tokens expected there.
+    pub fn validate_boolean_expression(boolean_expression: &str) -> Result<(), ExpressionError> {
+        attribute_spans(boolean_expression).map(|_| ())
+    }
+}
+
+impl Policy {
+    /// Checks the syntax of the given boolean expression and that all its
+    /// attributes belong to this policy.
+    ///
+    /// On failure, the returned error gives the position of the erroneous
+    /// part of the expression and the tokens expected there: the attributes
+    /// of the axis for an unknown attribute, or the axes for an unknown axis.
+    pub fn validate_boolean_expression(
+        &self,
+        boolean_expression: &str,
+    ) -> Result<(), ExpressionError> {
+        for (start, end) in attribute_spans(boolean_expression)? {
+            let text = &boolean_expression[start..end];
+            let unknown = |expected: Vec<String>, message: String| ExpressionError {
+                offset: start,
+                length: end - start,
+                expected,
+                message,
+            };
+            let attribute = parse_attribute(text)
+                .map_err(|e| unknown(vec![ATTRIBUTE.to_string()], e.to_string()))?;
+            let Some(axis) = self.axis(&attribute.axis) else {
+                let mut axes = self
+                    .axes()
+                    .map(|axis| format!("{}::", Escaped(axis.name())))
+                    .collect::<Vec<_>>();
+                axes.sort();
+                return Err(unknown(axes, format!("unknown axis '{}'", attribute.axis)));
+            };
+            if self.find_attribute(attribute.as_attribute_ref()).is_none() {
+                return Err(unknown(
+                    axis.attribute_names()
+                        .map(|name| format!("{}::{}", Escaped(axis.name()), Escaped(name)))
+                        .collect(),
+                    format!("unknown attribute '{attribute}'"),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{tests::policy, Error};
+
+    #[test]
+    fn test_validate_boolean_expression() -> Result<(), Error> {
+        for valid in [
+            "Department::HR",
+            " (Department::R&D || Department::FIN) && Security Level::Top Secret ",
+        ] {
+            assert_eq!(Ok(()), AccessPolicy::validate_boolean_expression(valid));
+        }
+
+        for (invalid, offset, expected) in [
+            ("", 0, vec!["(", ATTRIBUTE]),
+            ("Department::HR &&", 17, vec!["(", ATTRIBUTE]),
+            ("(Department::HR", 15, vec![")"]),
+            ("Department::HR)", 14, vec!["&&", "||"]),
+            ("Department::HR && Department", 18, vec![ATTRIBUTE]),
+            ("Department::HR (Department::FIN)", 15, vec!["&&", "||"]),
+        ] {
+            let error = AccessPolicy::validate_boolean_expression(invalid).unwrap_err();
+            assert_eq!(offset, error.offset, "{invalid}: {error}");
+            assert_eq!(expected, error.expected, "{invalid}: {error}");
+        }
+
+        let policy = policy()?;
+        assert_eq!(
+            Ok(()),
+            policy.validate_boolean_expression("Department::HR && Security Level::Protected")
+        );
+        let error = policy
+            .validate_boolean_expression("Department::HR || Department::Sales")
+            .unwrap_err();
+        assert_eq!((18, 17), (error.offset, error.length));
+        assert_eq!(4, error.expected.len());
+        let error = policy
+            .validate_boolean_expression("Country::France")
+            .unwrap_err();
+        assert_eq!(vec!["Department::", "Security Level::"], error.expected);
+        Ok(())
+    }
+}
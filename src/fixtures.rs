@@ -0,0 +1,171 @@
+//! Sample policies for tests and demonstrations.
+
+use crate::{EncryptionHint, Error, Policy, PolicyAxis};
+use alloc::{vec, vec::Vec};
+
+/// Builds an axis whose attributes all use the given encryption hint.
+fn axis(name: &str, attributes: &[&str], hint: EncryptionHint, hierarchical: bool) -> PolicyAxis {
+    PolicyAxis::new(
+        name,
+        attributes.iter().map(|name| (*name, hint)).collect(),
+        hierarchical,
+    )
+}
+
+/// Builds the hierarchical "Security Level" axis with hybridized encryption
+/// hinted for the given top levels.
+fn security_level(levels: &[&str], nb_hybridized: usize) -> PolicyAxis {
+    PolicyAxis::new(
+        "Security Level",
+        levels
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let hint = if i + nb_hybridized >= levels.len() {
+                    EncryptionHint::Hybridized
+                } else {
+                    EncryptionHint::Classic
+                };
+                (*name, hint)
+            })
+            .collect::<Vec<_>>(),
+        true,
+    )
+}
+
+/// Returns the canonical demo policy:
+///
+/// - a hierarchical "Security Level" axis: `Protected` < `Confidential` <
+///   `Top Secret` (hybridized)
+/// - a "Department" axis: `R&D`, `HR`, `MKG`, `FIN`
+///
+/// 100 attribute creations are allowed.
+pub fn demo_policy() -> Result<Policy, Error> {
+    let mut policy = Policy::new(100);
+    policy.add_axis(security_level(
+        &["Protected", "Confidential", "Top Secret"],
+        1,
+    ))?;
+    policy.add_axis(axis(
+        "Department",
+        &["R&D", "HR", "MKG", "FIN"],
+        EncryptionHint::Classic,
+        false,
+    ))?;
+    Ok(policy)
+}
+
+/// Returns a policy modeling a multinational company:
+///
+/// - a hierarchical "Security Level" axis of 5 levels, the 2 highest ones
+///   hybridized
+/// - a "Department" axis of 12 departments
+/// - a "Country" axis of the 27 member states of the European Union
+///
+/// 1000 attribute creations are allowed.
+pub fn enterprise_policy() -> Result<Policy, Error> {
+    let mut policy = Policy::new(1000);
+    policy.add_axis(security_level(
+        &["Public", "Internal", "Confidential", "Secret", "Top Secret"],
+        2,
+    ))?;
+    policy.add_axis(axis(
+        "Department",
+        &[
+            "R&D",
+            "HR",
+            "MKG",
+            "FIN",
+            "Legal",
+            "Sales",
+            "Support",
+            "IT",
+            "Security",
+            "Procurement",
+            "Operations",
+            "Executive",
+        ],
+        EncryptionHint::Classic,
+        false,
+    ))?;
+    policy.add_axis(axis(
+        "Country",
+        &[
+            "Austria",
+            "Belgium",
+            "Bulgaria",
+            "Croatia",
+            "Cyprus",
+            "Czechia",
+            "Denmark",
+            "Estonia",
+            "Finland",
+            "France",
+            "Germany",
+            "Greece",
+            "Hungary",
+            "Ireland",
+            "Italy",
+            "Latvia",
+            "Lithuania",
+            "Luxembourg",
+            "Malta",
+            "Netherlands",
+            "Poland",
+            "Portugal",
+            "Romania",
+            "Slovakia",
+            "Slovenia",
+            "Spain",
+            "Sweden",
+        ],
+        EncryptionHint::Classic,
+        false,
+    ))?;
+    Ok(policy)
+}
+
+/// Returns a policy modeling a hospital:
+///
+/// - a hierarchical "Sensitivity" axis: `Administrative` < `Clinical` <
+///   `Psychiatric` < `Genetic`, the 2 highest ones hybridized
+/// - a "Role" axis: `Nurse`, `Physician`, `Pharmacist`, `Researcher`,
+///   `Billing`
+/// - a "Ward" axis of 8 wards
+///
+/// 500 attribute creations are allowed.
+pub fn hospital_policy() -> Result<Policy, Error> {
+    let mut policy = Policy::new(500);
+    policy.add_axis(PolicyAxis::new(
+        "Sensitivity",
+        vec![
+            ("Administrative", EncryptionHint::Classic),
+            ("Clinical", EncryptionHint::Classic),
+            ("Psychiatric", EncryptionHint::Hybridized),
+            ("Genetic", EncryptionHint::Hybridized),
+        ],
+        true,
+    ))?;
+    policy.add_axis(axis(
+        "Role",
+        &["Nurse", "Physician", "Pharmacist", "Researcher", "Billing"],
+        EncryptionHint::Classic,
+        false,
+    ))?;
+    policy.add_axis(axis(
+        "Ward",
+        &[
+            "Cardiology",
+            "Emergency",
+            "Maternity",
+            "Neurology",
+            "Oncology",
+            "Pediatrics",
+            "Psychiatry",
+            "Radiology",
+        ],
+        EncryptionHint::Classic,
+        false,
+    ))?;
+    Ok(policy)
+}
@@ -0,0 +1,91 @@
+//! Policies parsed from the JSON given to the interfaces, kept so that
+//! repeated calls with the same policy do not parse it again.
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use sha2::{Digest, Sha256};
+
+use crate::{Error, Policy};
+
+/// Number of policies kept, the least recently used being dropped first.
+const CAPACITY: usize = 8;
+
+struct Entry {
+    /// SHA-256 hash of the JSON policy
+    digest: [u8; 32],
+    policy: Arc<Policy>,
+    is_legacy: bool,
+}
+
+static POLICIES: Mutex<VecDeque<Entry>> = Mutex::new(VecDeque::new());
+
+/// Parses a policy as `Policy::parse_and_convert`, also returning `true` if
+/// it uses the legacy format. The policy is shared with the previous calls
+/// given the same bytes.
+pub(crate) fn parse_policy(bytes: &[u8]) -> Result<(Arc<Policy>, bool), Error> {
+    let digest: [u8; 32] = Sha256::digest(bytes).into();
+    {
+        let mut policies = POLICIES.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(index) = policies.iter().position(|entry| entry.digest == digest) {
+            let entry = policies.remove(index).expect("entry found");
+            let found = (entry.policy.clone(), entry.is_legacy);
+            policies.push_front(entry);
+            return Ok(found);
+        }
+    }
+
+    // other calls are not blocked while parsing
+    let (policy, is_legacy) = Policy::parse_and_detect_legacy(bytes)?;
+    let policy = Arc::new(policy);
+    let mut policies = POLICIES.lock().unwrap_or_else(PoisonError::into_inner);
+    if !policies.iter().any(|entry| entry.digest == digest) {
+        policies.truncate(CAPACITY - 1);
+        policies.push_front(Entry {
+            digest,
+            policy: policy.clone(),
+            is_legacy,
+        });
+    }
+    Ok((policy, is_legacy))
+}
+
+/// Parses a policy as `parse_policy`, returning a copy of the shared policy
+/// for callers modifying it.
+#[cfg(any(feature = "jni", feature = "node", test))]
+pub(crate) fn parse_policy_mut(bytes: &[u8]) -> Result<(Policy, bool), Error> {
+    parse_policy(bytes).map(|(policy, is_legacy)| (Arc::unwrap_or_clone(policy), is_legacy))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{tests::policy, Attribute};
+
+    #[test]
+    fn test_parse_policy() -> Result<(), Error> {
+        let mut policy = policy()?;
+        let bytes = serde_json::to_vec(&policy).unwrap();
+        let (first, is_legacy) = parse_policy(&bytes)?;
+        assert!(!is_legacy);
+        assert_eq!(policy, *first);
+        assert!(Arc::ptr_eq(&first, &parse_policy(&bytes)?.0));
+
+        // modifying a copy leaves the shared policy unchanged
+        let (mut copy, _) = parse_policy_mut(&bytes)?;
+        copy.rotate(&Attribute::new("Department", "HR"))?;
+        assert_eq!(policy, *parse_policy(&bytes)?.0);
+
+        policy.rotate(&Attribute::new("Department", "HR"))?;
+        assert_eq!(copy, policy);
+        let bytes = serde_json::to_vec(&policy).unwrap();
+        assert!(!Arc::ptr_eq(&first, &parse_policy(&bytes)?.0));
+
+        let legacy_policy = include_bytes!("../../tests/legacy_policy.json");
+        assert!(parse_policy(legacy_policy)?.1);
+        assert!(parse_policy(b"{}").is_err());
+        Ok(())
+    }
+}
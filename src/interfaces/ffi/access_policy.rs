@@ -1,4 +1,5 @@
-use crate::AccessPolicy;
+use crate::interfaces::ffi::{error::catch_panic, log::read_shared_policy};
+use crate::{AccessPolicy, Attribute};
 use std::ffi::{c_char, c_int};
 
 /// Converts a boolean expression into an access policy.
@@ -13,26 +14,191 @@ pub unsafe extern "C" fn h_parse_boolean_access_policy(
     access_policy_len: *mut c_int,
     boolean_expression_ptr: *const c_char,
 ) -> c_int {
-    let expr = ffi_read_string!("boolean_expression", boolean_expression_ptr);
-    let access_policy = ffi_unwrap!(AccessPolicy::from_boolean_expression(&expr));
+    catch_panic(|| {
+        let expr = ffi_read_string!("boolean_expression", boolean_expression_ptr);
+        let access_policy = ffi_unwrap!(AccessPolicy::from_boolean_expression(&expr));
 
-    let access_policy_bytes = ffi_unwrap!(serde_json::to_vec(&access_policy));
+        let access_policy_bytes = ffi_unwrap!(serde_json::to_vec(&access_policy));
 
-    ffi_write_bytes!(
-        "access policy",
-        &access_policy_bytes,
-        access_policy_ptr,
-        access_policy_len
-    );
+        ffi_write_bytes!(
+            "access policy",
+            &access_policy_bytes,
+            access_policy_ptr,
+            access_policy_len
+        );
 
-    0
+        0
+    })
+}
+
+/// Converts an access policy into a boolean expression.
+///
+/// - `boolean_expression_ptr`  : output boolean expression buffer
+/// - `boolean_expression_len`  : size of the output buffer
+/// - `access_policy_ptr`       : access policy (JSON)
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_access_policy_to_boolean_expression(
+    boolean_expression_ptr: *mut c_char,
+    boolean_expression_len: *mut c_int,
+    access_policy_ptr: *const c_char,
+) -> c_int {
+    catch_panic(|| {
+        let access_policy_string = ffi_read_string!("access policy", access_policy_ptr);
+        let access_policy: AccessPolicy = ffi_unwrap!(serde_json::from_str(&access_policy_string));
+        ffi_ensure!(
+            !matches!(access_policy, AccessPolicy::All),
+            "the `All` access policy has no boolean expression"
+        );
+
+        ffi_write_bytes!(
+            "boolean expression",
+            access_policy.to_string().as_bytes(),
+            boolean_expression_ptr,
+            boolean_expression_len
+        );
+
+        0
+    })
+}
+
+/// Validates a boolean expression, against a policy if one is given.
+///
+/// If the expression is valid, 0 is written to `error_len`. Otherwise, the
+/// error is written to `error_ptr` as a JSON object
+/// `{ "offset", "length", "expected", "message" }` where `offset` and
+/// `length` locate the erroneous part of the expression, in bytes, and
+/// `expected` lists the tokens expected there.
+///
+/// - `error_ptr`               : output error buffer (JSON)
+/// - `error_len`               : size of the output buffer
+/// - `boolean_expression_ptr`  : boolean access policy string
+/// - `policy_ptr`              : policy (JSON), may be null
+/// - `policy_len`              : size of the policy, 0 if there is none
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_validate_boolean_expression(
+    error_ptr: *mut c_char,
+    error_len: *mut c_int,
+    boolean_expression_ptr: *const c_char,
+    policy_ptr: *const c_char,
+    policy_len: c_int,
+) -> c_int {
+    catch_panic(|| {
+        ffi_not_null!(error_len, "error length pointer should not be null");
+        let expr = ffi_read_string!("boolean_expression", boolean_expression_ptr);
+        let res = if policy_ptr.is_null() || policy_len == 0 {
+            AccessPolicy::validate_boolean_expression(&expr)
+        } else {
+            let policy_bytes = ffi_read_bytes!("policy", policy_ptr, policy_len);
+            ffi_unwrap!(read_shared_policy(policy_bytes)).validate_boolean_expression(&expr)
+        };
+
+        match res {
+            Ok(()) => *error_len = 0,
+            Err(error) => {
+                ffi_write_bytes!(
+                    "expression error",
+                    &ffi_unwrap!(serde_json::to_vec(&error)),
+                    error_ptr,
+                    error_len
+                );
+            }
+        }
+
+        0
+    })
+}
+
+/// Converts a boolean expression into the combinations of attributes
+/// granting access: a JSON array of arrays of attributes, the access policy
+/// being the disjunction of the conjunction of the attributes of each array.
+///
+/// - `combinations_ptr`        : output combinations buffer (JSON)
+/// - `combinations_len`        : size of the output buffer
+/// - `policy_ptr`              : policy (JSON)
+/// - `policy_len`              : size of the policy
+/// - `boolean_expression_ptr`  : boolean access policy string
+/// - `follow_hierarchical_axes`: if non-zero, the attributes below those of
+///   the expression on hierarchical axes also grant access
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_access_policy_to_combinations(
+    combinations_ptr: *mut c_char,
+    combinations_len: *mut c_int,
+    policy_ptr: *const c_char,
+    policy_len: c_int,
+    boolean_expression_ptr: *const c_char,
+    follow_hierarchical_axes: c_int,
+) -> c_int {
+    catch_panic(|| {
+        let policy_bytes = ffi_read_bytes!("policy", policy_ptr, policy_len);
+        let policy = ffi_unwrap!(read_shared_policy(policy_bytes));
+        let expr = ffi_read_string!("boolean_expression", boolean_expression_ptr);
+        let access_policy = ffi_unwrap!(policy.parse_access_policy(&expr));
+        let combinations = ffi_unwrap!(
+            access_policy.to_attribute_combinations(&policy, follow_hierarchical_axes != 0)
+        );
+
+        ffi_write_bytes!(
+            "combinations",
+            &ffi_unwrap!(serde_json::to_vec(&combinations)),
+            combinations_ptr,
+            combinations_len
+        );
+
+        0
+    })
+}
+
+/// Checks whether a user holding the given attributes is granted access by
+/// a boolean expression. On a hierarchical axis, an attribute also grants
+/// access to the attributes below it.
+///
+/// Returns 1 if access is granted, 0 if it is not and the opposite of a
+/// status code, e.g. `-FFI_ERROR`, on failure. The error message can be
+/// retrieved with `get_last_error`.
+///
+/// - `policy_ptr`              : policy (JSON)
+/// - `policy_len`              : size of the policy
+/// - `boolean_expression_ptr`  : boolean access policy string
+/// - `attributes_ptr`          : user attributes, written `Axis::Name` and
+///   separated by commas, e.g. `Department::HR,Security Level::Protected`
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_access_policy_is_satisfied(
+    policy_ptr: *const c_char,
+    policy_len: c_int,
+    boolean_expression_ptr: *const c_char,
+    attributes_ptr: *const c_char,
+) -> c_int {
+    let mut is_satisfied = false;
+    let res = catch_panic(|| {
+        let policy_bytes = ffi_read_bytes!("policy", policy_ptr, policy_len);
+        let policy = ffi_unwrap!(read_shared_policy(policy_bytes));
+        let expr = ffi_read_string!("boolean_expression", boolean_expression_ptr);
+        let attributes = ffi_read_string!("attributes", attributes_ptr);
+        let attributes = ffi_unwrap!(attributes
+            .split(',')
+            .filter(|attribute| !attribute.trim().is_empty())
+            .map(str::parse)
+            .collect::<Result<Vec<Attribute>, _>>());
+        let access_policy = ffi_unwrap!(policy.parse_access_policy(&expr));
+        is_satisfied = ffi_unwrap!(access_policy.is_satisfied_by(&policy, &attributes));
+        0
+    });
+    if res == 0 {
+        c_int::from(is_satisfied)
+    } else {
+        -res
+    }
 }
 
 #[cfg(test)]
 mod tests {
 
     use super::*;
-    use crate::interfaces::ffi::error::FfiError;
+    use crate::interfaces::ffi::error::{FfiError, FFI_BUFFER_TOO_SMALL, FFI_ERROR};
     use std::ffi::{c_int, CStr, CString};
 
     #[test]
@@ -70,9 +236,155 @@ mod tests {
         unsafe {
             let ler =
                 h_parse_boolean_access_policy(too_small_ptr, &mut too_small_len, c_str.as_ptr());
-            assert_eq!(98, ler);
+            assert_eq!(FFI_BUFFER_TOO_SMALL, ler);
+            assert_eq!(98, too_small_len);
+        };
+
+        // query the size of the buffer to allocate
+        let mut size = 0;
+        unsafe {
+            let ler =
+                h_parse_boolean_access_policy(std::ptr::null_mut(), &mut size, c_str.as_ptr());
+            assert_eq!(FFI_BUFFER_TOO_SMALL, ler);
+            assert_eq!(98, size);
+        };
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_access_policy_to_boolean_expression() -> Result<(), FfiError> {
+        let access_policy = CString::new(
+            r#"{"And":[{"Attr":"Department::MKG"},{"Or":[{"Attr":"Country::France"},{"Attr":"Country::Spain"}]}]}"#,
+        )?;
+        let mut expr = vec![0u8; 8192];
+        let mut expr_len = expr.len() as c_int;
+        unsafe {
+            let res = h_access_policy_to_boolean_expression(
+                expr.as_mut_ptr().cast(),
+                &mut expr_len,
+                access_policy.as_ptr(),
+            );
+            assert_eq!(0, res);
+        }
+        assert_eq!(
+            b"Department::MKG && (Country::France || Country::Spain)",
+            &expr[..expr_len as usize]
+        );
+
+        let all = CString::new(r#""All""#)?;
+        let mut expr_len = expr.len() as c_int;
+        unsafe {
+            let res = h_access_policy_to_boolean_expression(
+                expr.as_mut_ptr().cast(),
+                &mut expr_len,
+                all.as_ptr(),
+            );
+            assert_eq!(1, res);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_boolean_expression() -> Result<(), FfiError> {
+        let policy = serde_json::to_vec(&crate::tests::policy().unwrap()).unwrap();
+        let mut error = vec![0u8; 8192];
+        let error_ptr = error.as_mut_ptr().cast();
+
+        for (expr, policy_ptr, policy_len, expected) in [
+            ("Department::HR", std::ptr::null(), 0, None),
+            ("Department::Sales", std::ptr::null(), 0, None),
+            (
+                "Department::HR",
+                policy.as_ptr().cast(),
+                policy.len() as c_int,
+                None,
+            ),
+            (
+                "Department::HR ||",
+                std::ptr::null(),
+                0,
+                Some(
+                    r#"{"offset":17,"length":0,"expected":["(","<axis>::<name>"],"message":"unexpected end of expression"}"#,
+                ),
+            ),
+            (
+                "Department::Sales",
+                policy.as_ptr().cast(),
+                policy.len() as c_int,
+                Some(
+                    r#"{"offset":0,"length":17,"expected":["Department::R&D","Department::HR","Department::MKG","Department::FIN"],"message":"unknown attribute 'Department::Sales'"}"#,
+                ),
+            ),
+        ] {
+            let c_str = CString::new(expr)?;
+            let mut error_len = error.len() as c_int;
+            let res = unsafe {
+                h_validate_boolean_expression(
+                    error_ptr,
+                    &mut error_len,
+                    c_str.as_ptr(),
+                    policy_ptr,
+                    policy_len,
+                )
+            };
+            assert_eq!(0, res);
+            match expected {
+                None => assert_eq!(0, error_len, "{expr}"),
+                Some(expected) => {
+                    assert_eq!(expected.as_bytes(), &error[..error_len as usize], "{expr}")
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_access_policy_to_combinations() -> Result<(), FfiError> {
+        let policy = serde_json::to_vec(&crate::tests::policy().unwrap()).unwrap();
+        let c_str = CString::new("Department::HR && Security Level::Confidential")?;
+        let mut combinations = vec![0u8; 8192];
+        let mut combinations_len = combinations.len() as c_int;
+
+        let res = unsafe {
+            h_access_policy_to_combinations(
+                combinations.as_mut_ptr().cast(),
+                &mut combinations_len,
+                policy.as_ptr().cast(),
+                policy.len() as c_int,
+                c_str.as_ptr(),
+                1,
+            )
         };
+        assert_eq!(0, res);
+        assert_eq!(
+            r#"[["Department::HR","Security Level::Confidential"],["Department::HR","Security Level::Protected"]]"#.as_bytes(),
+            &combinations[..combinations_len as usize]
+        );
+        Ok(())
+    }
 
+    #[test]
+    fn test_access_policy_is_satisfied() -> Result<(), FfiError> {
+        let policy = serde_json::to_vec(&crate::tests::policy().unwrap()).unwrap();
+        let expr = CString::new("Department::HR && Security Level::Confidential")?;
+        for (attributes, expected) in [
+            ("Department::HR, Security Level::Top Secret", 1),
+            ("Department::HR,Security Level::Protected", 0),
+            ("", 0),
+            ("Department::Sales", -FFI_ERROR),
+        ] {
+            let attributes = CString::new(attributes)?;
+            let res = unsafe {
+                h_access_policy_is_satisfied(
+                    policy.as_ptr().cast(),
+                    policy.len() as c_int,
+                    expr.as_ptr(),
+                    attributes.as_ptr(),
+                )
+            };
+            assert_eq!(expected, res, "{attributes:?}");
+        }
         Ok(())
     }
 }
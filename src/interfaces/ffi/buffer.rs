@@ -0,0 +1,422 @@
+//! Variants of the FFI functions allocating their output buffer on the Rust
+//! side, sparing callers the size query and retry.
+//!
+//! Each `_alloc` function takes a pointer to the output pointer where its
+//! counterpart takes the output buffer. On success, the output pointer is
+//! set to a buffer owned by the library, which must be released with
+//! `h_free_buffer`, and its size is written to the length out-parameter.
+//! The output is computed only once.
+
+use std::{
+    cell::RefCell,
+    ffi::{c_char, c_int},
+    ptr,
+};
+
+use crate::interfaces::ffi::{
+    access_policy::{
+        h_access_policy_to_boolean_expression, h_access_policy_to_combinations,
+        h_parse_boolean_access_policy, h_validate_boolean_expression,
+    },
+    error::catch_panic,
+    handle::{h_policy_handle_to_json, PolicyHandle},
+    policy::{
+        h_policy_add_axis, h_policy_attribute_values, h_policy_attributes, h_policy_from_bytes,
+        h_policy_new, h_policy_rotate_attribute, h_policy_to_bytes, h_policy_to_json,
+        h_rotate_attributes,
+    },
+};
+
+thread_local! {
+    /// Bytes written by the FFI function called by an `_alloc` function, `None`
+    /// outside of such a call.
+    static CAPTURED: RefCell<Option<Vec<u8>>> = const { RefCell::new(None) };
+}
+
+/// Keeps the given bytes for the library-allocated output buffer if called
+/// within an `_alloc` function. Returns `false` otherwise.
+pub fn capture(bytes: &[u8]) -> bool {
+    CAPTURED.with(|captured| match captured.borrow_mut().as_mut() {
+        Some(captured) => {
+            captured.clear();
+            captured.extend_from_slice(bytes);
+            true
+        }
+        None => false,
+    })
+}
+
+/// Calls `f` with a null output pointer, capturing its output in a buffer
+/// allocated by the library.
+unsafe fn call_allocating(
+    buffer_ptr: *mut *mut c_char,
+    buffer_len: *mut c_int,
+    f: impl FnOnce(*mut c_char, *mut c_int) -> c_int,
+) -> c_int {
+    ffi_not_null!(buffer_ptr, "output buffer pointer should not be null");
+    ffi_not_null!(buffer_len, "output length pointer should not be null");
+    CAPTURED.with(|captured| *captured.borrow_mut() = Some(Vec::new()));
+    let res = f(ptr::null_mut(), buffer_len);
+    let bytes = CAPTURED
+        .with(|captured| captured.borrow_mut().take())
+        .unwrap_or_default();
+    *buffer_ptr = ptr::null_mut();
+    if res != 0 {
+        return res;
+    }
+    *buffer_len = bytes.len() as c_int;
+    if !bytes.is_empty() {
+        *buffer_ptr = Box::into_raw(bytes.into_boxed_slice()).cast();
+    }
+    0
+}
+
+/// Releases a buffer allocated by an `_alloc` function. Does nothing if the
+/// pointer is null.
+///
+/// - `buffer_ptr`  : buffer to release
+/// - `buffer_len`  : size of the buffer, as returned by the `_alloc` function
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_free_buffer(buffer_ptr: *mut c_char, buffer_len: c_int) {
+    if !buffer_ptr.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(
+            buffer_ptr.cast::<u8>(),
+            buffer_len as usize,
+        )));
+    }
+}
+
+/// Variant of `h_parse_boolean_access_policy` allocating the output buffer, to release with
+/// `h_free_buffer`.
+///
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_parse_boolean_access_policy_alloc(
+    access_policy_ptr: *mut *mut c_char,
+    access_policy_len: *mut c_int,
+    boolean_expression_ptr: *const c_char,
+) -> c_int {
+    catch_panic(|| {
+        call_allocating(access_policy_ptr, access_policy_len, |ptr, len| {
+            h_parse_boolean_access_policy(ptr, len, boolean_expression_ptr)
+        })
+    })
+}
+
+/// Variant of `h_access_policy_to_boolean_expression` allocating the output buffer, to release with
+/// `h_free_buffer`.
+///
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_access_policy_to_boolean_expression_alloc(
+    boolean_expression_ptr: *mut *mut c_char,
+    boolean_expression_len: *mut c_int,
+    access_policy_ptr: *const c_char,
+) -> c_int {
+    catch_panic(|| {
+        call_allocating(
+            boolean_expression_ptr,
+            boolean_expression_len,
+            |ptr, len| h_access_policy_to_boolean_expression(ptr, len, access_policy_ptr),
+        )
+    })
+}
+
+/// Variant of `h_validate_boolean_expression` allocating the output buffer, to release with
+/// `h_free_buffer`.
+///
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_validate_boolean_expression_alloc(
+    error_ptr: *mut *mut c_char,
+    error_len: *mut c_int,
+    boolean_expression_ptr: *const c_char,
+    policy_ptr: *const c_char,
+    policy_len: c_int,
+) -> c_int {
+    catch_panic(|| {
+        call_allocating(error_ptr, error_len, |ptr, len| {
+            h_validate_boolean_expression(ptr, len, boolean_expression_ptr, policy_ptr, policy_len)
+        })
+    })
+}
+
+/// Variant of `h_access_policy_to_combinations` allocating the output buffer, to release with
+/// `h_free_buffer`.
+///
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_access_policy_to_combinations_alloc(
+    combinations_ptr: *mut *mut c_char,
+    combinations_len: *mut c_int,
+    policy_ptr: *const c_char,
+    policy_len: c_int,
+    boolean_expression_ptr: *const c_char,
+    follow_hierarchical_axes: c_int,
+) -> c_int {
+    catch_panic(|| {
+        call_allocating(combinations_ptr, combinations_len, |ptr, len| {
+            h_access_policy_to_combinations(
+                ptr,
+                len,
+                policy_ptr,
+                policy_len,
+                boolean_expression_ptr,
+                follow_hierarchical_axes,
+            )
+        })
+    })
+}
+
+/// Variant of `h_policy_new` allocating the output buffer, to release with
+/// `h_free_buffer`.
+///
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_policy_new_alloc(
+    policy_ptr: *mut *mut c_char,
+    policy_len: *mut c_int,
+    max_attribute_creations: c_int,
+) -> c_int {
+    catch_panic(|| {
+        call_allocating(policy_ptr, policy_len, |ptr, len| {
+            h_policy_new(ptr, len, max_attribute_creations)
+        })
+    })
+}
+
+/// Variant of `h_policy_add_axis` allocating the output buffer, to release with
+/// `h_free_buffer`.
+///
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_policy_add_axis_alloc(
+    updated_policy_ptr: *mut *mut c_char,
+    updated_policy_len: *mut c_int,
+    current_policy_ptr: *const c_char,
+    current_policy_len: c_int,
+    axis_ptr: *const c_char,
+) -> c_int {
+    catch_panic(|| {
+        call_allocating(updated_policy_ptr, updated_policy_len, |ptr, len| {
+            h_policy_add_axis(ptr, len, current_policy_ptr, current_policy_len, axis_ptr)
+        })
+    })
+}
+
+/// Variant of `h_policy_rotate_attribute` allocating the output buffer, to release with
+/// `h_free_buffer`.
+///
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_policy_rotate_attribute_alloc(
+    updated_policy_ptr: *mut *mut c_char,
+    updated_policy_len: *mut c_int,
+    current_policy_ptr: *const c_char,
+    current_policy_len: c_int,
+    attribute_ptr: *const c_char,
+) -> c_int {
+    catch_panic(|| {
+        call_allocating(updated_policy_ptr, updated_policy_len, |ptr, len| {
+            h_policy_rotate_attribute(
+                ptr,
+                len,
+                current_policy_ptr,
+                current_policy_len,
+                attribute_ptr,
+            )
+        })
+    })
+}
+
+/// Variant of `h_policy_to_json` allocating the output buffer, to release with
+/// `h_free_buffer`.
+///
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_policy_to_json_alloc(
+    json_ptr: *mut *mut c_char,
+    json_len: *mut c_int,
+    policy_ptr: *const c_char,
+    policy_len: c_int,
+) -> c_int {
+    catch_panic(|| {
+        call_allocating(json_ptr, json_len, |ptr, len| {
+            h_policy_to_json(ptr, len, policy_ptr, policy_len)
+        })
+    })
+}
+
+/// Variant of `h_policy_to_bytes` allocating the output buffer, to release with
+/// `h_free_buffer`.
+///
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_policy_to_bytes_alloc(
+    bytes_ptr: *mut *mut c_char,
+    bytes_len: *mut c_int,
+    policy_ptr: *const c_char,
+    policy_len: c_int,
+) -> c_int {
+    catch_panic(|| {
+        call_allocating(bytes_ptr, bytes_len, |ptr, len| {
+            h_policy_to_bytes(ptr, len, policy_ptr, policy_len)
+        })
+    })
+}
+
+/// Variant of `h_policy_from_bytes` allocating the output buffer, to release with
+/// `h_free_buffer`.
+///
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_policy_from_bytes_alloc(
+    json_ptr: *mut *mut c_char,
+    json_len: *mut c_int,
+    bytes_ptr: *const c_char,
+    bytes_len: c_int,
+) -> c_int {
+    catch_panic(|| {
+        call_allocating(json_ptr, json_len, |ptr, len| {
+            h_policy_from_bytes(ptr, len, bytes_ptr, bytes_len)
+        })
+    })
+}
+
+/// Variant of `h_policy_attribute_values` allocating the output buffer, to release with
+/// `h_free_buffer`.
+///
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_policy_attribute_values_alloc(
+    values_ptr: *mut *mut c_char,
+    values_len: *mut c_int,
+    policy_ptr: *const c_char,
+    policy_len: c_int,
+    attribute_ptr: *const c_char,
+) -> c_int {
+    catch_panic(|| {
+        call_allocating(values_ptr, values_len, |ptr, len| {
+            h_policy_attribute_values(ptr, len, policy_ptr, policy_len, attribute_ptr)
+        })
+    })
+}
+
+/// Variant of `h_policy_attributes` allocating the output buffer, to release with
+/// `h_free_buffer`.
+///
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_policy_attributes_alloc(
+    attributes_ptr: *mut *mut c_char,
+    attributes_len: *mut c_int,
+    policy_ptr: *const c_char,
+    policy_len: c_int,
+    axis_ptr: *const c_char,
+) -> c_int {
+    catch_panic(|| {
+        call_allocating(attributes_ptr, attributes_len, |ptr, len| {
+            h_policy_attributes(ptr, len, policy_ptr, policy_len, axis_ptr)
+        })
+    })
+}
+
+/// Variant of `h_rotate_attributes` allocating the output buffer, to release with
+/// `h_free_buffer`.
+///
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_rotate_attributes_alloc(
+    updated_policy_ptr: *mut *mut c_char,
+    updated_policy_len: *mut c_int,
+    current_policy_ptr: *const c_char,
+    current_policy_len: c_int,
+    attributes_ptr: *const c_char,
+) -> c_int {
+    catch_panic(|| {
+        call_allocating(updated_policy_ptr, updated_policy_len, |ptr, len| {
+            h_rotate_attributes(
+                ptr,
+                len,
+                current_policy_ptr,
+                current_policy_len,
+                attributes_ptr,
+            )
+        })
+    })
+}
+
+/// Variant of `h_policy_handle_to_json` allocating the output buffer, to release with
+/// `h_free_buffer`.
+///
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_policy_handle_to_json_alloc(
+    json_ptr: *mut *mut c_char,
+    json_len: *mut c_int,
+    handle: PolicyHandle,
+) -> c_int {
+    catch_panic(|| {
+        call_allocating(json_ptr, json_len, |ptr, len| {
+            h_policy_handle_to_json(ptr, len, handle)
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{tests::policy, Policy};
+
+    #[test]
+    fn test_library_allocated_buffers() {
+        let policy = policy().unwrap();
+        let policy_bytes = serde_json::to_vec(&policy).unwrap();
+
+        let mut json_ptr = ptr::null_mut();
+        let mut json_len = 0;
+        let res = unsafe {
+            h_policy_to_json_alloc(
+                &mut json_ptr,
+                &mut json_len,
+                policy_bytes.as_ptr().cast(),
+                policy_bytes.len() as c_int,
+            )
+        };
+        assert_eq!(0, res);
+        let json = unsafe { std::slice::from_raw_parts(json_ptr.cast(), json_len as usize) };
+        assert_eq!(policy, Policy::parse_and_convert(json).unwrap());
+        unsafe { h_free_buffer(json_ptr, json_len) };
+
+        // Outside of an `_alloc` call, a null output pointer queries the size.
+        let mut len = 0;
+        let res = unsafe {
+            h_policy_to_json(
+                ptr::null_mut(),
+                &mut len,
+                policy_bytes.as_ptr().cast(),
+                policy_bytes.len() as c_int,
+            )
+        };
+        assert_eq!(crate::interfaces::ffi::error::FFI_BUFFER_TOO_SMALL, res);
+        assert_eq!(json_len, len);
+
+        // A valid expression has no error to write.
+        let expression = std::ffi::CString::new("Department::HR").unwrap();
+        let mut error_ptr = ptr::null_mut();
+        let mut error_len = 0;
+        let res = unsafe {
+            h_validate_boolean_expression_alloc(
+                &mut error_ptr,
+                &mut error_len,
+                expression.as_ptr(),
+                ptr::null(),
+                0,
+            )
+        };
+        assert_eq!(0, res);
+        assert!(error_ptr.is_null());
+        assert_eq!(0, error_len);
+    }
+}
@@ -1,10 +1,28 @@
 use std::{
+    any::Any,
     cell::RefCell,
     ffi::{CStr, CString},
     os::raw::{c_char, c_int},
+    panic::AssertUnwindSafe,
 };
 use thiserror::Error;
 
+/// Status code returned by the FFI functions on success.
+pub const FFI_OK: c_int = 0;
+
+/// Status code returned by the FFI functions on failure. The error message
+/// can be retrieved with `get_last_error`.
+pub const FFI_ERROR: c_int = 1;
+
+/// Status code returned by the FFI functions when the output pointer is null
+/// or the output buffer is too small. The required size is written to the
+/// length out-parameter.
+pub const FFI_BUFFER_TOO_SMALL: c_int = 2;
+
+/// Status code returned by the FFI functions when a panic was caught. The
+/// panic message can be retrieved with `get_last_error`.
+pub const FFI_PANIC: c_int = 3;
+
 #[derive(Error, Debug)]
 pub enum FfiError {
     #[error("Invalid NULL pointer passed for: {0}")]
@@ -12,6 +30,12 @@ pub enum FfiError {
 
     #[error("FFI error: {0}")]
     Generic(String),
+
+    #[error("panic: {0}")]
+    Panic(String),
+
+    #[error("{0}")]
+    BufferTooSmall(String),
 }
 
 impl From<std::ffi::NulError> for FfiError {
@@ -20,18 +44,57 @@ impl From<std::ffi::NulError> for FfiError {
     }
 }
 
+impl FfiError {
+    /// Status code reported for this error by `h_get_last_error_code`.
+    #[must_use]
+    pub const fn code(&self) -> c_int {
+        match self {
+            Self::NullPointer(_) | Self::Generic(_) => FFI_ERROR,
+            Self::Panic(_) => FFI_PANIC,
+            Self::BufferTooSmall(_) => FFI_BUFFER_TOO_SMALL,
+        }
+    }
+}
+
 thread_local! {
-    /// a thread-local variable which holds the most recent error
-    static LAST_ERROR: RefCell<Option<Box<FfiError>>> = RefCell::new(None);
+    /// a thread-local variable which holds the most recent error and its
+    /// status code
+    static LAST_ERROR: RefCell<Option<(c_int, Box<FfiError>)>> = const { RefCell::new(None) };
 }
 
 /// Set the most recent error, clearing whatever may have been there before.
 pub fn set_last_error(err: FfiError) {
+    let code = err.code();
+    set_last_error_with_code(err, code);
+}
+
+/// Set the most recent error along with the status code returned to the
+/// caller, clearing whatever may have been there before.
+pub fn set_last_error_with_code(err: FfiError, code: c_int) {
     LAST_ERROR.with(|prev| {
-        *prev.borrow_mut() = Some(Box::new(err));
+        *prev.borrow_mut() = Some((code, Box::new(err)));
     });
 }
 
+/// Calls `f`, converting a panic into an `FfiError::Panic` and the
+/// `FFI_PANIC` status code: unwinding across the FFI boundary is undefined
+/// behavior.
+pub fn catch_panic(f: impl FnOnce() -> c_int) -> c_int {
+    std::panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|payload| {
+        set_last_error(FfiError::Panic(panic_message(&payload)));
+        FFI_PANIC
+    })
+}
+
+/// Returns the message of a panic caught by `catch_unwind`.
+pub(crate) fn panic_message(payload: &Box<dyn Any + Send>) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(ToString::to_string)
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_owned())
+}
+
 /// Externally set the last error recorded on the Rust side
 ///
 /// # Safety
@@ -39,21 +102,29 @@ pub fn set_last_error(err: FfiError) {
 /// Interface
 #[no_mangle]
 pub unsafe extern "C" fn set_error(error_message_ptr: *const c_char) -> i32 {
-    ffi_not_null!(error_message_ptr, "error message");
-    let error_message = match CStr::from_ptr(error_message_ptr).to_str() {
-        Ok(msg) => msg.to_owned(),
-        Err(e) => {
-            set_last_error(FfiError::Generic(format!(
-                "failed to set error message: {e}"
-            )));
-            return 1;
-        }
-    };
-    set_last_error(FfiError::Generic(error_message));
-    0
+    catch_panic(|| {
+        ffi_not_null!(error_message_ptr, "error message");
+        let error_message = match CStr::from_ptr(error_message_ptr).to_str() {
+            Ok(msg) => msg.to_owned(),
+            Err(e) => {
+                set_last_error(FfiError::Generic(format!(
+                    "failed to set error message: {e}"
+                )));
+                return 1;
+            }
+        };
+        set_last_error(FfiError::Generic(error_message));
+        0
+    })
 }
 
 /// Get the most recent error as utf-8 bytes, clearing it in the process.
+///
+/// If `error_msg_ptr` is null, the size of the buffer needed to hold the
+/// error message, including the final NULL byte, is written to `error_len`
+/// and `FFI_BUFFER_TOO_SMALL` is returned. The error is not cleared.
+///
+/// Otherwise, the message is truncated to fit in the buffer.
 /// # Safety
 /// - `error_msg`: must be pre-allocated with a sufficient size
 #[no_mangle]
@@ -61,43 +132,59 @@ pub unsafe extern "C" fn get_last_error(
     error_msg_ptr: *mut c_char,
     error_len: *mut c_int,
 ) -> c_int {
-    if error_msg_ptr.is_null() {
-        eprintln!("get_last_error: must pass a pre-allocated buffer");
-        return 1;
-    }
-    if error_len.is_null() {
-        eprintln!("get_last_error: must pass a pre-allocated len with the max buffer length");
-        return 1;
-    }
-    if *error_len < 1 {
-        eprintln!("get_last_error: the buffer must be at least one byte long");
-        return 1;
-    }
-    let err = LAST_ERROR.with(|prev| prev.borrow_mut().take());
-
-    // Build a CString that will cleanup NULL bytes in the middle if needed
-    let cs = ffi_unwrap!(
-        CString::new(err.map_or(String::new(), |e| e.to_string())),
-        "failed to convert error to CString"
-    );
-    // the CString as bytes
-    let bytes = cs.as_bytes();
-
-    // leave a space for a null byte at the end if the string exceeds the buffer
-    // The actual bytes size, not taking into account the final NULL
-    let actual_len = std::cmp::min((*error_len - 1) as usize, bytes.len());
-
-    // create a 0 initialized vector with the message
-    let mut result = vec![0; *error_len as usize];
-    {
-        let (left, _right) = result.split_at_mut(actual_len);
-        left.copy_from_slice(&bytes[0..actual_len]);
-    }
+    catch_panic(|| {
+        if error_len.is_null() {
+            eprintln!("get_last_error: must pass a pre-allocated len with the max buffer length");
+            return FFI_ERROR;
+        }
+        if error_msg_ptr.is_null() {
+            let len = LAST_ERROR.with(|prev| {
+                prev.borrow()
+                    .as_ref()
+                    .map_or(0, |(_, e)| e.to_string().len())
+            });
+            *error_len = (len + 1) as c_int;
+            return FFI_BUFFER_TOO_SMALL;
+        }
+        if *error_len < 1 {
+            eprintln!("get_last_error: the buffer must be at least one byte long");
+            return FFI_ERROR;
+        }
+        let err = LAST_ERROR.with(|prev| prev.borrow_mut().take());
 
-    //copy the result in the OUT array
-    std::slice::from_raw_parts_mut(error_msg_ptr.cast(), *error_len as usize)
-        .copy_from_slice(&result);
+        // Build a CString that will cleanup NULL bytes in the middle if needed
+        let cs = ffi_unwrap!(
+            CString::new(err.map_or(String::new(), |(_, e)| e.to_string())),
+            "failed to convert error to CString"
+        );
+        // the CString as bytes
+        let bytes = cs.as_bytes();
 
-    *error_len = actual_len as i32;
-    0
+        // leave a space for a null byte at the end if the string exceeds the buffer
+        // The actual bytes size, not taking into account the final NULL
+        let actual_len = std::cmp::min((*error_len - 1) as usize, bytes.len());
+
+        // create a 0 initialized vector with the message
+        let mut result = vec![0; *error_len as usize];
+        {
+            let (left, _right) = result.split_at_mut(actual_len);
+            left.copy_from_slice(&bytes[0..actual_len]);
+        }
+
+        //copy the result in the OUT array
+        std::slice::from_raw_parts_mut(error_msg_ptr.cast(), *error_len as usize)
+            .copy_from_slice(&result);
+
+        *error_len = actual_len as i32;
+        0
+    })
+}
+
+/// Get the status code of the most recent error, `FFI_OK` if there is none.
+///
+/// The error is not cleared, its message can still be retrieved with
+/// `get_last_error`.
+#[no_mangle]
+pub extern "C" fn h_get_last_error_code() -> c_int {
+    catch_panic(|| LAST_ERROR.with(|prev| prev.borrow().as_ref().map_or(FFI_OK, |(code, _)| *code)))
 }
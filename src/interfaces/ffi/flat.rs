@@ -0,0 +1,416 @@
+//! Flat API, designed for callers such as Go (cgo) whose threads may change
+//! between two calls and which therefore cannot rely on thread-local state.
+//!
+//! The `h_flat_*` functions:
+//! - take every string as a pointer and an explicit length, without NULL
+//!   terminator;
+//! - write their outputs to caller-allocated buffers, following the usual
+//!   length convention: on input the size of the buffer, on output the
+//!   number of bytes written or, when `FFI_BUFFER_TOO_SMALL` is returned, the
+//!   required size;
+//! - return one of the `FFI_*` status codes and write the error message, if
+//!   any, to the `error_ptr`/`error_len` buffer given to each call, truncated
+//!   to its size. The last error read by `get_last_error` is left untouched.
+
+use std::{
+    ffi::{c_char, c_int, c_uint},
+    panic::AssertUnwindSafe,
+};
+
+use crate::{
+    interfaces::ffi::{
+        error::{panic_message, FfiError, FFI_OK},
+        log::{log_lints, read_policy, read_shared_policy},
+        macros::FFI_MAX_INPUT_LEN,
+    },
+    AccessPolicy, Attribute, Policy,
+};
+
+/// Calls `f`, writing its error message, if any, to the error buffer and
+/// returning the matching status code.
+unsafe fn flat_call(
+    error_ptr: *mut c_char,
+    error_len: *mut c_int,
+    f: impl FnOnce() -> Result<(), FfiError>,
+) -> c_int {
+    let res = std::panic::catch_unwind(AssertUnwindSafe(f))
+        .unwrap_or_else(|payload| Err(FfiError::Panic(panic_message(&payload))));
+    let (code, message) = match res {
+        Ok(()) => (FFI_OK, String::new()),
+        Err(e) => (e.code(), e.to_string()),
+    };
+    if !error_len.is_null() {
+        let mut len = if error_ptr.is_null() {
+            0
+        } else {
+            message.len().min((*error_len).max(0) as usize)
+        };
+        while !message.is_char_boundary(len) {
+            len -= 1;
+        }
+        if len > 0 {
+            std::ptr::copy_nonoverlapping(message.as_ptr(), error_ptr.cast(), len);
+        }
+        *error_len = len as c_int;
+    }
+    code
+}
+
+unsafe fn read_bytes<'a>(name: &str, ptr: *const c_char, len: c_int) -> Result<&'a [u8], FfiError> {
+    if ptr.is_null() {
+        return Err(FfiError::NullPointer(name.to_owned()));
+    }
+    if len <= 0 || len as usize > FFI_MAX_INPUT_LEN {
+        return Err(FfiError::Generic(format!(
+            "{name} should have a size between 1 and {FFI_MAX_INPUT_LEN} bytes, got {len}"
+        )));
+    }
+    Ok(std::slice::from_raw_parts(ptr.cast(), len as usize))
+}
+
+unsafe fn read_str<'a>(name: &str, ptr: *const c_char, len: c_int) -> Result<&'a str, FfiError> {
+    std::str::from_utf8(read_bytes(name, ptr, len)?).map_err(|e| {
+        FfiError::Generic(format!(
+            "{name} is not a valid UTF-8 string: invalid byte at offset {}",
+            e.valid_up_to()
+        ))
+    })
+}
+
+unsafe fn write_bytes(
+    name: &str,
+    bytes: &[u8],
+    ptr: *mut c_char,
+    len: *mut c_int,
+) -> Result<(), FfiError> {
+    if len.is_null() {
+        return Err(FfiError::NullPointer(format!("{name} length")));
+    }
+    let required = c_int::try_from(bytes.len())
+        .map_err(|_| FfiError::Generic(format!("{name} does not fit in a C buffer")))?;
+    let allocated = *len;
+    *len = required;
+    if ptr.is_null() || allocated < required {
+        return Err(FfiError::BufferTooSmall(format!(
+            "The pre-allocated {name} buffer is too small; need {required} bytes, allocated {allocated}"
+        )));
+    }
+    std::ptr::copy_nonoverlapping(bytes.as_ptr(), ptr.cast(), bytes.len());
+    Ok(())
+}
+
+fn to_json(value: &impl serde::Serialize) -> Result<Vec<u8>, FfiError> {
+    serde_json::to_vec(value).map_err(|e| FfiError::Generic(e.to_string()))
+}
+
+fn generic(e: crate::Error) -> FfiError {
+    FfiError::Generic(e.to_string())
+}
+
+/// Creates an empty policy allowing the given number of attribute creations
+/// (additions + rotations).
+///
+/// - `policy_ptr`              : output policy buffer (JSON)
+/// - `policy_len`              : size of the output buffer
+/// - `max_attribute_creations` : maximum number of attribute creations
+/// - `error_ptr`               : output error message buffer
+/// - `error_len`               : size of the error buffer
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_flat_policy_new(
+    policy_ptr: *mut c_char,
+    policy_len: *mut c_int,
+    max_attribute_creations: c_uint,
+    error_ptr: *mut c_char,
+    error_len: *mut c_int,
+) -> c_int {
+    flat_call(error_ptr, error_len, || {
+        let policy = to_json(&Policy::new(max_attribute_creations))?;
+        write_bytes("policy", &policy, policy_ptr, policy_len)
+    })
+}
+
+/// Adds an axis to a policy.
+///
+/// - `updated_policy_ptr`  : output policy buffer (JSON)
+/// - `updated_policy_len`  : size of the output buffer
+/// - `policy_ptr`          : policy to update (JSON)
+/// - `policy_len`          : size of the policy to update
+/// - `axis_ptr`            : axis to add (JSON)
+/// - `axis_len`            : size of the axis
+/// - `error_ptr`           : output error message buffer
+/// - `error_len`           : size of the error buffer
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_flat_policy_add_axis(
+    updated_policy_ptr: *mut c_char,
+    updated_policy_len: *mut c_int,
+    policy_ptr: *const c_char,
+    policy_len: c_int,
+    axis_ptr: *const c_char,
+    axis_len: c_int,
+    error_ptr: *mut c_char,
+    error_len: *mut c_int,
+) -> c_int {
+    flat_call(error_ptr, error_len, || {
+        let mut policy =
+            read_policy(read_bytes("policy", policy_ptr, policy_len)?).map_err(generic)?;
+        let axis = serde_json::from_slice(read_bytes("axis", axis_ptr, axis_len)?)
+            .map_err(|e| FfiError::Generic(e.to_string()))?;
+        policy.add_axis(axis).map_err(generic)?;
+        log_lints(&policy);
+        write_bytes(
+            "updated policy",
+            &to_json(&policy)?,
+            updated_policy_ptr,
+            updated_policy_len,
+        )
+    })
+}
+
+/// Rotates an attribute of a policy, changing its underlying value with an
+/// unused value.
+///
+/// - `updated_policy_ptr`  : output policy buffer (JSON)
+/// - `updated_policy_len`  : size of the output buffer
+/// - `policy_ptr`          : policy to update (JSON)
+/// - `policy_len`          : size of the policy to update
+/// - `attribute_ptr`       : attribute to rotate, written `Axis::Name`
+/// - `attribute_len`       : size of the attribute
+/// - `error_ptr`           : output error message buffer
+/// - `error_len`           : size of the error buffer
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_flat_policy_rotate_attribute(
+    updated_policy_ptr: *mut c_char,
+    updated_policy_len: *mut c_int,
+    policy_ptr: *const c_char,
+    policy_len: c_int,
+    attribute_ptr: *const c_char,
+    attribute_len: c_int,
+    error_ptr: *mut c_char,
+    error_len: *mut c_int,
+) -> c_int {
+    flat_call(error_ptr, error_len, || {
+        let mut policy =
+            read_policy(read_bytes("policy", policy_ptr, policy_len)?).map_err(generic)?;
+        let attribute = read_str("attribute", attribute_ptr, attribute_len)?
+            .parse::<Attribute>()
+            .map_err(generic)?;
+        policy.rotate(&attribute).map_err(generic)?;
+        log_lints(&policy);
+        write_bytes(
+            "updated policy",
+            &to_json(&policy)?,
+            updated_policy_ptr,
+            updated_policy_len,
+        )
+    })
+}
+
+/// Gets the current value of an attribute.
+///
+/// - `value`           : output attribute value
+/// - `policy_ptr`      : policy (JSON)
+/// - `policy_len`      : size of the policy
+/// - `attribute_ptr`   : attribute, written `Axis::Name`
+/// - `attribute_len`   : size of the attribute
+/// - `error_ptr`       : output error message buffer
+/// - `error_len`       : size of the error buffer
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_flat_policy_attribute_current_value(
+    value: *mut c_uint,
+    policy_ptr: *const c_char,
+    policy_len: c_int,
+    attribute_ptr: *const c_char,
+    attribute_len: c_int,
+    error_ptr: *mut c_char,
+    error_len: *mut c_int,
+) -> c_int {
+    flat_call(error_ptr, error_len, || {
+        if value.is_null() {
+            return Err(FfiError::NullPointer("value".to_owned()));
+        }
+        let policy =
+            read_shared_policy(read_bytes("policy", policy_ptr, policy_len)?).map_err(generic)?;
+        let attribute = read_str("attribute", attribute_ptr, attribute_len)?
+            .parse::<Attribute>()
+            .map_err(generic)?;
+        *value = policy
+            .attribute_current_value(&attribute)
+            .map_err(generic)?;
+        Ok(())
+    })
+}
+
+/// Converts a boolean expression into a JSON access policy.
+///
+/// - `access_policy_ptr`       : output access policy buffer (JSON)
+/// - `access_policy_len`       : size of the output buffer
+/// - `boolean_expression_ptr`  : boolean expression
+/// - `boolean_expression_len`  : size of the boolean expression
+/// - `error_ptr`               : output error message buffer
+/// - `error_len`               : size of the error buffer
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_flat_parse_boolean_access_policy(
+    access_policy_ptr: *mut c_char,
+    access_policy_len: *mut c_int,
+    boolean_expression_ptr: *const c_char,
+    boolean_expression_len: c_int,
+    error_ptr: *mut c_char,
+    error_len: *mut c_int,
+) -> c_int {
+    flat_call(error_ptr, error_len, || {
+        let boolean_expression = read_str(
+            "boolean expression",
+            boolean_expression_ptr,
+            boolean_expression_len,
+        )?;
+        let access_policy =
+            AccessPolicy::from_boolean_expression(boolean_expression).map_err(generic)?;
+        write_bytes(
+            "access policy",
+            &to_json(&access_policy)?,
+            access_policy_ptr,
+            access_policy_len,
+        )
+    })
+}
+
+/// Computes the attribute combinations granting access under a boolean
+/// expression, as a JSON array of arrays of attributes.
+///
+/// - `combinations_ptr`        : output combinations buffer (JSON)
+/// - `combinations_len`        : size of the output buffer
+/// - `policy_ptr`              : policy (JSON)
+/// - `policy_len`              : size of the policy
+/// - `boolean_expression_ptr`  : boolean expression
+/// - `boolean_expression_len`  : size of the boolean expression
+/// - `follow_hierarchical_axes`: if non-zero, the attributes below those of
+///   the expression on hierarchical axes also grant access
+/// - `error_ptr`               : output error message buffer
+/// - `error_len`               : size of the error buffer
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_flat_access_policy_to_combinations(
+    combinations_ptr: *mut c_char,
+    combinations_len: *mut c_int,
+    policy_ptr: *const c_char,
+    policy_len: c_int,
+    boolean_expression_ptr: *const c_char,
+    boolean_expression_len: c_int,
+    follow_hierarchical_axes: c_int,
+    error_ptr: *mut c_char,
+    error_len: *mut c_int,
+) -> c_int {
+    flat_call(error_ptr, error_len, || {
+        let policy =
+            read_shared_policy(read_bytes("policy", policy_ptr, policy_len)?).map_err(generic)?;
+        let boolean_expression = read_str(
+            "boolean expression",
+            boolean_expression_ptr,
+            boolean_expression_len,
+        )?;
+        let combinations = policy
+            .parse_access_policy(boolean_expression)
+            .and_then(|access_policy| {
+                access_policy.to_attribute_combinations(&policy, follow_hierarchical_axes != 0)
+            })
+            .map_err(generic)?;
+        write_bytes(
+            "combinations",
+            &to_json(&combinations)?,
+            combinations_ptr,
+            combinations_len,
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        interfaces::ffi::error::{h_get_last_error_code, FFI_BUFFER_TOO_SMALL, FFI_ERROR},
+        tests::policy,
+    };
+
+    #[test]
+    fn test_flat_api() {
+        let policy = serde_json::to_vec(&policy().unwrap()).unwrap();
+        let mut error = vec![0_u8; 256];
+
+        let attribute = "Department::FIN";
+        let mut updated = vec![0_u8; 8192];
+        let mut updated_len = updated.len() as c_int;
+        let mut error_len = error.len() as c_int;
+        let res = unsafe {
+            h_flat_policy_rotate_attribute(
+                updated.as_mut_ptr().cast(),
+                &mut updated_len,
+                policy.as_ptr().cast(),
+                policy.len() as c_int,
+                attribute.as_ptr().cast(),
+                attribute.len() as c_int,
+                error.as_mut_ptr().cast(),
+                &mut error_len,
+            )
+        };
+        assert_eq!(FFI_OK, res);
+        assert_eq!(0, error_len);
+
+        let mut value = 0;
+        let res = unsafe {
+            h_flat_policy_attribute_current_value(
+                &mut value,
+                updated.as_ptr().cast(),
+                updated_len,
+                attribute.as_ptr().cast(),
+                attribute.len() as c_int,
+                error.as_mut_ptr().cast(),
+                &mut error_len,
+            )
+        };
+        assert_eq!(FFI_OK, res);
+        assert_eq!(8, value);
+
+        // Errors are written to the given buffer, truncated to its size, and
+        // not recorded as the last error.
+        let attribute = "Department::Sales";
+        let mut error_len = 20;
+        let res = unsafe {
+            h_flat_policy_attribute_current_value(
+                &mut value,
+                policy.as_ptr().cast(),
+                policy.len() as c_int,
+                attribute.as_ptr().cast(),
+                attribute.len() as c_int,
+                error.as_mut_ptr().cast(),
+                &mut error_len,
+            )
+        };
+        assert_eq!(FFI_ERROR, res);
+        assert_eq!(b"FFI error: attribute", &error[..error_len as usize]);
+        assert_eq!(FFI_OK, h_get_last_error_code());
+
+        let expression = "Department::HR";
+        let mut access_policy_len = 4;
+        let mut error_len = error.len() as c_int;
+        let res = unsafe {
+            h_flat_parse_boolean_access_policy(
+                updated.as_mut_ptr().cast(),
+                &mut access_policy_len,
+                expression.as_ptr().cast(),
+                expression.len() as c_int,
+                error.as_mut_ptr().cast(),
+                &mut error_len,
+            )
+        };
+        assert_eq!(FFI_BUFFER_TOO_SMALL, res);
+        assert_eq!(
+            r#"{"Attr":"Department::HR"}"#.len() as c_int,
+            access_policy_len
+        );
+    }
+}
@@ -0,0 +1,217 @@
+//! Policies kept alive on the Rust side and addressed by opaque handles, so
+//! that callers performing several operations on a policy do not pay for
+//! its JSON serialization on every call.
+
+use std::{
+    collections::BTreeMap,
+    ffi::{c_char, c_int, c_uint},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex, PoisonError,
+    },
+};
+
+use crate::{
+    interfaces::ffi::{
+        error::{catch_panic, FfiError},
+        log::{log_lints, read_policy},
+    },
+    Attribute, Policy,
+};
+
+/// Opaque handle to a policy opened with `h_policy_open`. 0 is never a valid
+/// handle.
+pub type PolicyHandle = u64;
+
+static NEXT_HANDLE: AtomicU64 = AtomicU64::new(1);
+
+static POLICIES: Mutex<BTreeMap<PolicyHandle, Policy>> = Mutex::new(BTreeMap::new());
+
+/// Calls `f` on the policy registered under the given handle.
+fn with_policy<T>(handle: PolicyHandle, f: impl FnOnce(&mut Policy) -> T) -> Result<T, FfiError> {
+    let mut policies = POLICIES.lock().unwrap_or_else(PoisonError::into_inner);
+    policies
+        .get_mut(&handle)
+        .map(f)
+        .ok_or_else(|| FfiError::Generic(format!("unknown policy handle {handle}")))
+}
+
+/// Opens a policy, returning a handle to use with the `h_policy_handle_*`
+/// functions. The policy must be closed with `h_policy_close`.
+///
+/// - `handle`      : output handle
+/// - `policy_ptr`  : policy (JSON, current or legacy format)
+/// - `policy_len`  : size of the policy
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_policy_open(
+    handle: *mut PolicyHandle,
+    policy_ptr: *const c_char,
+    policy_len: c_int,
+) -> c_int {
+    catch_panic(|| {
+        ffi_not_null!(handle, "policy handle pointer should not be null");
+        let policy_bytes = ffi_read_bytes!("policy", policy_ptr, policy_len);
+        let policy = ffi_unwrap!(read_policy(policy_bytes));
+
+        let new_handle = NEXT_HANDLE.fetch_add(1, Ordering::Relaxed);
+        POLICIES
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(new_handle, policy);
+        *handle = new_handle;
+        0
+    })
+}
+
+/// Closes a policy opened with `h_policy_open`, invalidating its handle.
+#[no_mangle]
+pub extern "C" fn h_policy_close(handle: PolicyHandle) -> c_int {
+    catch_panic(|| {
+        let policy = POLICIES
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&handle);
+        ffi_ensure!(policy.is_some(), "unknown policy handle {}", handle);
+        0
+    })
+}
+
+/// Adds an axis to an opened policy.
+///
+/// - `handle`      : policy handle
+/// - `axis_ptr`    : axis to add (JSON)
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_policy_handle_add_axis(
+    handle: PolicyHandle,
+    axis_ptr: *const c_char,
+) -> c_int {
+    catch_panic(|| {
+        let axis_string = ffi_read_string!("axis", axis_ptr);
+        let axis = ffi_unwrap!(serde_json::from_str(&axis_string));
+        let res = ffi_unwrap!(with_policy(handle, |policy| {
+            let res = policy.add_axis(axis);
+            log_lints(policy);
+            res
+        }));
+        ffi_unwrap!(res);
+        0
+    })
+}
+
+/// Rotates an attribute of an opened policy.
+///
+/// - `handle`          : policy handle
+/// - `attribute_ptr`   : attribute to rotate, written `Axis::Name`
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_policy_handle_rotate_attribute(
+    handle: PolicyHandle,
+    attribute_ptr: *const c_char,
+) -> c_int {
+    catch_panic(|| {
+        let attr_string = ffi_read_string!("attribute", attribute_ptr);
+        let attr = ffi_unwrap!(attr_string.parse::<Attribute>());
+        let res = ffi_unwrap!(with_policy(handle, |policy| {
+            let res = policy.rotate(&attr);
+            log_lints(policy);
+            res
+        }));
+        ffi_unwrap!(res);
+        0
+    })
+}
+
+/// Retrieves the current value of an attribute of an opened policy.
+///
+/// - `value`           : output value
+/// - `handle`          : policy handle
+/// - `attribute_ptr`   : attribute, written `Axis::Name`
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_policy_handle_attribute_current_value(
+    value: *mut c_uint,
+    handle: PolicyHandle,
+    attribute_ptr: *const c_char,
+) -> c_int {
+    catch_panic(|| {
+        ffi_not_null!(value, "value pointer should not be null");
+        let attr_string = ffi_read_string!("attribute", attribute_ptr);
+        let attr = ffi_unwrap!(attr_string.parse::<Attribute>());
+        let res = ffi_unwrap!(with_policy(handle, |policy| {
+            policy.attribute_current_value(&attr)
+        }));
+        *value = ffi_unwrap!(res);
+        0
+    })
+}
+
+/// Writes an opened policy as JSON.
+///
+/// - `json_ptr`    : output JSON buffer
+/// - `json_len`    : size of the output buffer
+/// - `handle`      : policy handle
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_policy_handle_to_json(
+    json_ptr: *mut c_char,
+    json_len: *mut c_int,
+    handle: PolicyHandle,
+) -> c_int {
+    catch_panic(|| {
+        let res = ffi_unwrap!(with_policy(handle, |policy| serde_json::to_vec(policy)));
+        let policy_bytes = ffi_unwrap!(res);
+        ffi_write_bytes!("policy JSON", &policy_bytes, json_ptr, json_len);
+        0
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::policy;
+
+    #[test]
+    fn test_policy_handle() {
+        let mut policy = policy().unwrap();
+        let policy_bytes = serde_json::to_vec(&policy).unwrap();
+        let attribute = Attribute::new("Department", "HR");
+        let attribute_string = std::ffi::CString::new(attribute.to_string()).unwrap();
+
+        let mut handle = 0;
+        assert_eq!(0, unsafe {
+            h_policy_open(
+                &mut handle,
+                policy_bytes.as_ptr().cast(),
+                policy_bytes.len() as c_int,
+            )
+        });
+        assert_eq!(0, unsafe {
+            h_policy_handle_rotate_attribute(handle, attribute_string.as_ptr())
+        });
+        policy.rotate(&attribute).unwrap();
+
+        let mut value = 0;
+        assert_eq!(0, unsafe {
+            h_policy_handle_attribute_current_value(&mut value, handle, attribute_string.as_ptr())
+        });
+        assert_eq!(policy.attribute_current_value(&attribute).unwrap(), value);
+
+        let mut json = vec![0_u8; 8192];
+        let mut json_len = json.len() as c_int;
+        assert_eq!(0, unsafe {
+            h_policy_handle_to_json(json.as_mut_ptr().cast(), &mut json_len, handle)
+        });
+        assert_eq!(
+            policy,
+            Policy::parse_and_convert(&json[..json_len as usize]).unwrap()
+        );
+
+        assert_eq!(0, h_policy_close(handle));
+        assert_eq!(1, h_policy_close(handle));
+        assert_eq!(1, unsafe {
+            h_policy_handle_rotate_attribute(handle, attribute_string.as_ptr())
+        });
+    }
+}
@@ -0,0 +1,109 @@
+//! Diagnostics reported to the host through a logging callback.
+
+use std::{
+    ffi::{c_char, c_int, CString},
+    sync::{Arc, PoisonError, RwLock},
+};
+
+use crate::{interfaces::cache::parse_policy, Error, Policy};
+
+/// Log level of errors.
+pub const FFI_LOG_ERROR: c_int = 1;
+
+/// Log level of warnings: legacy policy conversions and policy lint findings,
+/// such as a nearly exhausted attribute creation capacity.
+pub const FFI_LOG_WARN: c_int = 2;
+
+/// Log level of informational messages.
+pub const FFI_LOG_INFO: c_int = 3;
+
+/// Function called with the level and the NULL-terminated UTF-8 message of
+/// each diagnostic. The message is only valid during the call.
+pub type LogCallback = Option<unsafe extern "C" fn(level: c_int, message: *const c_char)>;
+
+static LOG_CALLBACK: RwLock<LogCallback> = RwLock::new(None);
+
+/// Sets the function receiving the diagnostics of the library, replacing the
+/// previous one. Diagnostics are discarded if the callback is null, which is
+/// the default.
+///
+/// The callback may be called from any thread calling the library.
+#[no_mangle]
+pub extern "C" fn h_set_log_callback(callback: LogCallback) {
+    *LOG_CALLBACK.write().unwrap_or_else(PoisonError::into_inner) = callback;
+}
+
+fn callback() -> LogCallback {
+    *LOG_CALLBACK.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Reports a diagnostic to the log callback, if any.
+pub fn log(level: c_int, message: &str) {
+    if let Some(callback) = callback() {
+        // Build a CString that will cleanup NULL bytes in the middle if needed
+        let message = CString::new(message.replace('\0', "")).unwrap_or_default();
+        unsafe { callback(level, message.as_ptr()) };
+    }
+}
+
+/// Parses a policy as `Policy::parse_and_convert`, warning if it uses the
+/// legacy format. The policy is shared with the previous calls given the same
+/// bytes.
+pub fn read_shared_policy(bytes: &[u8]) -> Result<Arc<Policy>, Error> {
+    let (policy, is_legacy) = parse_policy(bytes)?;
+    if is_legacy {
+        log(
+            FFI_LOG_WARN,
+            "policy converted from the legacy format, write it back to upgrade it",
+        );
+    }
+    Ok(policy)
+}
+
+/// Parses a policy as `read_shared_policy`, returning a copy to modify.
+pub fn read_policy(bytes: &[u8]) -> Result<Policy, Error> {
+    read_shared_policy(bytes).map(Arc::unwrap_or_clone)
+}
+
+/// Warns about the lint findings of a policy, which includes a nearly
+/// exhausted attribute creation capacity.
+pub fn log_lints(policy: &Policy) {
+    if callback().is_some() {
+        for lint in policy.lint() {
+            log(FFI_LOG_WARN, &lint.to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{ffi::CStr, sync::Mutex};
+
+    use super::*;
+
+    static MESSAGES: Mutex<Vec<(c_int, String)>> = Mutex::new(Vec::new());
+
+    unsafe extern "C" fn collect(level: c_int, message: *const c_char) {
+        let message = CStr::from_ptr(message).to_string_lossy().into_owned();
+        MESSAGES.lock().unwrap().push((level, message));
+    }
+
+    #[test]
+    fn test_log_callback() {
+        let legacy_policy = include_bytes!("../../../tests/legacy_policy.json");
+
+        h_set_log_callback(Some(collect));
+        read_policy(legacy_policy).unwrap();
+        h_set_log_callback(None);
+        read_policy(legacy_policy).unwrap();
+
+        let messages = MESSAGES.lock().unwrap();
+        assert_eq!(
+            1,
+            messages
+                .iter()
+                .filter(|(level, message)| *level == FFI_LOG_WARN && message.contains("legacy"))
+                .count()
+        );
+    }
+}
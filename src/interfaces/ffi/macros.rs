@@ -1,5 +1,20 @@
 pub use std::ffi::CStr;
 
+/// Maximum size of an input of the FFI functions, in bytes for buffers and
+/// UTF-8 strings and in code units for UTF-16 strings.
+pub const FFI_MAX_INPUT_LEN: usize = 1 << 28;
+
+/// Returns the length of the NULL-terminated string starting at `ptr`, or
+/// `None` if no NULL terminator is found within `FFI_MAX_INPUT_LEN` code
+/// units.
+///
+/// # Safety
+/// `ptr` must point to a NULL-terminated string or to at least
+/// `FFI_MAX_INPUT_LEN` readable code units.
+pub unsafe fn bounded_len<T: Copy + Default + PartialEq>(ptr: *const T) -> Option<usize> {
+    (0..FFI_MAX_INPUT_LEN).find(|&i| *ptr.add(i) == T::default())
+}
+
 /// Return early with an error if a pointer is null
 ///
 /// This macro is equivalent to
@@ -98,33 +113,82 @@ macro_rules! ffi_bail {
         return 1;
     };
     ($msg: expr, $error_code: expr) => {
-        $crate::interfaces::ffi::error::set_last_error(
+        $crate::interfaces::ffi::error::set_last_error_with_code(
             $crate::interfaces::ffi::error::FfiError::Generic($msg.to_string()),
+            $error_code,
         );
         return $error_code;
     };
 }
 
+/// Writes the given bytes to the output buffer and their size to the length
+/// out-parameter.
+///
+/// If the output pointer is null or the buffer is too small, only the
+/// required size is written and `FFI_BUFFER_TOO_SMALL` is returned: callers
+/// can query the size to allocate by passing a null pointer. Within an
+/// `_alloc` call, a null output pointer instead hands the bytes over to the
+/// library-allocated buffer.
 #[macro_export]
 macro_rules! ffi_write_bytes {
     ($name: literal, $bytes: expr, $bytes_ptr: ident, $bytes_len: ident) => {
         $crate::ffi_not_null!(
-            $bytes_ptr,
-            format!("{} pointer should point to pre-allocated memory", $name)
+            $bytes_len,
+            format!("{} length pointer should not be null", $name)
         );
 
-        let allocated = *$bytes_len;
+        let allocated = if $bytes_ptr.is_null() { 0 } else { *$bytes_len };
         *$bytes_len = $bytes.len() as c_int;
-        if allocated < *$bytes_len {
+        // Within an `_alloc` call, the bytes are kept for the library to
+        // allocate the output buffer.
+        if !$bytes_ptr.is_null() || !$crate::interfaces::ffi::buffer::capture($bytes) {
+            if allocated < *$bytes_len {
+                $crate::ffi_bail!(
+                    format!("The pre-allocated {} buffer is too small; need {} bytes, allocated {allocated}", $name, *$bytes_len),
+                    $crate::interfaces::ffi::error::FFI_BUFFER_TOO_SMALL
+                );
+            }
+            std::slice::from_raw_parts_mut($bytes_ptr.cast(), $bytes.len()).copy_from_slice($bytes);
+        }
+    };
+}
+
+/// Writes two outputs as `ffi_write_bytes`, only if both buffers are large
+/// enough: otherwise, the required sizes of both buffers are written and
+/// `FFI_BUFFER_TOO_SMALL` is returned.
+#[macro_export]
+macro_rules! ffi_write_bytes_pair {
+    (
+        ($name1: literal, $bytes1: expr, $bytes_ptr1: ident, $bytes_len1: ident),
+        ($name2: literal, $bytes2: expr, $bytes_ptr2: ident, $bytes_len2: ident) $(,)?
+    ) => {
+        $crate::ffi_not_null!($bytes_len1, format!("{} length pointer should not be null", $name1));
+        $crate::ffi_not_null!($bytes_len2, format!("{} length pointer should not be null", $name2));
+
+        let fits = |ptr: *mut c_char, len: c_int, bytes: &[u8]| {
+            !ptr.is_null() && bytes.len() <= len as usize
+        };
+        if !fits($bytes_ptr1, *$bytes_len1, $bytes1) || !fits($bytes_ptr2, *$bytes_len2, $bytes2) {
+            *$bytes_len1 = $bytes1.len() as c_int;
+            *$bytes_len2 = $bytes2.len() as c_int;
             $crate::ffi_bail!(
-                format!("The pre-allocated {} buffer is too small; need {} bytes, allocated {allocated}", $name, *$bytes_len),
-                $bytes.len() as c_int
+                format!(
+                    "the pre-allocated buffers are too small; need {} bytes for the {} and {} bytes for the {}",
+                    $bytes1.len(),
+                    $name1,
+                    $bytes2.len(),
+                    $name2
+                ),
+                $crate::interfaces::ffi::error::FFI_BUFFER_TOO_SMALL
             );
         }
-        std::slice::from_raw_parts_mut($bytes_ptr.cast(), $bytes.len()).copy_from_slice($bytes);
+        $crate::ffi_write_bytes!($name1, $bytes1, $bytes_ptr1, $bytes_len1);
+        $crate::ffi_write_bytes!($name2, $bytes2, $bytes_ptr2, $bytes_len2);
     };
 }
 
+/// Read a byte buffer, returning early with an error if the pointer is null
+/// or the size is not positive or greater than `FFI_MAX_INPUT_LEN`.
 #[macro_export]
 macro_rules! ffi_read_bytes {
     ($name: literal, $bytes_ptr: ident, $bytes_len: ident) => {{
@@ -133,10 +197,18 @@ macro_rules! ffi_read_bytes {
             format!("{} pointer should point to pre-allocated memory", $name)
         );
 
-        if $bytes_len == 0 {
+        if $bytes_len <= 0 {
             $crate::ffi_bail!(format!(
-                "{} buffer should have a size greater than zero",
-                $name
+                "{} buffer should have a size greater than zero, got {}",
+                $name, $bytes_len
+            ));
+        }
+        if $bytes_len as usize > $crate::interfaces::ffi::macros::FFI_MAX_INPUT_LEN {
+            $crate::ffi_bail!(format!(
+                "{} buffer should not exceed {} bytes, got {}",
+                $name,
+                $crate::interfaces::ffi::macros::FFI_MAX_INPUT_LEN,
+                $bytes_len
             ));
         }
 
@@ -144,15 +216,54 @@ macro_rules! ffi_read_bytes {
     }};
 }
 
+/// Read a NULL-terminated UTF-8 string, returning early with an error if the
+/// pointer is null, the string exceeds `FFI_MAX_INPUT_LEN` bytes or is not
+/// valid UTF-8.
 #[macro_export]
 macro_rules! ffi_read_string {
     ($name: literal, $string_ptr: ident) => {{
         $crate::ffi_not_null!($string_ptr, format!("{} pointer should not be null", $name));
 
-        match $crate::interfaces::ffi::macros::CStr::from_ptr($string_ptr).to_str() {
-            Ok(msg) => msg.to_owned(),
+        let Some(len) = $crate::interfaces::ffi::macros::bounded_len($string_ptr.cast::<u8>())
+        else {
+            $crate::ffi_bail!(format!(
+                "{} should be NULL-terminated within {} bytes",
+                $name,
+                $crate::interfaces::ffi::macros::FFI_MAX_INPUT_LEN
+            ));
+        };
+        match std::str::from_utf8(std::slice::from_raw_parts($string_ptr.cast(), len)) {
+            Ok(string) => string.to_owned(),
+            Err(e) => {
+                $crate::ffi_bail!(format!(
+                    "{} is not a valid UTF-8 string: invalid byte at offset {}",
+                    $name,
+                    e.valid_up_to()
+                ));
+            }
+        }
+    }};
+}
+
+/// Read a NULL-terminated UTF-16 string, returning early with an error if the
+/// pointer is null, the string exceeds `FFI_MAX_INPUT_LEN` code units or is
+/// not valid UTF-16.
+#[macro_export]
+macro_rules! ffi_read_wstring {
+    ($name: literal, $string_ptr: ident) => {{
+        $crate::ffi_not_null!($string_ptr, format!("{} pointer should not be null", $name));
+
+        let Some(len) = $crate::interfaces::ffi::macros::bounded_len($string_ptr) else {
+            $crate::ffi_bail!(format!(
+                "{} should be NULL-terminated within {} code units",
+                $name,
+                $crate::interfaces::ffi::macros::FFI_MAX_INPUT_LEN
+            ));
+        };
+        match String::from_utf16(std::slice::from_raw_parts($string_ptr, len)) {
+            Ok(string) => string,
             Err(e) => {
-                $crate::ffi_bail!(format!("CoverCrypt keys generation: invalid Policy: {}", e));
+                $crate::ffi_bail!(format!("{} is not a valid UTF-16 string: {}", $name, e));
             }
         }
     }};
@@ -2,5 +2,11 @@
 pub mod macros;
 
 pub mod access_policy;
+pub mod buffer;
 pub mod error;
+pub mod flat;
+pub mod handle;
+pub mod log;
 pub mod policy;
+pub mod version;
+pub mod wide;
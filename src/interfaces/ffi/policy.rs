@@ -1,6 +1,339 @@
-use crate::{Attribute, Policy};
-use std::ffi::{c_char, c_int};
+use crate::interfaces::{
+    ffi::{
+        error::catch_panic,
+        log::{log_lints, read_policy, read_shared_policy},
+    },
+    UpgradeReport,
+};
+use crate::{Attribute, Attributes, Policy};
+use std::{
+    collections::BTreeMap,
+    ffi::{c_char, c_int, c_uint},
+};
 
+/// Creates an empty policy allowing the given number of attribute creations
+/// (additions + rotations).
+///
+/// - `policy_ptr`                  : output policy buffer (JSON)
+/// - `policy_len`                  : size of the output buffer
+/// - `max_attribute_creations`     : maximum number of attribute creations
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_policy_new(
+    policy_ptr: *mut c_char,
+    policy_len: *mut c_int,
+    max_attribute_creations: c_int,
+) -> c_int {
+    catch_panic(|| {
+        ffi_ensure!(
+            max_attribute_creations >= 0,
+            "the maximum number of attribute creations should be positive, got {}",
+            max_attribute_creations
+        );
+        let policy = Policy::new(max_attribute_creations as u32);
+        let policy_bytes = ffi_unwrap!(serde_json::to_vec(&policy));
+        ffi_write_bytes!("policy", &policy_bytes, policy_ptr, policy_len);
+        0
+    })
+}
+
+/// Adds an axis to a policy.
+///
+/// - `updated_policy_ptr`  : output policy buffer (JSON)
+/// - `updated_policy_len`  : size of the output buffer
+/// - `current_policy_ptr`  : policy to update (JSON)
+/// - `current_policy_len`  : size of the policy to update
+/// - `axis_ptr`            : axis to add (JSON)
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_policy_add_axis(
+    updated_policy_ptr: *mut c_char,
+    updated_policy_len: *mut c_int,
+    current_policy_ptr: *const c_char,
+    current_policy_len: c_int,
+    axis_ptr: *const c_char,
+) -> c_int {
+    catch_panic(|| {
+        let policy_bytes =
+            ffi_read_bytes!("current policy", current_policy_ptr, current_policy_len);
+        let mut policy = ffi_unwrap!(read_policy(policy_bytes));
+        let axis_string = ffi_read_string!("axis", axis_ptr);
+        let axis = ffi_unwrap!(serde_json::from_str(&axis_string));
+
+        ffi_unwrap!(policy.add_axis(axis));
+        log_lints(&policy);
+
+        ffi_write_bytes!(
+            "updated policy",
+            &ffi_unwrap!(serde_json::to_vec(&policy)),
+            updated_policy_ptr,
+            updated_policy_len
+        );
+
+        0
+    })
+}
+
+/// Rotates an attribute of a policy, changing its underlying value with an
+/// unused value.
+///
+/// - `updated_policy_ptr`  : output policy buffer (JSON)
+/// - `updated_policy_len`  : size of the output buffer
+/// - `current_policy_ptr`  : policy to update (JSON)
+/// - `current_policy_len`  : size of the policy to update
+/// - `attribute_ptr`       : attribute to rotate, written `Axis::Name`
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_policy_rotate_attribute(
+    updated_policy_ptr: *mut c_char,
+    updated_policy_len: *mut c_int,
+    current_policy_ptr: *const c_char,
+    current_policy_len: c_int,
+    attribute_ptr: *const c_char,
+) -> c_int {
+    catch_panic(|| {
+        let policy_bytes =
+            ffi_read_bytes!("current policy", current_policy_ptr, current_policy_len);
+        let mut policy = ffi_unwrap!(read_policy(policy_bytes));
+        let attr_string = ffi_read_string!("attribute", attribute_ptr);
+        let attr = ffi_unwrap!(attr_string.parse::<Attribute>());
+
+        ffi_unwrap!(policy.rotate(&attr));
+        log_lints(&policy);
+
+        ffi_write_bytes!(
+            "updated policy",
+            &ffi_unwrap!(serde_json::to_vec(&policy)),
+            updated_policy_ptr,
+            updated_policy_len
+        );
+
+        0
+    })
+}
+
+/// Writes a policy as JSON in the current format. The given policy may use
+/// the legacy format.
+///
+/// - `json_ptr`    : output JSON buffer
+/// - `json_len`    : size of the output buffer
+/// - `policy_ptr`  : policy (JSON, current or legacy format)
+/// - `policy_len`  : size of the policy
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_policy_to_json(
+    json_ptr: *mut c_char,
+    json_len: *mut c_int,
+    policy_ptr: *const c_char,
+    policy_len: c_int,
+) -> c_int {
+    catch_panic(|| {
+        let policy_bytes = ffi_read_bytes!("policy", policy_ptr, policy_len);
+        let policy = ffi_unwrap!(read_shared_policy(policy_bytes));
+
+        ffi_write_bytes!(
+            "policy JSON",
+            &ffi_unwrap!(serde_json::to_vec(&*policy)),
+            json_ptr,
+            json_len
+        );
+
+        0
+    })
+}
+
+/// Writes a policy in the compact binary format.
+///
+/// - `bytes_ptr`   : output binary buffer
+/// - `bytes_len`   : size of the output buffer
+/// - `policy_ptr`  : policy (JSON, current or legacy format)
+/// - `policy_len`  : size of the policy
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_policy_to_bytes(
+    bytes_ptr: *mut c_char,
+    bytes_len: *mut c_int,
+    policy_ptr: *const c_char,
+    policy_len: c_int,
+) -> c_int {
+    catch_panic(|| {
+        let policy_bytes = ffi_read_bytes!("policy", policy_ptr, policy_len);
+        let policy = ffi_unwrap!(read_shared_policy(policy_bytes));
+
+        ffi_write_bytes!(
+            "policy bytes",
+            &ffi_unwrap!(policy.to_bytes()),
+            bytes_ptr,
+            bytes_len
+        );
+
+        0
+    })
+}
+
+/// Writes as JSON a policy given in the compact binary format.
+///
+/// - `json_ptr`    : output JSON buffer
+/// - `json_len`    : size of the output buffer
+/// - `bytes_ptr`   : policy (binary)
+/// - `bytes_len`   : size of the policy
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_policy_from_bytes(
+    json_ptr: *mut c_char,
+    json_len: *mut c_int,
+    bytes_ptr: *const c_char,
+    bytes_len: c_int,
+) -> c_int {
+    catch_panic(|| {
+        let policy_bytes = ffi_read_bytes!("policy bytes", bytes_ptr, bytes_len);
+        let policy = ffi_unwrap!(Policy::from_bytes(policy_bytes));
+
+        ffi_write_bytes!(
+            "policy JSON",
+            &ffi_unwrap!(serde_json::to_vec(&policy)),
+            json_ptr,
+            json_len
+        );
+
+        0
+    })
+}
+
+/// Writes the values given to an attribute over rotations as a JSON array,
+/// the current value first.
+///
+/// - `values_ptr`      : output values buffer (JSON)
+/// - `values_len`      : size of the output buffer
+/// - `policy_ptr`      : policy (JSON)
+/// - `policy_len`      : size of the policy
+/// - `attribute_ptr`   : attribute, written `Axis::Name`
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_policy_attribute_values(
+    values_ptr: *mut c_char,
+    values_len: *mut c_int,
+    policy_ptr: *const c_char,
+    policy_len: c_int,
+    attribute_ptr: *const c_char,
+) -> c_int {
+    catch_panic(|| {
+        let policy_bytes = ffi_read_bytes!("policy", policy_ptr, policy_len);
+        let policy = ffi_unwrap!(read_shared_policy(policy_bytes));
+        let attr_string = ffi_read_string!("attribute", attribute_ptr);
+        let attr = ffi_unwrap!(attr_string.parse::<Attribute>());
+        let values = ffi_unwrap!(policy.attribute_values(&attr));
+
+        ffi_write_bytes!(
+            "attribute values",
+            &ffi_unwrap!(serde_json::to_vec(&values)),
+            values_ptr,
+            values_len
+        );
+
+        0
+    })
+}
+
+/// Writes the attributes of a policy, or of one of its axes, as a JSON array
+/// of strings written `Axis::Name`, e.g.
+/// `["Department::R&D", "Department::HR"]`. Attributes are listed by axis,
+/// in the order the axes were added, then in axis order.
+///
+/// - `attributes_ptr`  : output attributes buffer (JSON)
+/// - `attributes_len`  : size of the output buffer
+/// - `policy_ptr`      : policy (JSON)
+/// - `policy_len`      : size of the policy
+/// - `axis_ptr`        : name of the axis to list, null to list all axes
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_policy_attributes(
+    attributes_ptr: *mut c_char,
+    attributes_len: *mut c_int,
+    policy_ptr: *const c_char,
+    policy_len: c_int,
+    axis_ptr: *const c_char,
+) -> c_int {
+    catch_panic(|| {
+        let policy_bytes = ffi_read_bytes!("policy", policy_ptr, policy_len);
+        let policy = ffi_unwrap!(read_shared_policy(policy_bytes));
+        let mut attributes = policy.attributes();
+        if !axis_ptr.is_null() {
+            let axis_string = ffi_read_string!("axis", axis_ptr);
+            let axis = match policy.axis(&axis_string) {
+                Some(axis) => axis,
+                None => {
+                    ffi_bail!(format!("axis not found: {axis_string}"));
+                }
+            };
+            attributes.retain(|attribute| attribute.axis == *axis.name());
+        }
+        policy.sort_attributes(&mut attributes);
+
+        ffi_write_bytes!(
+            "attributes",
+            &ffi_unwrap!(serde_json::to_vec(&attributes)),
+            attributes_ptr,
+            attributes_len
+        );
+
+        0
+    })
+}
+
+/// Retrieves the current value of an attribute.
+///
+/// - `value`           : output value
+/// - `policy_ptr`      : policy (JSON)
+/// - `policy_len`      : size of the policy
+/// - `attribute_ptr`   : attribute, written `Axis::Name`
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_policy_attribute_current_value(
+    value: *mut c_uint,
+    policy_ptr: *const c_char,
+    policy_len: c_int,
+    attribute_ptr: *const c_char,
+) -> c_int {
+    catch_panic(|| {
+        ffi_not_null!(value, "value pointer should not be null");
+        let policy_bytes = ffi_read_bytes!("policy", policy_ptr, policy_len);
+        let policy = ffi_unwrap!(read_shared_policy(policy_bytes));
+        let attr_string = ffi_read_string!("attribute", attribute_ptr);
+        let attr = ffi_unwrap!(attr_string.parse::<Attribute>());
+
+        *value = ffi_unwrap!(policy.attribute_current_value(&attr));
+
+        0
+    })
+}
+
+/// Retrieves the number of attribute creations (additions + rotations) still
+/// allowed by a policy.
+///
+/// - `capacity`        : output number of remaining attribute creations
+/// - `policy_ptr`      : policy (JSON)
+/// - `policy_len`      : size of the policy
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_policy_remaining_capacity(
+    capacity: *mut c_uint,
+    policy_ptr: *const c_char,
+    policy_len: c_int,
+) -> c_int {
+    catch_panic(|| {
+        ffi_not_null!(capacity, "capacity pointer should not be null");
+        let policy_bytes = ffi_read_bytes!("policy", policy_ptr, policy_len);
+        let policy = ffi_unwrap!(read_shared_policy(policy_bytes));
+
+        *capacity = policy.remaining_attribute_creations();
+
+        0
+    })
+}
+
+/// Same as `h_policy_new`.
+///
 /// # Safety
 #[no_mangle]
 pub unsafe extern "C" fn h_policy(
@@ -8,12 +341,11 @@ pub unsafe extern "C" fn h_policy(
     policy_len: *mut c_int,
     max_attribute_creations: c_int,
 ) -> c_int {
-    let policy = Policy::new(max_attribute_creations as u32);
-    let policy_bytes = ffi_unwrap!(serde_json::to_vec(&policy));
-    ffi_write_bytes!("policy", &policy_bytes, policy_ptr, policy_len);
-    0
+    h_policy_new(policy_ptr, policy_len, max_attribute_creations)
 }
 
+/// Same as `h_policy_add_axis`.
+///
 /// # Safety
 #[no_mangle]
 pub unsafe extern "C" fn h_add_policy_axis(
@@ -23,23 +355,17 @@ pub unsafe extern "C" fn h_add_policy_axis(
     current_policy_len: c_int,
     axis_ptr: *mut c_char,
 ) -> c_int {
-    let policy_bytes = ffi_read_bytes!("current policy", current_policy_ptr, current_policy_len);
-    let mut policy = ffi_unwrap!(Policy::parse_and_convert(policy_bytes));
-    let axis_string = ffi_read_string!("axis", axis_ptr);
-    let axis = ffi_unwrap!(serde_json::from_str(&axis_string));
-
-    ffi_unwrap!(policy.add_axis(axis));
-
-    ffi_write_bytes!(
-        "updated policy",
-        &ffi_unwrap!(serde_json::to_vec(&policy)),
+    h_policy_add_axis(
         updated_policy_ptr,
-        updated_policy_len
-    );
-
-    0
+        updated_policy_len,
+        current_policy_ptr,
+        current_policy_len,
+        axis_ptr,
+    )
 }
 
+/// Same as `h_policy_rotate_attribute`.
+///
 /// # Safety
 #[no_mangle]
 pub unsafe extern "C" fn h_rotate_attribute(
@@ -49,27 +375,253 @@ pub unsafe extern "C" fn h_rotate_attribute(
     current_policy_len: c_int,
     axis_ptr: *const c_char,
 ) -> c_int {
-    let policy_bytes = ffi_read_bytes!("current policy", current_policy_ptr, current_policy_len);
-    let mut policy = ffi_unwrap!(Policy::parse_and_convert(policy_bytes));
-    let attr_string = ffi_read_string!("axis", axis_ptr);
-    let attr = ffi_unwrap!(Attribute::try_from(attr_string.as_str()));
+    h_policy_rotate_attribute(
+        updated_policy_ptr,
+        updated_policy_len,
+        current_policy_ptr,
+        current_policy_len,
+        axis_ptr,
+    )
+}
 
-    ffi_unwrap!(policy.rotate(&attr));
+/// Rotates a comma-separated list of attributes, e.g.
+/// `Department::FIN, Security Level::Top Secret`.
+///
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_rotate_attributes(
+    updated_policy_ptr: *mut c_char,
+    updated_policy_len: *mut c_int,
+    current_policy_ptr: *const c_char,
+    current_policy_len: c_int,
+    attributes_ptr: *const c_char,
+) -> c_int {
+    catch_panic(|| {
+        let policy_bytes =
+            ffi_read_bytes!("current policy", current_policy_ptr, current_policy_len);
+        let mut policy = ffi_unwrap!(read_policy(policy_bytes));
+        let attributes_string = ffi_read_string!("attributes", attributes_ptr);
+        let attributes = ffi_unwrap!(attributes_string.parse::<Attributes>());
 
-    ffi_write_bytes!(
-        "updated policy",
-        &ffi_unwrap!(serde_json::to_vec(&policy)),
-        updated_policy_ptr,
-        updated_policy_len
-    );
+        for attr in &attributes {
+            ffi_unwrap!(policy.rotate(attr));
+        }
+        log_lints(&policy);
 
-    0
+        ffi_write_bytes!(
+            "updated policy",
+            &ffi_unwrap!(serde_json::to_vec(&policy)),
+            updated_policy_ptr,
+            updated_policy_len
+        );
+
+        0
+    })
+}
+
+/// Rotates a comma-separated list of attributes and writes, in addition to
+/// the updated policy, the new value of each rotated attribute as a JSON
+/// object, e.g. `{"Department::FIN": 12}`.
+///
+/// If one of the output buffers is null or too small, the required sizes of
+/// both buffers are written and `FFI_BUFFER_TOO_SMALL` is returned.
+///
+/// - `updated_policy_ptr`  : output policy buffer (JSON)
+/// - `updated_policy_len`  : size of the output policy buffer
+/// - `new_values_ptr`      : output new values buffer (JSON)
+/// - `new_values_len`      : size of the output new values buffer
+/// - `current_policy_ptr`  : policy to update (JSON)
+/// - `current_policy_len`  : size of the policy to update
+/// - `attributes_ptr`      : attributes to rotate, e.g.
+///   `Department::FIN, Security Level::Top Secret`
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_policy_rotate_attributes(
+    updated_policy_ptr: *mut c_char,
+    updated_policy_len: *mut c_int,
+    new_values_ptr: *mut c_char,
+    new_values_len: *mut c_int,
+    current_policy_ptr: *const c_char,
+    current_policy_len: c_int,
+    attributes_ptr: *const c_char,
+) -> c_int {
+    catch_panic(|| {
+        ffi_not_null!(
+            updated_policy_len,
+            "updated policy length pointer should not be null"
+        );
+        ffi_not_null!(
+            new_values_len,
+            "new values length pointer should not be null"
+        );
+        let policy_bytes =
+            ffi_read_bytes!("current policy", current_policy_ptr, current_policy_len);
+        let mut policy = ffi_unwrap!(read_policy(policy_bytes));
+        let attributes_string = ffi_read_string!("attributes", attributes_ptr);
+        let attributes = ffi_unwrap!(attributes_string.parse::<Attributes>());
+
+        let mut new_values = BTreeMap::new();
+        for attr in &attributes {
+            ffi_unwrap!(policy.rotate(attr));
+            new_values.insert(attr, ffi_unwrap!(policy.attribute_current_value(attr)));
+        }
+        log_lints(&policy);
+        let updated_policy = ffi_unwrap!(serde_json::to_vec(&policy));
+        let new_values = ffi_unwrap!(serde_json::to_vec(&new_values));
+
+        ffi_write_bytes_pair!(
+            (
+                "updated policy",
+                &updated_policy,
+                updated_policy_ptr,
+                updated_policy_len
+            ),
+            ("new values", &new_values, new_values_ptr, new_values_len),
+        );
+
+        0
+    })
+}
+
+/// Writes the differences between two policies as a JSON object listing the
+/// `added_axes`, `removed_axes`, `added_attributes`, `removed_attributes`,
+/// `rotated_attributes` and `hint_changes` from the first policy to the
+/// second one, and the new `max_attribute_creations` if it changed (`null`
+/// otherwise).
+///
+/// - `diff_ptr`        : output diff buffer (JSON)
+/// - `diff_len`        : size of the output buffer
+/// - `policy_ptr`      : first policy (JSON)
+/// - `policy_len`      : size of the first policy
+/// - `other_ptr`       : second policy (JSON)
+/// - `other_len`       : size of the second policy
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_policy_diff(
+    diff_ptr: *mut c_char,
+    diff_len: *mut c_int,
+    policy_ptr: *const c_char,
+    policy_len: c_int,
+    other_ptr: *const c_char,
+    other_len: c_int,
+) -> c_int {
+    catch_panic(|| {
+        let policy_bytes = ffi_read_bytes!("policy", policy_ptr, policy_len);
+        let policy = ffi_unwrap!(read_shared_policy(policy_bytes));
+        let other_bytes = ffi_read_bytes!("other policy", other_ptr, other_len);
+        let other = ffi_unwrap!(read_shared_policy(other_bytes));
+
+        ffi_write_bytes!(
+            "policy diff",
+            &ffi_unwrap!(serde_json::to_vec(&policy.diff(&other))),
+            diff_ptr,
+            diff_len
+        );
+
+        0
+    })
+}
+
+/// Merges two copies of a policy modified independently. See
+/// `Policy::merge` for the merge rules.
+///
+/// - `merged_policy_ptr`   : output policy buffer (JSON)
+/// - `merged_policy_len`   : size of the output buffer
+/// - `policy_ptr`          : policy to merge into (JSON)
+/// - `policy_len`          : size of the policy to merge into
+/// - `other_ptr`           : policy to merge (JSON)
+/// - `other_len`           : size of the policy to merge
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_policy_merge(
+    merged_policy_ptr: *mut c_char,
+    merged_policy_len: *mut c_int,
+    policy_ptr: *const c_char,
+    policy_len: c_int,
+    other_ptr: *const c_char,
+    other_len: c_int,
+) -> c_int {
+    catch_panic(|| {
+        let policy_bytes = ffi_read_bytes!("policy", policy_ptr, policy_len);
+        let mut policy = ffi_unwrap!(read_policy(policy_bytes));
+        let other_bytes = ffi_read_bytes!("other policy", other_ptr, other_len);
+        let other = ffi_unwrap!(read_shared_policy(other_bytes));
+
+        ffi_unwrap!(policy.merge(&other));
+        log_lints(&policy);
+
+        ffi_write_bytes!(
+            "merged policy",
+            &ffi_unwrap!(serde_json::to_vec(&policy)),
+            merged_policy_ptr,
+            merged_policy_len
+        );
+
+        0
+    })
+}
+
+/// Converts a policy in the legacy format to the current format, and writes
+/// an upgrade report as a JSON object:
+///
+/// - `legacy`      : `true` if the given policy used the legacy format
+/// - `axes`        : number of axes of the policy
+/// - `attributes`  : number of attributes of the policy
+/// - `changes`     : descriptions of the changes made by the conversion
+/// - `lints`       : warnings about the upgraded policy
+///
+/// A policy already in the current format is written back unchanged. If one
+/// of the output buffers is null or too small, the required sizes of both
+/// buffers are written and `FFI_BUFFER_TOO_SMALL` is returned.
+///
+/// - `upgraded_policy_ptr` : output policy buffer (JSON)
+/// - `upgraded_policy_len` : size of the output policy buffer
+/// - `report_ptr`          : output report buffer (JSON)
+/// - `report_len`          : size of the output report buffer
+/// - `legacy_policy_ptr`   : policy to upgrade (JSON)
+/// - `legacy_policy_len`   : size of the policy to upgrade
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_policy_upgrade_legacy(
+    upgraded_policy_ptr: *mut c_char,
+    upgraded_policy_len: *mut c_int,
+    report_ptr: *mut c_char,
+    report_len: *mut c_int,
+    legacy_policy_ptr: *const c_char,
+    legacy_policy_len: c_int,
+) -> c_int {
+    catch_panic(|| {
+        let policy_bytes = ffi_read_bytes!("legacy policy", legacy_policy_ptr, legacy_policy_len);
+        let (policy, is_legacy) = ffi_unwrap!(Policy::parse_and_detect_legacy(policy_bytes));
+
+        let report = UpgradeReport::new(&policy, is_legacy);
+        let upgraded_policy = ffi_unwrap!(serde_json::to_vec(&policy));
+        let report = ffi_unwrap!(serde_json::to_vec(&report));
+
+        ffi_write_bytes_pair!(
+            (
+                "upgraded policy",
+                &upgraded_policy,
+                upgraded_policy_ptr,
+                upgraded_policy_len
+            ),
+            ("upgrade report", &report, report_ptr, report_len),
+        );
+
+        0
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{interfaces::ffi::error::get_last_error, tests::policy};
+    use crate::{
+        interfaces::ffi::error::{
+            get_last_error, h_get_last_error_code, FFI_BUFFER_TOO_SMALL, FFI_OK, FFI_PANIC,
+        },
+        tests::policy,
+        EncryptionHint, PolicyAxis,
+    };
     use std::ffi::{CStr, CString};
 
     #[test]
@@ -143,4 +695,387 @@ mod tests {
         // assert ffi and non-ffi have same behavior.
         assert_eq!(policy, ffi_rotated_policy);
     }
+
+    /// Calls `f` with an output buffer and returns the bytes it wrote.
+    unsafe fn call(f: impl FnOnce(*mut c_char, *mut c_int) -> c_int) -> Vec<u8> {
+        let mut bytes = vec![0u8; 8192];
+        let mut len = bytes.len() as c_int;
+        let res = f(bytes.as_mut_ptr().cast(), &mut len);
+        if res != 0 {
+            let mut error = vec![0u8; 8192];
+            let error_ptr = error.as_mut_ptr().cast();
+            let mut error_len = error.len() as c_int;
+            get_last_error(error_ptr, &mut error_len);
+            panic!("{}", CStr::from_ptr(error_ptr).to_str().unwrap());
+        }
+        bytes.truncate(len as usize);
+        bytes
+    }
+
+    #[test]
+    fn test_policy_lifecycle() {
+        let mut expected = policy().unwrap();
+        let security_level = CString::new(
+            serde_json::to_string(&PolicyAxis::new(
+                "Security Level",
+                vec![
+                    ("Protected", EncryptionHint::Classic),
+                    ("Confidential", EncryptionHint::Classic),
+                    ("Top Secret", EncryptionHint::Hybridized),
+                ],
+                true,
+            ))
+            .unwrap(),
+        )
+        .unwrap();
+        let department = CString::new(
+            serde_json::to_string(&PolicyAxis::new(
+                "Department",
+                ["R&D", "HR", "MKG", "FIN"]
+                    .into_iter()
+                    .map(|name| (name, EncryptionHint::Classic))
+                    .collect(),
+                false,
+            ))
+            .unwrap(),
+        )
+        .unwrap();
+        let attribute = CString::new("Department::HR").unwrap();
+
+        let policy_bytes = unsafe {
+            let mut policy_bytes = call(|ptr, len| h_policy_new(ptr, len, 100));
+            for axis in [&security_level, &department] {
+                policy_bytes = call(|ptr, len| {
+                    h_policy_add_axis(
+                        ptr,
+                        len,
+                        policy_bytes.as_ptr().cast(),
+                        policy_bytes.len() as c_int,
+                        axis.as_ptr(),
+                    )
+                });
+            }
+            policy_bytes = call(|ptr, len| {
+                h_policy_rotate_attribute(
+                    ptr,
+                    len,
+                    policy_bytes.as_ptr().cast(),
+                    policy_bytes.len() as c_int,
+                    attribute.as_ptr(),
+                )
+            });
+            call(|ptr, len| {
+                h_policy_to_json(
+                    ptr,
+                    len,
+                    policy_bytes.as_ptr().cast(),
+                    policy_bytes.len() as c_int,
+                )
+            })
+        };
+
+        expected
+            .rotate(&Attribute::new("Department", "HR"))
+            .unwrap();
+        assert_eq!(expected, Policy::parse_and_convert(&policy_bytes).unwrap());
+    }
+
+    #[test]
+    fn test_attribute_values() {
+        let mut policy = policy().unwrap();
+        let attribute = Attribute::new("Department", "HR");
+        policy.rotate(&attribute).unwrap();
+        let policy_bytes = serde_json::to_vec(&policy).unwrap();
+        let attribute_string = CString::new(attribute.to_string()).unwrap();
+
+        let values = unsafe {
+            call(|ptr, len| {
+                h_policy_attribute_values(
+                    ptr,
+                    len,
+                    policy_bytes.as_ptr().cast(),
+                    policy_bytes.len() as c_int,
+                    attribute_string.as_ptr(),
+                )
+            })
+        };
+        assert_eq!(
+            policy.attribute_values(&attribute).unwrap(),
+            serde_json::from_slice::<Vec<u32>>(&values).unwrap()
+        );
+
+        let mut value = 0;
+        let res = unsafe {
+            h_policy_attribute_current_value(
+                &mut value,
+                policy_bytes.as_ptr().cast(),
+                policy_bytes.len() as c_int,
+                attribute_string.as_ptr(),
+            )
+        };
+        assert_eq!(0, res);
+        assert_eq!(policy.attribute_current_value(&attribute).unwrap(), value);
+    }
+
+    #[test]
+    fn test_catch_panic_and_error_code() {
+        let res = catch_panic(|| panic!("boom"));
+        assert_eq!(FFI_PANIC, res);
+        // Reading the code leaves the message in place.
+        assert_eq!(FFI_PANIC, h_get_last_error_code());
+        assert_eq!(FFI_PANIC, h_get_last_error_code());
+
+        let mut message = vec![0_u8; 64];
+        let mut len = message.len() as c_int;
+        assert_eq!(0, unsafe {
+            get_last_error(message.as_mut_ptr().cast(), &mut len)
+        });
+        let message = unsafe { CStr::from_ptr(message.as_ptr().cast()) };
+        assert_eq!("panic: boom", message.to_str().unwrap());
+        assert_eq!(FFI_OK, h_get_last_error_code());
+    }
+
+    #[test]
+    fn test_input_validation() {
+        let last_error = || {
+            let mut message = vec![0_u8; 256];
+            let mut len = message.len() as c_int;
+            assert_eq!(0, unsafe {
+                get_last_error(message.as_mut_ptr().cast(), &mut len)
+            });
+            unsafe { CStr::from_ptr(message.as_ptr().cast()) }
+                .to_str()
+                .unwrap()
+                .to_owned()
+        };
+        let policy_json = serde_json::to_vec(&policy().unwrap()).unwrap();
+        let mut capacity = 0;
+
+        let res =
+            unsafe { h_policy_remaining_capacity(&mut capacity, policy_json.as_ptr().cast(), -1) };
+        assert_eq!(1, res);
+        assert!(last_error().contains("greater than zero, got -1"));
+
+        let res = unsafe {
+            h_policy_remaining_capacity(&mut capacity, policy_json.as_ptr().cast(), c_int::MAX)
+        };
+        assert_eq!(1, res);
+        assert!(last_error().contains("should not exceed"));
+
+        let attribute = b"Department::\xffHR\0";
+        let mut updated = vec![0_u8; 8192];
+        let mut updated_len = updated.len() as c_int;
+        let res = unsafe {
+            h_rotate_attribute(
+                updated.as_mut_ptr().cast(),
+                &mut updated_len,
+                policy_json.as_ptr().cast(),
+                policy_json.len() as c_int,
+                attribute.as_ptr().cast(),
+            )
+        };
+        assert_eq!(1, res);
+        assert!(last_error().contains("invalid byte at offset 12"));
+    }
+
+    #[test]
+    fn test_binary_serialization() {
+        let policy = policy().unwrap();
+        let policy_json = serde_json::to_vec(&policy).unwrap();
+
+        let bytes = unsafe {
+            call(|ptr, len| {
+                h_policy_to_bytes(
+                    ptr,
+                    len,
+                    policy_json.as_ptr().cast(),
+                    policy_json.len() as c_int,
+                )
+            })
+        };
+        assert_eq!(policy.to_bytes().unwrap(), bytes);
+
+        let json = unsafe {
+            call(|ptr, len| {
+                h_policy_from_bytes(ptr, len, bytes.as_ptr().cast(), bytes.len() as c_int)
+            })
+        };
+        assert_eq!(policy, Policy::parse_and_convert(&json).unwrap());
+    }
+
+    #[test]
+    fn test_attributes() {
+        let policy = policy().unwrap();
+        let policy_bytes = serde_json::to_vec(&policy).unwrap();
+
+        let list = |axis: Option<&CString>| unsafe {
+            call(|ptr, len| {
+                h_policy_attributes(
+                    ptr,
+                    len,
+                    policy_bytes.as_ptr().cast(),
+                    policy_bytes.len() as c_int,
+                    axis.map_or(std::ptr::null(), |axis| axis.as_ptr()),
+                )
+            })
+        };
+
+        let mut expected = policy.attributes();
+        policy.sort_attributes(&mut expected);
+        assert_eq!(
+            expected,
+            serde_json::from_slice::<Vec<Attribute>>(&list(None)).unwrap()
+        );
+        assert_eq!(
+            r#"["Department::R&D","Department::HR","Department::MKG","Department::FIN"]"#,
+            String::from_utf8(list(Some(&CString::new("Department").unwrap()))).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_remaining_capacity() {
+        let mut policy = policy().unwrap();
+        policy.rotate(&Attribute::new("Department", "HR")).unwrap();
+        let policy_bytes = serde_json::to_vec(&policy).unwrap();
+
+        let mut capacity = 0;
+        let res = unsafe {
+            h_policy_remaining_capacity(
+                &mut capacity,
+                policy_bytes.as_ptr().cast(),
+                policy_bytes.len() as c_int,
+            )
+        };
+        assert_eq!(0, res);
+        assert_eq!(100 - 7 - 1, capacity);
+    }
+
+    #[test]
+    fn test_rotate_attributes_with_new_values() {
+        let mut policy = policy().unwrap();
+        let policy_bytes = serde_json::to_vec(&policy).unwrap();
+        let attributes = CString::new("Department::FIN, Security Level::Top Secret").unwrap();
+
+        let rotate = |policy_len: &mut c_int, values_len: &mut c_int, buffers: bool| {
+            let mut updated_policy = vec![0_u8; *policy_len as usize];
+            let mut new_values = vec![0_u8; *values_len as usize];
+            let res = unsafe {
+                h_policy_rotate_attributes(
+                    if buffers {
+                        updated_policy.as_mut_ptr().cast()
+                    } else {
+                        std::ptr::null_mut()
+                    },
+                    policy_len,
+                    if buffers {
+                        new_values.as_mut_ptr().cast()
+                    } else {
+                        std::ptr::null_mut()
+                    },
+                    values_len,
+                    policy_bytes.as_ptr().cast(),
+                    policy_bytes.len() as c_int,
+                    attributes.as_ptr(),
+                )
+            };
+            updated_policy.truncate(*policy_len as usize);
+            new_values.truncate(*values_len as usize);
+            (res, updated_policy, new_values)
+        };
+
+        // Query the sizes of the buffers to allocate.
+        let (mut policy_len, mut values_len) = (0, 0);
+        let (res, _, _) = rotate(&mut policy_len, &mut values_len, false);
+        assert_eq!(FFI_BUFFER_TOO_SMALL, res);
+
+        let (res, updated_policy, new_values) = rotate(&mut policy_len, &mut values_len, true);
+        assert_eq!(0, res);
+        for attribute in ["Department::FIN", "Security Level::Top Secret"] {
+            policy.rotate(&attribute.parse().unwrap()).unwrap();
+        }
+        assert_eq!(policy, Policy::parse_and_convert(&updated_policy).unwrap());
+        assert_eq!(
+            format!(
+                r#"{{"Department::FIN":{},"Security Level::Top Secret":{}}}"#,
+                policy
+                    .attribute_current_value(("Department", "FIN"))
+                    .unwrap(),
+                policy
+                    .attribute_current_value(("Security Level", "Top Secret"))
+                    .unwrap()
+            ),
+            String::from_utf8(new_values).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_upgrade_legacy() {
+        let legacy_policy = include_bytes!("../../../tests/legacy_policy.json");
+        let mut upgraded_policy = vec![0_u8; 8192];
+        let mut upgraded_policy_len = upgraded_policy.len() as c_int;
+        let mut report = vec![0_u8; 8192];
+        let mut report_len = report.len() as c_int;
+        let res = unsafe {
+            h_policy_upgrade_legacy(
+                upgraded_policy.as_mut_ptr().cast(),
+                &mut upgraded_policy_len,
+                report.as_mut_ptr().cast(),
+                &mut report_len,
+                legacy_policy.as_ptr().cast(),
+                legacy_policy.len() as c_int,
+            )
+        };
+        assert_eq!(0, res);
+
+        let upgraded_policy: Policy =
+            serde_json::from_slice(&upgraded_policy[..upgraded_policy_len as usize]).unwrap();
+        assert_eq!(
+            Policy::parse_and_convert(legacy_policy).unwrap(),
+            upgraded_policy
+        );
+        let report: serde_json::Value =
+            serde_json::from_slice(&report[..report_len as usize]).unwrap();
+        assert_eq!(true, report["legacy"]);
+        assert_eq!(upgraded_policy.attributes.len(), report["attributes"]);
+    }
+
+    #[test]
+    fn test_diff_and_merge() {
+        let policy = policy().unwrap();
+        let mut other = policy.clone();
+        other.rotate(&Attribute::new("Department", "HR")).unwrap();
+        let policy_bytes = serde_json::to_vec(&policy).unwrap();
+        let other_bytes = serde_json::to_vec(&other).unwrap();
+
+        let diff = unsafe {
+            call(|ptr, len| {
+                h_policy_diff(
+                    ptr,
+                    len,
+                    policy_bytes.as_ptr().cast(),
+                    policy_bytes.len() as c_int,
+                    other_bytes.as_ptr().cast(),
+                    other_bytes.len() as c_int,
+                )
+            })
+        };
+        assert_eq!(
+            policy.diff(&other),
+            serde_json::from_slice::<crate::PolicyDiff>(&diff).unwrap()
+        );
+
+        let merged = unsafe {
+            call(|ptr, len| {
+                h_policy_merge(
+                    ptr,
+                    len,
+                    policy_bytes.as_ptr().cast(),
+                    policy_bytes.len() as c_int,
+                    other_bytes.as_ptr().cast(),
+                    other_bytes.len() as c_int,
+                )
+            })
+        };
+        assert_eq!(other, Policy::parse_and_convert(&merged).unwrap());
+    }
 }
@@ -0,0 +1,84 @@
+use std::ffi::{c_char, c_int, c_uint};
+
+use crate::interfaces::ffi::error::catch_panic;
+
+/// Version of the C ABI exposed by this library.
+///
+/// It is bumped on every incompatible change of an exported function
+/// signature or of a status code meaning. Callers should compare it to the
+/// value returned by `h_abi_version` when loading the library.
+pub const ABE_POLICY_ABI_VERSION: c_uint = 1;
+
+/// Get the version of the C ABI implemented by the loaded library.
+#[no_mangle]
+pub extern "C" fn h_abi_version() -> c_uint {
+    ABE_POLICY_ABI_VERSION
+}
+
+/// Feature flag set if encryption hints (hybridized or classic encryption)
+/// are supported.
+pub const FFI_FEATURE_HYBRIDIZATION: c_uint = 1;
+
+/// Feature flag set if the compact binary policy format is supported.
+pub const FFI_FEATURE_BINARY_FORMAT: c_uint = 1 << 1;
+
+/// Feature flag set if the library was built with random policy generation.
+pub const FFI_FEATURE_RANDOM: c_uint = 1 << 2;
+
+/// Writes the version of the library, e.g. `3.0.4`.
+///
+/// - `version_ptr` : output version buffer
+/// - `version_len` : size of the output buffer
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_get_version(version_ptr: *mut c_char, version_len: *mut c_int) -> c_int {
+    catch_panic(|| {
+        ffi_write_bytes!(
+            "version",
+            env!("CARGO_PKG_VERSION").as_bytes(),
+            version_ptr,
+            version_len
+        );
+        0
+    })
+}
+
+/// Get the features supported by the loaded library, as a combination of the
+/// `FFI_FEATURE_*` flags.
+#[no_mangle]
+pub extern "C" fn h_get_features() -> c_uint {
+    let mut features = FFI_FEATURE_HYBRIDIZATION | FFI_FEATURE_BINARY_FORMAT;
+    if cfg!(feature = "random") {
+        features |= FFI_FEATURE_RANDOM;
+    }
+    features
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_header_abi_version() {
+        let header = include_str!("../../../include/abe_policy.h");
+        assert!(header.contains(&format!(
+            "#define ABE_POLICY_ABI_VERSION {ABE_POLICY_ABI_VERSION}"
+        )));
+        assert!(header.contains("unsigned int h_abi_version(void);"));
+        assert_eq!(ABE_POLICY_ABI_VERSION, h_abi_version());
+    }
+
+    #[test]
+    fn test_version_and_features() {
+        let mut version = vec![0_u8; 32];
+        let mut version_len = version.len() as c_int;
+        assert_eq!(0, unsafe {
+            h_get_version(version.as_mut_ptr().cast(), &mut version_len)
+        });
+        assert_eq!(
+            env!("CARGO_PKG_VERSION").as_bytes(),
+            &version[..version_len as usize]
+        );
+        assert_ne!(0, h_get_features() & FFI_FEATURE_BINARY_FORMAT);
+    }
+}
@@ -0,0 +1,385 @@
+//! UTF-16 variants of the FFI functions taking strings, for callers such as
+//! Windows and .NET using wide strings natively.
+//!
+//! Each `_w` function takes NULL-terminated UTF-16 strings where its UTF-8
+//! counterpart takes C strings; its other arguments and its outputs, which
+//! are UTF-8, are unchanged. Invalid UTF-16 strings are rejected rather than
+//! converted lossily.
+
+use std::{
+    ffi::{c_char, c_int, c_uint, CString},
+    ptr,
+};
+
+use crate::interfaces::ffi::{
+    access_policy::{
+        h_access_policy_to_boolean_expression, h_access_policy_to_combinations,
+        h_parse_boolean_access_policy, h_validate_boolean_expression,
+    },
+    error::catch_panic,
+    handle::{
+        h_policy_handle_add_axis, h_policy_handle_attribute_current_value,
+        h_policy_handle_rotate_attribute, PolicyHandle,
+    },
+    policy::{
+        h_policy_add_axis, h_policy_attribute_current_value, h_policy_attribute_values,
+        h_policy_attributes, h_policy_rotate_attribute, h_policy_rotate_attributes,
+        h_rotate_attributes,
+    },
+};
+
+/// UTF-16 variant of `h_parse_boolean_access_policy`.
+///
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_parse_boolean_access_policy_w(
+    access_policy_ptr: *mut c_char,
+    access_policy_len: *mut c_int,
+    boolean_expression_ptr: *const u16,
+) -> c_int {
+    catch_panic(|| {
+        let boolean_expression = ffi_unwrap!(CString::new(ffi_read_wstring!(
+            "boolean expression",
+            boolean_expression_ptr
+        )));
+        h_parse_boolean_access_policy(
+            access_policy_ptr,
+            access_policy_len,
+            boolean_expression.as_ptr(),
+        )
+    })
+}
+
+/// UTF-16 variant of `h_access_policy_to_boolean_expression`.
+///
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_access_policy_to_boolean_expression_w(
+    boolean_expression_ptr: *mut c_char,
+    boolean_expression_len: *mut c_int,
+    access_policy_ptr: *const u16,
+) -> c_int {
+    catch_panic(|| {
+        let access_policy = ffi_unwrap!(CString::new(ffi_read_wstring!(
+            "access policy",
+            access_policy_ptr
+        )));
+        h_access_policy_to_boolean_expression(
+            boolean_expression_ptr,
+            boolean_expression_len,
+            access_policy.as_ptr(),
+        )
+    })
+}
+
+/// UTF-16 variant of `h_validate_boolean_expression`.
+///
+/// The offset and length of the error still count bytes of the UTF-8
+/// encoding of the expression.
+///
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_validate_boolean_expression_w(
+    error_ptr: *mut c_char,
+    error_len: *mut c_int,
+    boolean_expression_ptr: *const u16,
+    policy_ptr: *const c_char,
+    policy_len: c_int,
+) -> c_int {
+    catch_panic(|| {
+        let boolean_expression = ffi_unwrap!(CString::new(ffi_read_wstring!(
+            "boolean expression",
+            boolean_expression_ptr
+        )));
+        h_validate_boolean_expression(
+            error_ptr,
+            error_len,
+            boolean_expression.as_ptr(),
+            policy_ptr,
+            policy_len,
+        )
+    })
+}
+
+/// UTF-16 variant of `h_access_policy_to_combinations`.
+///
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_access_policy_to_combinations_w(
+    combinations_ptr: *mut c_char,
+    combinations_len: *mut c_int,
+    policy_ptr: *const c_char,
+    policy_len: c_int,
+    boolean_expression_ptr: *const u16,
+    follow_hierarchical_axes: c_int,
+) -> c_int {
+    catch_panic(|| {
+        let boolean_expression = ffi_unwrap!(CString::new(ffi_read_wstring!(
+            "boolean expression",
+            boolean_expression_ptr
+        )));
+        h_access_policy_to_combinations(
+            combinations_ptr,
+            combinations_len,
+            policy_ptr,
+            policy_len,
+            boolean_expression.as_ptr(),
+            follow_hierarchical_axes,
+        )
+    })
+}
+
+/// UTF-16 variant of `h_policy_add_axis`.
+///
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_policy_add_axis_w(
+    updated_policy_ptr: *mut c_char,
+    updated_policy_len: *mut c_int,
+    current_policy_ptr: *const c_char,
+    current_policy_len: c_int,
+    axis_ptr: *const u16,
+) -> c_int {
+    catch_panic(|| {
+        let axis = ffi_unwrap!(CString::new(ffi_read_wstring!("axis", axis_ptr)));
+        h_policy_add_axis(
+            updated_policy_ptr,
+            updated_policy_len,
+            current_policy_ptr,
+            current_policy_len,
+            axis.as_ptr(),
+        )
+    })
+}
+
+/// UTF-16 variant of `h_policy_rotate_attribute`.
+///
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_policy_rotate_attribute_w(
+    updated_policy_ptr: *mut c_char,
+    updated_policy_len: *mut c_int,
+    current_policy_ptr: *const c_char,
+    current_policy_len: c_int,
+    attribute_ptr: *const u16,
+) -> c_int {
+    catch_panic(|| {
+        let attribute = ffi_unwrap!(CString::new(ffi_read_wstring!("attribute", attribute_ptr)));
+        h_policy_rotate_attribute(
+            updated_policy_ptr,
+            updated_policy_len,
+            current_policy_ptr,
+            current_policy_len,
+            attribute.as_ptr(),
+        )
+    })
+}
+
+/// UTF-16 variant of `h_policy_attribute_values`.
+///
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_policy_attribute_values_w(
+    values_ptr: *mut c_char,
+    values_len: *mut c_int,
+    policy_ptr: *const c_char,
+    policy_len: c_int,
+    attribute_ptr: *const u16,
+) -> c_int {
+    catch_panic(|| {
+        let attribute = ffi_unwrap!(CString::new(ffi_read_wstring!("attribute", attribute_ptr)));
+        h_policy_attribute_values(
+            values_ptr,
+            values_len,
+            policy_ptr,
+            policy_len,
+            attribute.as_ptr(),
+        )
+    })
+}
+
+/// UTF-16 variant of `h_policy_attributes`.
+///
+/// `axis_ptr` may be null to list all axes.
+///
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_policy_attributes_w(
+    attributes_ptr: *mut c_char,
+    attributes_len: *mut c_int,
+    policy_ptr: *const c_char,
+    policy_len: c_int,
+    axis_ptr: *const u16,
+) -> c_int {
+    catch_panic(|| {
+        let axis = if axis_ptr.is_null() {
+            None
+        } else {
+            Some(ffi_unwrap!(CString::new(ffi_read_wstring!(
+                "axis", axis_ptr
+            ))))
+        };
+        h_policy_attributes(
+            attributes_ptr,
+            attributes_len,
+            policy_ptr,
+            policy_len,
+            axis.as_ref().map_or(ptr::null(), |s| s.as_ptr()),
+        )
+    })
+}
+
+/// UTF-16 variant of `h_policy_attribute_current_value`.
+///
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_policy_attribute_current_value_w(
+    value: *mut c_uint,
+    policy_ptr: *const c_char,
+    policy_len: c_int,
+    attribute_ptr: *const u16,
+) -> c_int {
+    catch_panic(|| {
+        let attribute = ffi_unwrap!(CString::new(ffi_read_wstring!("attribute", attribute_ptr)));
+        h_policy_attribute_current_value(value, policy_ptr, policy_len, attribute.as_ptr())
+    })
+}
+
+/// UTF-16 variant of `h_rotate_attributes`.
+///
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_rotate_attributes_w(
+    updated_policy_ptr: *mut c_char,
+    updated_policy_len: *mut c_int,
+    current_policy_ptr: *const c_char,
+    current_policy_len: c_int,
+    attributes_ptr: *const u16,
+) -> c_int {
+    catch_panic(|| {
+        let attributes = ffi_unwrap!(CString::new(ffi_read_wstring!(
+            "attributes",
+            attributes_ptr
+        )));
+        h_rotate_attributes(
+            updated_policy_ptr,
+            updated_policy_len,
+            current_policy_ptr,
+            current_policy_len,
+            attributes.as_ptr(),
+        )
+    })
+}
+
+/// UTF-16 variant of `h_policy_rotate_attributes`.
+///
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_policy_rotate_attributes_w(
+    updated_policy_ptr: *mut c_char,
+    updated_policy_len: *mut c_int,
+    new_values_ptr: *mut c_char,
+    new_values_len: *mut c_int,
+    current_policy_ptr: *const c_char,
+    current_policy_len: c_int,
+    attributes_ptr: *const u16,
+) -> c_int {
+    catch_panic(|| {
+        let attributes = ffi_unwrap!(CString::new(ffi_read_wstring!(
+            "attributes",
+            attributes_ptr
+        )));
+        h_policy_rotate_attributes(
+            updated_policy_ptr,
+            updated_policy_len,
+            new_values_ptr,
+            new_values_len,
+            current_policy_ptr,
+            current_policy_len,
+            attributes.as_ptr(),
+        )
+    })
+}
+
+/// UTF-16 variant of `h_policy_handle_add_axis`.
+///
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_policy_handle_add_axis_w(
+    handle: PolicyHandle,
+    axis_ptr: *const u16,
+) -> c_int {
+    catch_panic(|| {
+        let axis = ffi_unwrap!(CString::new(ffi_read_wstring!("axis", axis_ptr)));
+        h_policy_handle_add_axis(handle, axis.as_ptr())
+    })
+}
+
+/// UTF-16 variant of `h_policy_handle_rotate_attribute`.
+///
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_policy_handle_rotate_attribute_w(
+    handle: PolicyHandle,
+    attribute_ptr: *const u16,
+) -> c_int {
+    catch_panic(|| {
+        let attribute = ffi_unwrap!(CString::new(ffi_read_wstring!("attribute", attribute_ptr)));
+        h_policy_handle_rotate_attribute(handle, attribute.as_ptr())
+    })
+}
+
+/// UTF-16 variant of `h_policy_handle_attribute_current_value`.
+///
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_policy_handle_attribute_current_value_w(
+    value: *mut c_uint,
+    handle: PolicyHandle,
+    attribute_ptr: *const u16,
+) -> c_int {
+    catch_panic(|| {
+        let attribute = ffi_unwrap!(CString::new(ffi_read_wstring!("attribute", attribute_ptr)));
+        h_policy_handle_attribute_current_value(value, handle, attribute.as_ptr())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AccessPolicy;
+
+    fn wide(s: &str) -> Vec<u16> {
+        s.encode_utf16().chain([0]).collect()
+    }
+
+    #[test]
+    fn test_wide_strings() {
+        let expression = "Département::Ressources Humaines && Niveau::Élevé";
+        let mut access_policy = vec![0_u8; 8192];
+        let mut access_policy_len = access_policy.len() as c_int;
+        let res = unsafe {
+            h_parse_boolean_access_policy_w(
+                access_policy.as_mut_ptr().cast(),
+                &mut access_policy_len,
+                wide(expression).as_ptr(),
+            )
+        };
+        assert_eq!(0, res);
+        assert_eq!(
+            AccessPolicy::from_boolean_expression(expression).unwrap(),
+            serde_json::from_slice(&access_policy[..access_policy_len as usize]).unwrap()
+        );
+
+        // A lone surrogate is not valid UTF-16.
+        let mut access_policy_len = access_policy.len() as c_int;
+        let res = unsafe {
+            h_parse_boolean_access_policy_w(
+                access_policy.as_mut_ptr().cast(),
+                &mut access_policy_len,
+                [0xD800, 0].as_ptr(),
+            )
+        };
+        assert_eq!(1, res);
+    }
+}
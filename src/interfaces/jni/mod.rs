@@ -0,0 +1,314 @@
+//! JNI bindings, exposed as the static native methods of the Java class
+//! `com.cosmian.abe.AbePolicy`:
+//!
+//! ```text
+//! package com.cosmian.abe;
+//!
+//! public final class AbePolicy {
+//!     static { System.loadLibrary("abe_policy"); }
+//!
+//!     public static native byte[] newPolicy(int maxAttributeCreations);
+//!     public static native byte[] addAxis(byte[] policy, String axis);
+//!     public static native byte[] addAttribute(byte[] policy, String attribute, boolean isHybridized);
+//!     public static native byte[] rotateAttribute(byte[] policy, String attribute);
+//!     public static native int attributeCurrentValue(byte[] policy, String attribute);
+//!     public static native String parseBooleanAccessPolicy(String booleanExpression);
+//!     public static native String parseAccessPolicy(byte[] policy, String booleanExpression);
+//! }
+//! ```
+//!
+//! Policies are passed as JSON bytes and axes as JSON strings, as with the C
+//! interface. Errors are thrown as Java exceptions, see `exception_class`.
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use ::jni::{
+    objects::{JByteArray, JClass, JString},
+    sys::{jboolean, jbyteArray, jint, jstring, JNI_FALSE},
+    JNIEnv,
+};
+
+use crate::{
+    interfaces::cache::{parse_policy, parse_policy_mut},
+    AccessPolicy, Attribute, EncryptionHint, Error, Policy,
+};
+
+/// Error raised by a native method: either a policy error or a failure of the
+/// JNI calls converting the arguments and the result.
+enum JniError {
+    Policy(Error),
+    Jni(::jni::errors::Error),
+}
+
+impl From<Error> for JniError {
+    fn from(e: Error) -> Self {
+        Self::Policy(e)
+    }
+}
+
+impl From<::jni::errors::Error> for JniError {
+    fn from(e: ::jni::errors::Error) -> Self {
+        Self::Jni(e)
+    }
+}
+
+/// Returns the Java exception class thrown for the given error.
+fn exception_class(e: &Error) -> &'static str {
+    match e {
+        Error::AttributeNotFound(_) | Error::UnknownEpoch(..) | Error::PolicyNotFound(_) => {
+            "java/util/NoSuchElementException"
+        }
+        Error::CapacityOverflow
+        | Error::ExistingPolicy(_)
+        | Error::VersionMismatch { .. }
+        | Error::MergeConflict(_) => "java/lang/IllegalStateException",
+        Error::StorageError(_) | Error::IoError(_) => "java/lang/RuntimeException",
+        Error::MissingAttribute { .. }
+        | Error::MissingAxis
+        | Error::UnsupportedOperator(_)
+        | Error::InvalidBooleanExpression(_)
+        | Error::InvalidAttribute(_)
+        | Error::InvalidName(_)
+        | Error::InvalidAxis(_)
+        | Error::InvalidBinaryPolicy(_)
+        | Error::DeserializationError(_)
+        | Error::ValueDeserializationError(_) => "java/lang/IllegalArgumentException",
+    }
+}
+
+/// Calls `f`, throwing a Java exception and returning `default` if it fails
+/// or panics: unwinding across the JNI boundary is undefined behavior.
+fn run<'local, T>(
+    env: &mut JNIEnv<'local>,
+    default: T,
+    f: impl FnOnce(&mut JNIEnv<'local>) -> Result<T, JniError>,
+) -> T {
+    let (class, message) = match catch_unwind(AssertUnwindSafe(|| f(env))) {
+        Ok(Ok(res)) => return res,
+        Ok(Err(JniError::Policy(e))) => (exception_class(&e), e.to_string()),
+        // A failing JNI call may already have thrown an exception.
+        Ok(Err(JniError::Jni(_))) if env.exception_check().unwrap_or(true) => return default,
+        Ok(Err(JniError::Jni(e))) => ("java/lang/RuntimeException", e.to_string()),
+        Err(payload) => {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(ToString::to_string)
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_owned());
+            ("java/lang/RuntimeException", format!("panic: {message}"))
+        }
+    };
+    // Nothing more can be done if throwing fails.
+    let _ = env.throw_new(class, message);
+    default
+}
+
+fn new_policy(max_attribute_creations: jint) -> Result<Vec<u8>, Error> {
+    let max_attribute_creations = u32::try_from(max_attribute_creations).map_err(|_| {
+        Error::InvalidAttribute(format!(
+            "invalid maximum number of attribute creations: {max_attribute_creations}"
+        ))
+    })?;
+    serde_json::to_vec(&Policy::new(max_attribute_creations)).map_err(Error::DeserializationError)
+}
+
+fn add_axis(policy: &[u8], axis: &str) -> Result<Vec<u8>, Error> {
+    let (mut policy, _) = parse_policy_mut(policy)?;
+    policy.add_axis(serde_json::from_str(axis).map_err(Error::DeserializationError)?)?;
+    serde_json::to_vec(&policy).map_err(Error::DeserializationError)
+}
+
+fn add_attribute(policy: &[u8], attribute: &str, is_hybridized: bool) -> Result<Vec<u8>, Error> {
+    let (mut policy, _) = parse_policy_mut(policy)?;
+    let encryption_hint = if is_hybridized {
+        EncryptionHint::Hybridized
+    } else {
+        EncryptionHint::Classic
+    };
+    policy.add_attribute(&attribute.parse::<Attribute>()?, encryption_hint)?;
+    serde_json::to_vec(&policy).map_err(Error::DeserializationError)
+}
+
+fn rotate_attribute(policy: &[u8], attribute: &str) -> Result<Vec<u8>, Error> {
+    let (mut policy, _) = parse_policy_mut(policy)?;
+    policy.rotate(&attribute.parse::<Attribute>()?)?;
+    serde_json::to_vec(&policy).map_err(Error::DeserializationError)
+}
+
+fn attribute_current_value(policy: &[u8], attribute: &str) -> Result<jint, Error> {
+    let (policy, _) = parse_policy(policy)?;
+    let value = policy.attribute_current_value(&attribute.parse::<Attribute>()?)?;
+    // Java has no unsigned integers: values above `i32::MAX` are negative.
+    Ok(value as jint)
+}
+
+fn parse_boolean_access_policy(boolean_expression: &str) -> Result<String, Error> {
+    let access_policy = AccessPolicy::from_boolean_expression(boolean_expression)?;
+    serde_json::to_string(&access_policy).map_err(Error::DeserializationError)
+}
+
+fn parse_access_policy(policy: &[u8], boolean_expression: &str) -> Result<String, Error> {
+    let access_policy = parse_policy(policy)?
+        .0
+        .parse_access_policy(boolean_expression)?;
+    serde_json::to_string(&access_policy).map_err(Error::DeserializationError)
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_cosmian_abe_AbePolicy_newPolicy<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    max_attribute_creations: jint,
+) -> jbyteArray {
+    run(&mut env, std::ptr::null_mut(), |env| {
+        let policy = new_policy(max_attribute_creations)?;
+        Ok(env.byte_array_from_slice(&policy)?.into_raw())
+    })
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_cosmian_abe_AbePolicy_addAxis<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    policy: JByteArray<'local>,
+    axis: JString<'local>,
+) -> jbyteArray {
+    run(&mut env, std::ptr::null_mut(), |env| {
+        let policy = env.convert_byte_array(&policy)?;
+        let axis = String::from(env.get_string(&axis)?);
+        let policy = add_axis(&policy, &axis)?;
+        Ok(env.byte_array_from_slice(&policy)?.into_raw())
+    })
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_cosmian_abe_AbePolicy_addAttribute<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    policy: JByteArray<'local>,
+    attribute: JString<'local>,
+    is_hybridized: jboolean,
+) -> jbyteArray {
+    run(&mut env, std::ptr::null_mut(), |env| {
+        let policy = env.convert_byte_array(&policy)?;
+        let attribute = String::from(env.get_string(&attribute)?);
+        let policy = add_attribute(&policy, &attribute, is_hybridized != JNI_FALSE)?;
+        Ok(env.byte_array_from_slice(&policy)?.into_raw())
+    })
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_cosmian_abe_AbePolicy_rotateAttribute<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    policy: JByteArray<'local>,
+    attribute: JString<'local>,
+) -> jbyteArray {
+    run(&mut env, std::ptr::null_mut(), |env| {
+        let policy = env.convert_byte_array(&policy)?;
+        let attribute = String::from(env.get_string(&attribute)?);
+        let policy = rotate_attribute(&policy, &attribute)?;
+        Ok(env.byte_array_from_slice(&policy)?.into_raw())
+    })
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_cosmian_abe_AbePolicy_attributeCurrentValue<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    policy: JByteArray<'local>,
+    attribute: JString<'local>,
+) -> jint {
+    run(&mut env, 0, |env| {
+        let policy = env.convert_byte_array(&policy)?;
+        let attribute = String::from(env.get_string(&attribute)?);
+        Ok(attribute_current_value(&policy, &attribute)?)
+    })
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_cosmian_abe_AbePolicy_parseBooleanAccessPolicy<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    boolean_expression: JString<'local>,
+) -> jstring {
+    run(&mut env, std::ptr::null_mut(), |env| {
+        let boolean_expression = String::from(env.get_string(&boolean_expression)?);
+        let access_policy = parse_boolean_access_policy(&boolean_expression)?;
+        Ok(env.new_string(access_policy)?.into_raw())
+    })
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_cosmian_abe_AbePolicy_parseAccessPolicy<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    policy: JByteArray<'local>,
+    boolean_expression: JString<'local>,
+) -> jstring {
+    run(&mut env, std::ptr::null_mut(), |env| {
+        let policy = env.convert_byte_array(&policy)?;
+        let boolean_expression = String::from(env.get_string(&boolean_expression)?);
+        let access_policy = parse_access_policy(&policy, &boolean_expression)?;
+        Ok(env.new_string(access_policy)?.into_raw())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{tests::policy, PolicyAxis};
+
+    #[test]
+    fn test_policy_management() {
+        let policy = new_policy(100).unwrap();
+        let axis = serde_json::to_string(&PolicyAxis::new(
+            "Department",
+            vec![("HR", EncryptionHint::Classic)],
+            false,
+        ))
+        .unwrap();
+        let policy = add_axis(&policy, &axis).unwrap();
+        let policy = add_attribute(&policy, "Department::FIN", true).unwrap();
+        assert_eq!(
+            2,
+            attribute_current_value(&policy, "Department::FIN").unwrap()
+        );
+        let policy = rotate_attribute(&policy, "Department::FIN").unwrap();
+        assert_eq!(
+            3,
+            attribute_current_value(&policy, "Department::FIN").unwrap()
+        );
+
+        assert_eq!(
+            r#"{"Attr":"Department::HR"}"#,
+            parse_access_policy(&policy, "Department::HR").unwrap()
+        );
+        assert!(new_policy(-1).is_err());
+    }
+
+    #[test]
+    fn test_exception_mapping() {
+        let policy = serde_json::to_vec(&policy().unwrap()).unwrap();
+        for (res, class) in [
+            (
+                rotate_attribute(&policy, "Department::Sales").map(|_| ()),
+                "java/util/NoSuchElementException",
+            ),
+            (
+                parse_boolean_access_policy("Department::HR &&").map(|_| ()),
+                "java/lang/IllegalArgumentException",
+            ),
+            (
+                add_axis(b"{", "{}").map(|_| ()),
+                "java/lang/IllegalArgumentException",
+            ),
+        ] {
+            assert_eq!(class, exception_class(&res.unwrap_err()));
+        }
+        assert_eq!(
+            "java/lang/IllegalStateException",
+            exception_class(&Error::CapacityOverflow)
+        );
+    }
+}
@@ -3,3 +3,20 @@ pub mod wasm_bindgen;
 
 #[cfg(feature = "ffi")]
 pub mod ffi;
+
+#[cfg(feature = "jni")]
+pub mod jni;
+
+#[cfg(feature = "python")]
+pub mod python;
+
+#[cfg(feature = "node")]
+pub mod node;
+
+#[cfg(any(feature = "ffi", feature = "jni", feature = "node"))]
+mod cache;
+
+#[cfg(any(feature = "ffi", feature = "wasm_bindgen"))]
+mod upgrade;
+#[cfg(any(feature = "ffi", feature = "wasm_bindgen"))]
+pub(crate) use upgrade::UpgradeReport;
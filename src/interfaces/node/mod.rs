@@ -0,0 +1,216 @@
+//! Node.js native bindings, built with napi-rs.
+//!
+//! Policies are passed as `Buffer`s holding their JSON serialization and axes
+//! as JSON strings, as with the WebAssembly interface. Operations whose cost
+//! grows with the policy size run on the libuv thread pool and return a
+//! `Promise`.
+
+use napi::{
+    bindgen_prelude::{AsyncTask, Buffer},
+    Env, Task,
+};
+use napi_derive::napi;
+
+use crate::{
+    interfaces::cache::{parse_policy, parse_policy_mut},
+    AccessPolicy, Attribute, EncryptionHint, Error, Policy,
+};
+
+fn add_axis(policy: &[u8], axis: &str) -> Result<Vec<u8>, Error> {
+    let (mut policy, _) = parse_policy_mut(policy)?;
+    policy.add_axis(serde_json::from_str(axis).map_err(Error::DeserializationError)?)?;
+    serde_json::to_vec(&policy).map_err(Error::DeserializationError)
+}
+
+fn add_attribute(policy: &[u8], attribute: &str, is_hybridized: bool) -> Result<Vec<u8>, Error> {
+    let (mut policy, _) = parse_policy_mut(policy)?;
+    let encryption_hint = if is_hybridized {
+        EncryptionHint::Hybridized
+    } else {
+        EncryptionHint::Classic
+    };
+    policy.add_attribute(&attribute.parse::<Attribute>()?, encryption_hint)?;
+    serde_json::to_vec(&policy).map_err(Error::DeserializationError)
+}
+
+fn rotate_attributes(policy: &[u8], attributes: &[String]) -> Result<Vec<u8>, Error> {
+    let (mut policy, _) = parse_policy_mut(policy)?;
+    for attribute in attributes {
+        policy.rotate(&attribute.parse::<Attribute>()?)?;
+    }
+    serde_json::to_vec(&policy).map_err(Error::DeserializationError)
+}
+
+fn combinations(
+    policy: &[u8],
+    boolean_expression: &str,
+    follow_hierarchical_axes: bool,
+) -> Result<String, Error> {
+    let (policy, _) = parse_policy(policy)?;
+    let combinations = policy
+        .parse_access_policy(boolean_expression)?
+        .to_attribute_combinations(&policy, follow_hierarchical_axes)?;
+    serde_json::to_string(&combinations).map_err(Error::DeserializationError)
+}
+
+/// Creates an empty policy.
+#[napi]
+pub fn policy_new(max_attribute_creations: u32) -> napi::Result<Buffer> {
+    let policy = serde_json::to_vec(&Policy::new(max_attribute_creations))
+        .map_err(Error::DeserializationError)?;
+    Ok(policy.into())
+}
+
+/// Adds an axis, given as JSON, to a policy.
+#[napi]
+pub fn policy_add_axis(policy: Buffer, axis: String) -> napi::Result<Buffer> {
+    Ok(add_axis(&policy, &axis)?.into())
+}
+
+/// Adds an attribute, written `Axis::Name`, to an existing axis of a policy.
+#[napi]
+pub fn policy_add_attribute(
+    policy: Buffer,
+    attribute: String,
+    is_hybridized: bool,
+) -> napi::Result<Buffer> {
+    Ok(add_attribute(&policy, &attribute, is_hybridized)?.into())
+}
+
+/// Returns the current value of an attribute.
+#[napi]
+pub fn policy_attribute_current_value(policy: Buffer, attribute: String) -> napi::Result<u32> {
+    let (policy, _) = parse_policy(&policy)?;
+    Ok(policy.attribute_current_value(&attribute.parse::<Attribute>()?)?)
+}
+
+/// Converts a boolean expression into a JSON access policy.
+#[napi]
+pub fn parse_boolean_access_policy(boolean_expression: String) -> napi::Result<String> {
+    let access_policy = AccessPolicy::from_boolean_expression(&boolean_expression)?;
+    Ok(serde_json::to_string(&access_policy).map_err(Error::DeserializationError)?)
+}
+
+/// Task run by `policy_rotate_attributes`.
+pub struct RotateAttributes {
+    policy: Vec<u8>,
+    attributes: Vec<String>,
+}
+
+impl Task for RotateAttributes {
+    type Output = Vec<u8>;
+    type JsValue = Buffer;
+
+    fn compute(&mut self) -> napi::Result<Self::Output> {
+        Ok(rotate_attributes(&self.policy, &self.attributes)?)
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> napi::Result<Self::JsValue> {
+        Ok(output.into())
+    }
+}
+
+/// Rotates attributes, written `Axis::Name`, resolving to the updated policy.
+#[napi]
+pub fn policy_rotate_attributes(
+    policy: Buffer,
+    attributes: Vec<String>,
+) -> AsyncTask<RotateAttributes> {
+    AsyncTask::new(RotateAttributes {
+        policy: policy.to_vec(),
+        attributes,
+    })
+}
+
+/// Task run by `access_policy_to_combinations`.
+pub struct AccessPolicyToCombinations {
+    policy: Vec<u8>,
+    boolean_expression: String,
+    follow_hierarchical_axes: bool,
+}
+
+impl Task for AccessPolicyToCombinations {
+    type Output = String;
+    type JsValue = String;
+
+    fn compute(&mut self) -> napi::Result<Self::Output> {
+        Ok(combinations(
+            &self.policy,
+            &self.boolean_expression,
+            self.follow_hierarchical_axes,
+        )?)
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> napi::Result<Self::JsValue> {
+        Ok(output)
+    }
+}
+
+/// Resolves to the attribute combinations granting access under a boolean
+/// expression, as a JSON array of arrays of attributes.
+#[napi]
+pub fn access_policy_to_combinations(
+    policy: Buffer,
+    boolean_expression: String,
+    follow_hierarchical_axes: bool,
+) -> AsyncTask<AccessPolicyToCombinations> {
+    AsyncTask::new(AccessPolicyToCombinations {
+        policy: policy.to_vec(),
+        boolean_expression,
+        follow_hierarchical_axes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{tests::policy, PolicyAxis};
+    use napi::Status;
+
+    #[test]
+    fn test_policy_management() {
+        let policy = serde_json::to_vec(&Policy::new(100)).unwrap();
+        let axis = serde_json::to_string(&PolicyAxis::new(
+            "Department",
+            vec![("HR", EncryptionHint::Classic)],
+            false,
+        ))
+        .unwrap();
+        let policy = add_axis(&policy, &axis).unwrap();
+        let policy = add_attribute(&policy, "Department::FIN", true).unwrap();
+        let policy = rotate_attributes(
+            &policy,
+            &["Department::FIN".to_owned(), "Department::HR".to_owned()],
+        )
+        .unwrap();
+        let policy = Policy::parse_and_convert(&policy).unwrap();
+        assert_eq!(
+            vec![3, 2],
+            policy
+                .attribute_values(&Attribute::new("Department", "FIN"))
+                .unwrap()
+        );
+        assert_eq!(
+            4,
+            policy
+                .attribute_current_value(&Attribute::new("Department", "HR"))
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_access_policy_to_combinations() {
+        let policy = serde_json::to_vec(&policy().unwrap()).unwrap();
+        assert_eq!(
+            r#"[["Department::HR","Security Level::Confidential"],["Department::HR","Security Level::Protected"]]"#,
+            combinations(
+                &policy,
+                "Department::HR && Security Level::Confidential",
+                true
+            )
+            .unwrap()
+        );
+        let err = napi::Error::from(combinations(&policy, "Department::Sales", true).unwrap_err());
+        assert_eq!(Status::InvalidArg, err.status);
+    }
+}
@@ -0,0 +1,267 @@
+//! Python bindings, built as the `abe_policy` native module:
+//!
+//! ```text
+//! from abe_policy import AccessPolicy, Attribute, Policy, PolicyAxis
+//!
+//! policy = Policy(100)
+//! policy.add_axis(PolicyAxis("Department", [("HR", False), ("FIN", True)], False))
+//! policy.rotate(Attribute("Department", "FIN"))
+//! access_policy = AccessPolicy(Attribute("Department", "HR")) | policy.parse_access_policy("Department::FIN")
+//! ```
+//!
+//! Errors are raised as Python exceptions: `KeyError` for unknown attributes,
+//! `RuntimeError` when the policy state prevents the operation and
+//! `ValueError` for invalid inputs.
+
+use pyo3::prelude::*;
+
+use crate::{AccessPolicy, Attribute, EncryptionHint, Error, Policy, PolicyAxis};
+
+/// Policy attribute, written `Axis::Name`.
+#[pyclass(
+    name = "Attribute",
+    module = "abe_policy",
+    frozen,
+    eq,
+    hash,
+    skip_from_py_object
+)]
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct PyAttribute(Attribute);
+
+#[pymethods]
+impl PyAttribute {
+    #[new]
+    fn new(axis: &str, name: &str) -> PyResult<Self> {
+        Ok(Self(Attribute::try_new(axis, name)?))
+    }
+
+    /// Parses an attribute written `Axis::Name`.
+    #[staticmethod]
+    fn from_string(attribute: &str) -> PyResult<Self> {
+        Ok(Self(attribute.parse()?))
+    }
+
+    #[getter]
+    fn axis(&self) -> &str {
+        &self.0.axis
+    }
+
+    #[getter]
+    fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    fn __str__(&self) -> String {
+        self.0.to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Attribute({:?}, {:?})", &*self.0.axis, &*self.0.name)
+    }
+}
+
+/// Policy axis, given its attribute names and hybridized encryption hints.
+#[pyclass(name = "PolicyAxis", module = "abe_policy", frozen)]
+pub struct PyPolicyAxis(PolicyAxis);
+
+#[pymethods]
+impl PyPolicyAxis {
+    /// Attributes are `(name, is_hybridized)` pairs, from the lowest to the
+    /// highest for a hierarchical axis.
+    #[new]
+    fn new(name: &str, attributes: Vec<(String, bool)>, is_hierarchical: bool) -> Self {
+        Self(PolicyAxis::new(
+            name,
+            attributes
+                .iter()
+                .map(|(name, is_hybridized)| (name.as_str(), encryption_hint(*is_hybridized)))
+                .collect(),
+            is_hierarchical,
+        ))
+    }
+
+    #[getter]
+    fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    #[getter]
+    fn is_hierarchical(&self) -> bool {
+        self.0.hierarchical
+    }
+
+    fn __len__(&self) -> usize {
+        self.0.len()
+    }
+}
+
+/// Access policy, combined with the `&` and `|` operators.
+#[pyclass(
+    name = "AccessPolicy",
+    module = "abe_policy",
+    frozen,
+    skip_from_py_object
+)]
+#[derive(Clone)]
+pub struct PyAccessPolicy(AccessPolicy);
+
+#[pymethods]
+impl PyAccessPolicy {
+    #[new]
+    fn new(attribute: &PyAttribute) -> Self {
+        Self(AccessPolicy::Attr(attribute.0.clone()))
+    }
+
+    /// Parses a boolean expression such as `Department::HR && Level::Low`.
+    #[staticmethod]
+    fn from_boolean_expression(boolean_expression: &str) -> PyResult<Self> {
+        Ok(Self(AccessPolicy::from_boolean_expression(
+            boolean_expression,
+        )?))
+    }
+
+    fn __and__(&self, other: &Self) -> Self {
+        Self(self.0.clone() & other.0.clone())
+    }
+
+    fn __or__(&self, other: &Self) -> Self {
+        Self(self.0.clone() | other.0.clone())
+    }
+
+    fn attributes(&self) -> Vec<PyAttribute> {
+        self.0.attributes().into_iter().map(PyAttribute).collect()
+    }
+
+    fn to_json(&self) -> PyResult<String> {
+        Ok(serde_json::to_string(&self.0).map_err(Error::DeserializationError)?)
+    }
+
+    fn __str__(&self) -> String {
+        self.0.to_string()
+    }
+}
+
+/// Policy defining the axes and attributes used for encryption.
+#[pyclass(name = "Policy", module = "abe_policy")]
+pub struct PyPolicy(Policy);
+
+#[pymethods]
+impl PyPolicy {
+    #[new]
+    fn new(max_attribute_creations: u32) -> Self {
+        Self(Policy::new(max_attribute_creations))
+    }
+
+    /// Parses a JSON policy, converting it from a legacy format if needed.
+    #[staticmethod]
+    fn from_json(json: &str) -> PyResult<Self> {
+        Ok(Self(Policy::parse_and_convert(json.as_bytes())?))
+    }
+
+    fn to_json(&self) -> PyResult<String> {
+        Ok(serde_json::to_string(&self.0).map_err(Error::DeserializationError)?)
+    }
+
+    fn add_axis(&mut self, axis: &PyPolicyAxis) -> PyResult<()> {
+        Ok(self.0.add_axis(axis.0.clone())?)
+    }
+
+    #[pyo3(signature = (attribute, is_hybridized = false))]
+    fn add_attribute(&mut self, attribute: &PyAttribute, is_hybridized: bool) -> PyResult<()> {
+        Ok(self
+            .0
+            .add_attribute(&attribute.0, encryption_hint(is_hybridized))?)
+    }
+
+    fn rotate(&mut self, attribute: &PyAttribute) -> PyResult<()> {
+        Ok(self.0.rotate(&attribute.0)?)
+    }
+
+    fn attribute_current_value(&self, attribute: &PyAttribute) -> PyResult<u32> {
+        Ok(self.0.attribute_current_value(&attribute.0)?)
+    }
+
+    /// Returns the values of an attribute, the current one first.
+    fn attribute_values(&self, attribute: &PyAttribute) -> PyResult<Vec<u32>> {
+        Ok(self.0.attribute_values(&attribute.0)?)
+    }
+
+    fn attributes(&self) -> Vec<PyAttribute> {
+        self.0.attributes().into_iter().map(PyAttribute).collect()
+    }
+
+    fn remaining_attribute_creations(&self) -> u32 {
+        self.0.remaining_attribute_creations()
+    }
+
+    /// Parses a boolean expression, checking its attributes against the
+    /// policy.
+    fn parse_access_policy(&self, boolean_expression: &str) -> PyResult<PyAccessPolicy> {
+        Ok(PyAccessPolicy(
+            self.0.parse_access_policy(boolean_expression)?,
+        ))
+    }
+
+    fn __str__(&self) -> String {
+        self.0.to_string()
+    }
+}
+
+fn encryption_hint(is_hybridized: bool) -> EncryptionHint {
+    if is_hybridized {
+        EncryptionHint::Hybridized
+    } else {
+        EncryptionHint::Classic
+    }
+}
+
+#[pymodule]
+fn abe_policy(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyAttribute>()?;
+    m.add_class::<PyPolicyAxis>()?;
+    m.add_class::<PyAccessPolicy>()?;
+    m.add_class::<PyPolicy>()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_python_module() {
+        Python::initialize();
+        Python::attach(|py| {
+            let module = PyModule::new(py, "abe_policy").unwrap();
+            abe_policy(&module).unwrap();
+            let globals = module.dict();
+            py.run(
+                cr#"
+policy = Policy(100)
+policy.add_axis(PolicyAxis("Department", [("HR", False), ("FIN", True)], False))
+policy.rotate(Attribute("Department", "FIN"))
+assert policy.attribute_values(Attribute.from_string("Department::FIN")) == [3, 2]
+
+access_policy = AccessPolicy(Attribute("Department", "HR")) | policy.parse_access_policy("Department::FIN")
+assert str(access_policy) == "Department::HR || Department::FIN"
+assert str(access_policy & AccessPolicy.from_boolean_expression("Level::Low")) == "(Department::HR || Department::FIN) && Level::Low"
+
+try:
+    policy.rotate(Attribute("Department", "MKG"))
+    assert False
+except KeyError:
+    pass
+try:
+    Policy.from_json("{")
+    assert False
+except ValueError:
+    pass
+"#,
+                Some(&globals),
+                None,
+            )
+            .unwrap();
+        });
+    }
+}
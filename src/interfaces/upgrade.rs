@@ -0,0 +1,41 @@
+use serde::Serialize;
+
+use crate::Policy;
+
+/// Report of the conversion of a policy from the legacy format.
+#[derive(Serialize)]
+pub(crate) struct UpgradeReport {
+    /// `true` if the given policy used the legacy format
+    legacy: bool,
+    /// Number of axes of the policy
+    axes: usize,
+    /// Number of attributes of the policy
+    attributes: usize,
+    /// Descriptions of the changes made by the conversion
+    changes: Vec<String>,
+    /// Warnings about the upgraded policy
+    lints: Vec<String>,
+}
+
+impl UpgradeReport {
+    pub(crate) fn new(policy: &Policy, is_legacy: bool) -> Self {
+        let changes = if is_legacy {
+            vec![
+                "format version set to V1".to_owned(),
+                format!(
+                    "encryption hint of the {} attributes set to Classic",
+                    policy.attributes.len()
+                ),
+            ]
+        } else {
+            Vec::new()
+        };
+        Self {
+            legacy: is_legacy,
+            axes: policy.axes.len(),
+            attributes: policy.attributes.len(),
+            changes,
+            lints: policy.lint().iter().map(ToString::to_string).collect(),
+        }
+    }
+}
@@ -1,96 +1,793 @@
-use crate::{AccessPolicy, Attribute, EncryptionHint, Error, Policy, PolicyAxis};
-use js_sys::{Array, Boolean, JsString, Reflect};
+//! WebAssembly interface.
+//!
+//! Policies, axes and access policies are exchanged as plain JavaScript
+//! objects, whose TypeScript types are declared below.
+//!
+//! `serde_json` is not needed: the functions using the binary format are
+//! only built with the `json` feature, and the JSON Schema of the policies
+//! with the `schema` one.
+
+use crate::{
+    interfaces::UpgradeReport,
+    policy::{PolicyAxesParameters, UncheckedPolicy},
+    AccessPolicy, Attribute, AxisName, EncryptionHint, Error, ExpressionError, IndexMap,
+    LegacyPolicy, Policy, PolicyAxis,
+};
+use js_sys::{Array, JsString};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_wasm_bindgen::Serializer;
+use std::collections::BTreeMap;
 use wasm_bindgen::{prelude::wasm_bindgen, JsValue};
 
+/// TypeScript declarations of the values exchanged with JavaScript.
+#[wasm_bindgen(typescript_custom_section)]
+const TS_TYPES: &str = r#"
+/** Attribute, written `Axis::Name`. */
+export type Attribute = string;
+
+export type EncryptionHint = "Classic" | "Hybridized";
+
+export type AccessPolicy =
+    | { Attr: Attribute }
+    | { And: [AccessPolicy, AccessPolicy] }
+    | { Or: [AccessPolicy, AccessPolicy] }
+    | "All";
+
+export interface PolicyAxis {
+    name: string;
+    attributes_properties: Array<{ name: string; encryption_hint: EncryptionHint }>;
+    hierarchical: boolean;
+}
+
+export interface Policy {
+    version: "V1";
+    last_attribute_value: number;
+    max_attribute_creations: number;
+    axes: Record<string, { attribute_names: Array<string>; is_hierarchical: boolean }>;
+    attributes: Array<{
+        axis: string;
+        name: string;
+        values: Array<number>;
+        encryption_hint: EncryptionHint;
+        retired_epochs?: number;
+    }>;
+    naming_policy?: unknown;
+    reclaimed_values?: Array<number>;
+    [field: string]: unknown;
+}
+
+export interface AttributeProperty {
+    name: string;
+    isHybridized: boolean;
+}
+
+export interface PolicyAttribute {
+    axis: string;
+    name: string;
+    isHybridized: boolean;
+    currentValue: number;
+}
+
+export interface Rotation {
+    policy: Policy;
+    values: Record<Attribute, number>;
+}
+
+export interface PolicyDiff {
+    added_axes: Array<string>;
+    removed_axes: Array<string>;
+    added_attributes: Array<Attribute>;
+    removed_attributes: Array<Attribute>;
+    rotated_attributes: Array<Attribute>;
+    hint_changes: Array<Attribute>;
+    max_attribute_creations?: number;
+}
+
+export interface UpgradeReport {
+    legacy: boolean;
+    axes: number;
+    attributes: number;
+    changes: Array<string>;
+    lints: Array<string>;
+}
+
+export interface PolicyUpgrade {
+    policy: Policy;
+    report: UpgradeReport;
+}
+
+export type ErrorCode =
+    | "ATTRIBUTE_NOT_FOUND"
+    | "UNKNOWN_EPOCH"
+    | "MISSING_ATTRIBUTE"
+    | "MISSING_AXIS"
+    | "UNSUPPORTED_OPERATOR"
+    | "CAPACITY_OVERFLOW"
+    | "EXISTING_POLICY"
+    | "POLICY_NOT_FOUND"
+    | "VERSION_MISMATCH"
+    | "STORAGE_ERROR"
+    | "INVALID_BOOLEAN_EXPRESSION"
+    | "INVALID_ATTRIBUTE"
+    | "INVALID_NAME"
+    | "INVALID_AXIS"
+    | "MERGE_CONFLICT"
+    | "INVALID_BINARY_POLICY"
+    | "DESERIALIZATION_ERROR"
+    | "IO_ERROR";
+
+export interface ExpressionError {
+    offset: number;
+    length: number;
+    expected: Array<string>;
+    message: string;
+}
+"#;
+
+/// Error thrown by the functions of this module, with a code identifying its
+/// kind. Invalid boolean expressions given to
+/// `webassembly_parse_boolean_access_policy` throw an `ExpressionError`
+/// instead.
+#[wasm_bindgen]
+pub struct PolicyError {
+    code: &'static str,
+    message: String,
+    detail: Option<String>,
+}
+
+#[wasm_bindgen]
+impl PolicyError {
+    /// Kind of error, e.g. `CAPACITY_OVERFLOW`
+    #[wasm_bindgen(getter, unchecked_return_type = "ErrorCode")]
+    pub fn code(&self) -> String {
+        self.code.to_owned()
+    }
+
+    /// Description of the error
+    #[wasm_bindgen(getter)]
+    pub fn message(&self) -> String {
+        self.message.clone()
+    }
+
+    /// Subject of the error, e.g. the attribute not found, if any
+    #[wasm_bindgen(getter)]
+    pub fn detail(&self) -> Option<String> {
+        self.detail.clone()
+    }
+
+    #[wasm_bindgen(js_name = toString)]
+    pub fn display(&self) -> String {
+        format!("{}: {}", self.code, self.message)
+    }
+}
+
+impl From<Error> for PolicyError {
+    fn from(e: Error) -> Self {
+        let (code, detail) = match &e {
+            Error::AttributeNotFound(detail) => ("ATTRIBUTE_NOT_FOUND", Some(detail.clone())),
+            Error::UnknownEpoch(detail, _) => ("UNKNOWN_EPOCH", Some(detail.clone())),
+            Error::MissingAttribute { item, .. } => ("MISSING_ATTRIBUTE", item.clone()),
+            Error::MissingAxis => ("MISSING_AXIS", None),
+            Error::UnsupportedOperator(detail) => ("UNSUPPORTED_OPERATOR", Some(detail.clone())),
+            Error::CapacityOverflow => ("CAPACITY_OVERFLOW", None),
+            Error::ExistingPolicy(detail) => ("EXISTING_POLICY", Some(detail.clone())),
+            Error::PolicyNotFound(detail) => ("POLICY_NOT_FOUND", Some(detail.clone())),
+            Error::VersionMismatch { .. } => ("VERSION_MISMATCH", None),
+            Error::StorageError(detail) => ("STORAGE_ERROR", Some(detail.clone())),
+            Error::InvalidBooleanExpression(detail) => {
+                ("INVALID_BOOLEAN_EXPRESSION", Some(detail.clone()))
+            }
+            Error::InvalidAttribute(detail) => ("INVALID_ATTRIBUTE", Some(detail.clone())),
+            Error::InvalidName(detail) => ("INVALID_NAME", Some(detail.clone())),
+            Error::InvalidAxis(detail) => ("INVALID_AXIS", Some(detail.clone())),
+            Error::MergeConflict(detail) => ("MERGE_CONFLICT", Some(detail.clone())),
+            Error::InvalidBinaryPolicy(detail) => ("INVALID_BINARY_POLICY", Some(detail.clone())),
+            #[cfg(feature = "json")]
+            Error::DeserializationError(e) => ("DESERIALIZATION_ERROR", Some(e.to_string())),
+            Error::ValueDeserializationError(detail) => {
+                ("DESERIALIZATION_ERROR", Some(detail.clone()))
+            }
+            Error::IoError(e) => ("IO_ERROR", Some(e.to_string())),
+        };
+        Self {
+            code,
+            message: e.to_string(),
+            detail,
+        }
+    }
+}
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "Policy")]
+    pub type JsPolicy;
+
+    #[wasm_bindgen(typescript_type = "PolicyAxis")]
+    pub type JsPolicyAxis;
+
+    #[wasm_bindgen(typescript_type = "AccessPolicy")]
+    pub type JsAccessPolicy;
+}
+
+/// Serializer writing maps as plain objects, as declared in `TS_TYPES`.
+const SERIALIZER: Serializer = Serializer::json_compatible();
+
+fn to_js<T: Serialize + ?Sized>(value: &T) -> Result<JsValue, JsValue> {
+    Ok(value.serialize(&SERIALIZER)?)
+}
+
+fn from_js<T: DeserializeOwned>(value: JsValue) -> Result<T, Error> {
+    serde_wasm_bindgen::from_value(value)
+        .map_err(|e| Error::ValueDeserializationError(e.to_string()))
+}
+
+/// Policy in the legacy format, whose axes are `[attribute_names,
+/// is_hierarchical]` arrays: `serde_wasm_bindgen` reads them as tuples but
+/// not as structures.
+#[derive(Deserialize)]
+struct JsLegacyPolicy {
+    last_attribute_value: u32,
+    max_attribute_creations: u32,
+    axes: IndexMap<AxisName, (Vec<String>, bool)>,
+    attributes: IndexMap<Attribute, Vec<u32>>,
+}
+
+impl From<JsLegacyPolicy> for LegacyPolicy {
+    fn from(policy: JsLegacyPolicy) -> Self {
+        Self {
+            last_attribute_value: policy.last_attribute_value,
+            max_attribute_creations: policy.max_attribute_creations,
+            axes: policy
+                .axes
+                .into_iter()
+                .map(|(axis, (attribute_names, is_hierarchical))| {
+                    (
+                        axis,
+                        PolicyAxesParameters {
+                            attribute_names,
+                            is_hierarchical,
+                        },
+                    )
+                })
+                .collect(),
+            attributes: policy.attributes,
+        }
+    }
+}
+
+/// Reads a policy as `read_policy_and_detect_legacy` does, without checking
+/// its structure.
+fn read_unchecked_policy_and_detect_legacy(
+    policy: JsValue,
+) -> Result<(UncheckedPolicy, bool), Error> {
+    match from_js::<UncheckedPolicy>(policy.clone()) {
+        Ok(policy) => Ok((policy, false)),
+        Err(e) => match from_js::<JsLegacyPolicy>(policy) {
+            Ok(policy) => Ok((LegacyPolicy::from(policy).into(), true)),
+            // The `Policy` error is the one that should be reported.
+            Err(_) => Err(e),
+        },
+    }
+}
+
+/// Reads a policy object, also returning `true` if it uses the legacy format,
+/// which is converted to the current one.
+fn read_policy_and_detect_legacy(policy: JsValue) -> Result<(Policy, bool), Error> {
+    let (policy, is_legacy) = read_unchecked_policy_and_detect_legacy(policy)?;
+    let policy = Policy::try_from(policy)
+        .map_err(|problem| Error::ValueDeserializationError(problem.to_string()))?;
+    Ok((policy, is_legacy))
+}
+
+fn read_policy(policy: JsPolicy) -> Result<Policy, Error> {
+    read_policy_and_detect_legacy(policy.into()).map(|(policy, _)| policy)
+}
+
+fn write_policy(policy: &Policy) -> Result<JsPolicy, JsValue> {
+    Ok(to_js(policy)?.into())
+}
+
+/// Converts a boolean expression into an access policy, checking that its
+/// attributes belong to the policy if one is given.
+///
+/// If the expression is invalid, throws an `{offset, length, expected,
+/// message}` object: `offset` and `length` locate the erroneous part of the
+/// expression, in UTF-16 code units as JavaScript string indices, and
+/// `expected` lists the tokens expected there (see `ExpressionError`).
+///
+/// - `boolean_expression`  : boolean access policy string
+/// - `policy`              : global policy, optional
 #[wasm_bindgen]
 pub fn webassembly_parse_boolean_access_policy(
     boolean_expression: &str,
-) -> Result<String, JsValue> {
-    let access_policy = AccessPolicy::from_boolean_expression(boolean_expression)
-        .map_err(|e| JsValue::from_str(&format!("Error parsing the access policy: {e}")))?;
-    serde_json::to_string(&access_policy)
-        .map_err(|e| JsValue::from_str(&format!("Error serializing the access policy: {e}")))
+    policy: Option<JsPolicy>,
+) -> Result<JsAccessPolicy, JsValue> {
+    let policy = policy.map(read_policy).transpose()?;
+    let validation = match &policy {
+        Some(policy) => policy.validate_boolean_expression(boolean_expression),
+        None => AccessPolicy::validate_boolean_expression(boolean_expression),
+    };
+    if let Err(error) = validation {
+        let utf16_len = |s: &str| s.encode_utf16().count();
+        let start = &boolean_expression[..error.offset];
+        let erroneous = &boolean_expression[error.offset..error.offset + error.length];
+        return Err(serde_wasm_bindgen::to_value(&ExpressionError {
+            offset: utf16_len(start),
+            length: utf16_len(erroneous),
+            ..error
+        })?);
+    }
+    let access_policy = match &policy {
+        Some(policy) => policy.parse_access_policy(boolean_expression),
+        None => AccessPolicy::from_boolean_expression(boolean_expression),
+    }?;
+    Ok(to_js(&access_policy)?.into())
+}
+
+/// Builds the access policy granted by a single attribute, checking that
+/// neither the axis nor the name is empty or contains the separator `::`.
+///
+/// - `axis`    : policy axis the attribute belongs to
+/// - `name`    : attribute name within this axis
+#[wasm_bindgen]
+pub fn webassembly_ap_attr(axis: &str, name: &str) -> Result<JsAccessPolicy, JsValue> {
+    Ok(to_js(&AccessPolicy::from(Attribute::try_new(axis, name)?))?.into())
+}
+
+/// Builds the access policy granting access when both `a` and `b` do.
+#[wasm_bindgen]
+pub fn webassembly_ap_and(a: JsAccessPolicy, b: JsAccessPolicy) -> Result<JsAccessPolicy, JsValue> {
+    let (a, b): (AccessPolicy, AccessPolicy) = (from_js(a.into())?, from_js(b.into())?);
+    Ok(to_js(&(a & b))?.into())
+}
+
+/// Builds the access policy granting access when `a` or `b` does.
+#[wasm_bindgen]
+pub fn webassembly_ap_or(a: JsAccessPolicy, b: JsAccessPolicy) -> Result<JsAccessPolicy, JsValue> {
+    let (a, b): (AccessPolicy, AccessPolicy) = (from_js(a.into())?, from_js(b.into())?);
+    Ok(to_js(&(a | b))?.into())
 }
 
 #[wasm_bindgen]
 extern "C" {
-    #[wasm_bindgen(typescript_type = "Array<string>")]
+    #[wasm_bindgen(typescript_type = "Array<Attribute>")]
     pub type Attributes;
 }
 
+/// Returns `true` if a user holding the given attributes is granted access by
+/// the boolean expression. On a hierarchical axis, an attribute also grants
+/// access to the lower ones.
+///
+/// - `boolean_expression`  : boolean access policy string
+/// - `attributes`          : user attributes, written `Axis::Name`
+/// - `policy`              : global policy
+#[wasm_bindgen]
+pub fn webassembly_access_policy_is_satisfied(
+    boolean_expression: &str,
+    attributes: Attributes,
+    policy: JsPolicy,
+) -> Result<bool, JsValue> {
+    let attributes: Vec<Attribute> = from_js(attributes.into())?;
+    let policy = read_policy(policy)?;
+    let access_policy = policy.parse_access_policy(boolean_expression)?;
+    Ok(access_policy.is_satisfied_by(&policy, &attributes)?)
+}
+
 #[wasm_bindgen]
 extern "C" {
-    #[wasm_bindgen(typescript_type = "{name: string, isHybridized: boolean}")]
-    pub type AttributeProperty;
+    #[wasm_bindgen(typescript_type = "Array<AttributeProperty>")]
+    pub type AttributeProperties;
+}
+
+/// Attribute name and encryption hint given to `webassembly_policy_axis`.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AttributeProperty {
+    name: String,
+    is_hybridized: bool,
 }
 
 #[wasm_bindgen]
 pub fn webassembly_policy_axis(
     name: String,
-    attribute_properties: Vec<AttributeProperty>,
+    attribute_properties: AttributeProperties,
     is_hierarchical: bool,
-) -> Result<String, JsValue> {
-    let attribute_properties = attribute_properties
-        .into_iter()
-        .map(|obj| -> Result<(String, EncryptionHint), JsValue> {
-            let name = String::from(JsString::from(Reflect::get(
-                &obj,
-                &JsValue::from_str("name"),
-            )?));
-            let encryption_hint = bool::from(Boolean::from(Reflect::get(
-                &obj,
-                &JsValue::from_str("name"),
-            )?));
-            let encryption_hint = if encryption_hint {
-                EncryptionHint::Hybridized
-            } else {
-                EncryptionHint::Classic
-            };
-            Ok((name, encryption_hint))
-        })
-        .collect::<Result<Vec<_>, _>>()?;
+) -> Result<JsPolicyAxis, JsValue> {
+    let attribute_properties: Vec<AttributeProperty> = from_js(attribute_properties.into())?;
 
-    serde_json::to_string(&PolicyAxis::new(
+    Ok(to_js(&PolicyAxis::new(
         &name,
         attribute_properties
             .iter()
-            .map(|(name, encryption_hint)| (name.as_str(), *encryption_hint))
+            .map(|property| {
+                let encryption_hint = if property.is_hybridized {
+                    EncryptionHint::Hybridized
+                } else {
+                    EncryptionHint::Classic
+                };
+                (property.name.as_str(), encryption_hint)
+            })
             .collect(),
         is_hierarchical,
-    ))
-    .map_err(|e| JsValue::from_str(&e.to_string()))
+    ))?
+    .into())
+}
+
+#[wasm_bindgen]
+pub fn webassembly_policy(nb_creations: u32) -> Result<JsPolicy, JsValue> {
+    write_policy(&Policy::new(nb_creations))
+}
+
+#[wasm_bindgen]
+pub fn webassembly_add_axis(policy: JsPolicy, axis: JsPolicyAxis) -> Result<JsPolicy, JsValue> {
+    let mut policy = read_policy(policy)?;
+    policy.add_axis(from_js(axis.into())?)?;
+    write_policy(&policy)
+}
+
+/// Adds an attribute to an existing axis of the policy. The new attribute is
+/// the highest one of a hierarchical axis.
+///
+/// - `policy`          : global policy
+/// - `axis`            : name of the axis
+/// - `name`            : name of the attribute
+/// - `is_hybridized`   : hint for hybridized encryption
+#[wasm_bindgen]
+pub fn webassembly_add_attribute(
+    policy: JsPolicy,
+    axis: &str,
+    name: &str,
+    is_hybridized: bool,
+) -> Result<JsPolicy, JsValue> {
+    let mut policy = read_policy(policy)?;
+    let encryption_hint = if is_hybridized {
+        EncryptionHint::Hybridized
+    } else {
+        EncryptionHint::Classic
+    };
+    policy.add_attribute(&Attribute::try_new(axis, name)?, encryption_hint)?;
+    write_policy(&policy)
+}
+
+/// Removes an attribute from the policy. Its values are not reused.
+///
+/// - `policy`      : global policy
+/// - `axis`        : name of the axis
+/// - `name`        : name of the attribute
+#[wasm_bindgen]
+pub fn webassembly_remove_attribute(
+    policy: JsPolicy,
+    axis: &str,
+    name: &str,
+) -> Result<JsPolicy, JsValue> {
+    let mut policy = read_policy(policy)?;
+    policy.remove_attribute(&Attribute::try_new(axis, name)?)?;
+    write_policy(&policy)
 }
 
+/// Renames an attribute of the policy, keeping its values so that existing
+/// keys and ciphertexts remain valid.
+///
+/// - `policy`      : global policy
+/// - `axis`        : name of the axis
+/// - `old_name`    : current name of the attribute
+/// - `new_name`    : new name of the attribute
 #[wasm_bindgen]
-pub fn webassembly_policy(nb_creations: u32) -> Result<Vec<u8>, JsValue> {
-    serde_json::to_vec(&Policy::new(nb_creations)).map_err(|e| JsValue::from_str(&e.to_string()))
+pub fn webassembly_rename_attribute(
+    policy: JsPolicy,
+    axis: &str,
+    old_name: &str,
+    new_name: &str,
+) -> Result<JsPolicy, JsValue> {
+    let mut policy = read_policy(policy)?;
+    let new_name = Attribute::try_new(axis, new_name)?.name;
+    policy.rename_attribute(&Attribute::try_new(axis, old_name)?, &new_name)?;
+    write_policy(&policy)
 }
 
 #[wasm_bindgen]
-pub fn webassembly_add_axis(policy: Vec<u8>, axis: String) -> Result<Vec<u8>, JsValue> {
-    let mut policy = Policy::parse_and_convert(&policy)?;
-    policy.add_axis(serde_json::from_str(&axis).map_err(Error::DeserializationError)?)?;
-    serde_json::to_vec(&policy).map_err(|e| JsValue::from_str(&e.to_string()))
+extern "C" {
+    #[wasm_bindgen(typescript_type = "Rotation")]
+    pub type Rotation;
 }
 
 /// Rotates attributes, changing their underlying values with that of an unused
-/// slot
+/// slot. Returns the updated policy with the new values of the rotated
+/// attributes.
 ///
-/// - `attributes`  : user access policy (boolean expression as string)
-/// - `policy`      : global policy data (JSON)
+/// - `attributes`  : attributes to rotate, written `Axis::Name`
+/// - `policy`      : global policy
 #[wasm_bindgen]
 pub fn webassembly_rotate_attributes(
     attributes: Attributes,
-    policy: Vec<u8>,
-) -> Result<Vec<u8>, JsValue> {
+    policy: JsPolicy,
+) -> Result<Rotation, JsValue> {
+    /// Result of `webassembly_rotate_attributes`.
+    #[derive(Serialize)]
+    struct RotationResult<'a> {
+        policy: &'a Policy,
+        values: BTreeMap<String, u32>,
+    }
+
     let attributes = Array::from(&JsValue::from(attributes));
-    let mut policy = Policy::parse_and_convert(&policy)?;
+    let mut policy = read_policy(policy)?;
 
     // Rotate attributes of the current policy
+    let mut values = BTreeMap::new();
     for attr in attributes.values() {
-        let attribute = Attribute::try_from(String::from(JsString::from(attr?)).as_str())?;
+        let attribute = String::from(JsString::from(attr?)).parse::<Attribute>()?;
         policy.rotate(&attribute)?;
+        let attribute = policy.canonical_attribute(&attribute)?;
+        values.insert(
+            attribute.to_string(),
+            policy.attribute_current_value(&attribute)?,
+        );
+    }
+
+    Ok(to_js(&RotationResult {
+        policy: &policy,
+        values,
+    })?
+    .into())
+}
+
+/// Rotates attributes, changing their underlying values with that of an unused
+/// slot
+///
+/// - `attributes`  : comma-separated list of attributes, e.g.
+///   `Department::FIN, Security Level::Top Secret`
+/// - `policy`      : global policy
+#[wasm_bindgen]
+pub fn webassembly_rotate_attribute_list(
+    attributes: &str,
+    policy: JsPolicy,
+) -> Result<JsPolicy, JsValue> {
+    let attributes = attributes.parse::<crate::Attributes>()?;
+    let mut policy = read_policy(policy)?;
+
+    for attribute in &attributes {
+        policy.rotate(attribute)?;
+    }
+
+    write_policy(&policy)
+}
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "Array<PolicyAttribute>")]
+    pub type PolicyAttributes;
+}
+
+/// Attribute description returned by `webassembly_policy_attributes`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PolicyAttribute<'a> {
+    axis: &'a str,
+    name: &'a str,
+    is_hybridized: bool,
+    current_value: u32,
+}
+
+/// Lists the attributes of a policy, sorted by axis then by rank within their
+/// axis.
+///
+/// - `policy`      : global policy
+#[wasm_bindgen]
+pub fn webassembly_policy_attributes(policy: JsPolicy) -> Result<PolicyAttributes, JsValue> {
+    let policy = read_policy(policy)?;
+    let mut attributes = policy.attributes();
+    policy.sort_attributes(&mut attributes);
+    let attributes = attributes
+        .iter()
+        .map(|attribute| {
+            Ok(PolicyAttribute {
+                axis: &attribute.axis,
+                name: &attribute.name,
+                is_hybridized: policy.attribute_hybridization_hint(attribute)?
+                    == EncryptionHint::Hybridized,
+                current_value: policy.attribute_current_value(attribute)?,
+            })
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    Ok(serde_wasm_bindgen::to_value(&attributes)?.into())
+}
+
+/// Returns the values of an attribute, the current one first.
+///
+/// - `policy`      : global policy
+/// - `attribute`   : attribute, written `Axis::Name`
+#[wasm_bindgen]
+pub fn webassembly_attribute_values(
+    policy: JsPolicy,
+    #[wasm_bindgen(unchecked_param_type = "Attribute")] attribute: &str,
+) -> Result<Vec<u32>, JsValue> {
+    let policy = read_policy(policy)?;
+    Ok(policy.attribute_values(&attribute.parse::<Attribute>()?)?)
+}
+
+/// Returns the current value of an attribute.
+///
+/// - `policy`      : global policy
+/// - `attribute`   : attribute, written `Axis::Name`
+#[wasm_bindgen]
+pub fn webassembly_attribute_current_value(
+    policy: JsPolicy,
+    #[wasm_bindgen(unchecked_param_type = "Attribute")] attribute: &str,
+) -> Result<u32, JsValue> {
+    let policy = read_policy(policy)?;
+    Ok(policy.attribute_current_value(&attribute.parse::<Attribute>()?)?)
+}
+
+/// Returns the attribute, written `Axis::Name`, which had or has the given
+/// value, e.g. a value read from a ciphertext header.
+///
+/// - `policy`      : global policy
+/// - `value`       : attribute value
+#[wasm_bindgen(unchecked_return_type = "Attribute")]
+pub fn webassembly_attribute_from_value(policy: JsPolicy, value: u32) -> Result<String, JsValue> {
+    let policy = read_policy(policy)?;
+    for attribute in policy.attributes() {
+        if policy.attribute_values(&attribute)?.contains(&value) {
+            return Ok(attribute.to_string());
+        }
     }
+    Err(Error::AttributeNotFound(format!("no attribute has the value {value}")).into())
+}
 
-    Ok(serde_json::to_vec(&policy).map_err(Error::DeserializationError)?)
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "PolicyDiff")]
+    pub type PolicyDiff;
+}
+
+/// Compares two versions of a policy, listing the axes and attributes added,
+/// removed or rotated by the new one. All lists are sorted.
+///
+/// - `old_policy`  : global policy
+/// - `new_policy`  : global policy
+#[wasm_bindgen]
+pub fn webassembly_policy_diff(
+    old_policy: JsPolicy,
+    new_policy: JsPolicy,
+) -> Result<PolicyDiff, JsValue> {
+    let old_policy = read_policy(old_policy)?;
+    let new_policy = read_policy(new_policy)?;
+    Ok(serde_wasm_bindgen::to_value(&old_policy.diff(&new_policy))?.into())
+}
+
+/// Serializes a policy in the compact binary format.
+///
+/// - `policy`      : global policy
+#[cfg(feature = "json")]
+#[wasm_bindgen]
+pub fn webassembly_policy_to_bytes(policy: JsPolicy) -> Result<Vec<u8>, JsValue> {
+    Ok(read_policy(policy)?.to_bytes()?)
+}
+
+/// Deserializes a policy written by `webassembly_policy_to_bytes`.
+///
+/// - `bytes`       : policy in the compact binary format
+#[cfg(feature = "json")]
+#[wasm_bindgen]
+pub fn webassembly_policy_from_bytes(bytes: &[u8]) -> Result<JsPolicy, JsValue> {
+    write_policy(&Policy::from_bytes(bytes)?)
+}
+
+/// Returns the fingerprint of a policy, as a lowercase hexadecimal string:
+/// equal policies have the same fingerprint.
+///
+/// - `policy`      : global policy
+#[cfg(feature = "json")]
+#[wasm_bindgen]
+pub fn webassembly_policy_fingerprint(policy: JsPolicy) -> Result<String, JsValue> {
+    let fingerprint = read_policy(policy)?.fingerprint()?;
+    Ok(fingerprint
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect())
+}
+
+/// Returns the JSON Schema of the policies.
+#[cfg(feature = "schema")]
+#[wasm_bindgen(unchecked_return_type = "object")]
+pub fn webassembly_policy_json_schema() -> Result<JsValue, JsValue> {
+    to_js(&schemars::schema_for!(Policy))
+}
+
+/// Checks the consistency of a policy, returning the descriptions of its
+/// problems. The list is empty if the policy is consistent.
+///
+/// - `policy`      : global policy
+#[wasm_bindgen]
+pub fn webassembly_validate_policy(policy: JsPolicy) -> Result<Vec<String>, JsValue> {
+    let (policy, _) = read_unchecked_policy_and_detect_legacy(policy.into())?;
+    Ok(policy
+        .check_consistency()
+        .iter()
+        .map(ToString::to_string)
+        .collect())
+}
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "PolicyUpgrade")]
+    pub type PolicyUpgrade;
+}
+
+/// Converts a policy in the legacy format to the current format, returning
+/// the converted policy with an upgrade report. A policy already in the
+/// current format is returned unchanged.
+///
+/// - `legacy_policy`   : global policy, in the legacy or current format
+#[wasm_bindgen]
+pub fn webassembly_upgrade_legacy_policy(
+    #[wasm_bindgen(unchecked_param_type = "object")] legacy_policy: JsValue,
+) -> Result<PolicyUpgrade, JsValue> {
+    /// Result of `webassembly_upgrade_legacy_policy`.
+    #[derive(Serialize)]
+    struct Upgrade<'a> {
+        policy: &'a Policy,
+        report: UpgradeReport,
+    }
+
+    let (policy, is_legacy) = read_policy_and_detect_legacy(legacy_policy)?;
+    Ok(to_js(&Upgrade {
+        policy: &policy,
+        report: UpgradeReport::new(&policy, is_legacy),
+    })?
+    .into())
+}
+
+/// Policy held in the WebAssembly memory, sparing the conversion from and to a
+/// JavaScript object of the `webassembly_*` functions on each operation.
+#[wasm_bindgen]
+pub struct WasmPolicy(Policy);
+
+#[wasm_bindgen]
+impl WasmPolicy {
+    /// Creates an empty policy.
+    ///
+    /// - `max_attribute_creations` : maximum number of attribute creations
+    ///   (additions + rotations)
+    #[wasm_bindgen(constructor)]
+    pub fn new(max_attribute_creations: u32) -> Self {
+        Self(Policy::new(max_attribute_creations))
+    }
+
+    /// Reads a policy, converting it from a legacy format if needed.
+    ///
+    /// - `policy`      : global policy
+    #[wasm_bindgen(js_name = fromJson)]
+    pub fn from_json(policy: JsPolicy) -> Result<WasmPolicy, JsValue> {
+        Ok(Self(read_policy(policy)?))
+    }
+
+    /// Adds an axis, as returned by `webassembly_policy_axis`.
+    #[wasm_bindgen(js_name = addAxis)]
+    pub fn add_axis(&mut self, axis: JsPolicyAxis) -> Result<(), JsValue> {
+        self.0.add_axis(from_js(axis.into())?)?;
+        Ok(())
+    }
+
+    /// Rotates an attribute, written `Axis::Name`.
+    pub fn rotate(
+        &mut self,
+        #[wasm_bindgen(unchecked_param_type = "Attribute")] attribute: &str,
+    ) -> Result<(), JsValue> {
+        self.0.rotate(&attribute.parse::<Attribute>()?)?;
+        Ok(())
+    }
+
+    /// Lists the attributes, sorted by axis then by rank within their axis.
+    pub fn attributes(&self) -> Result<Attributes, JsValue> {
+        let mut attributes = self.0.attributes();
+        self.0.sort_attributes(&mut attributes);
+        Ok(to_js(&attributes)?.into())
+    }
+
+    /// Returns the policy, as taken by the `webassembly_*` functions.
+    #[wasm_bindgen(js_name = toJson)]
+    pub fn to_json(&self) -> Result<JsPolicy, JsValue> {
+        write_policy(&self.0)
+    }
 }
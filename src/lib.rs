@@ -6,19 +6,117 @@
 //!
 //! An `Attribute` is composed by an axis name and an attribute name within
 //! this axis.
+//!
+//! The `std` feature is enabled by default. Without it, the crate only
+//! depends on `core` and `alloc`. The `serde` feature implements `Serialize`
+//! and `Deserialize` for the policy types, and the `json` feature, also
+//! enabled by default, adds everything relying on `serde_json`: the JSON
+//! parsing and display of policies, `PolicyRef`, the binary format and the
+//! policy stores. The `parallel` feature generates and counts attribute
+//! combinations on several threads with `rayon`.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[macro_use]
+mod macros;
 
 mod access_policy;
 mod attribute;
+mod axis_name;
+#[cfg(feature = "json")]
+mod binary;
+#[cfg(feature = "std")]
+mod canonical;
+mod consistency;
+mod diff;
 mod error;
+mod events;
+mod expression;
+mod lint;
+#[cfg(feature = "std")]
+mod manager;
+mod naming_policy;
+mod pattern;
 mod policy;
+#[cfg(feature = "json")]
+mod policy_ref;
+#[cfg(feature = "random")]
+mod random;
+mod storage;
+mod store;
+#[cfg(feature = "json")]
+mod strict;
 
 pub use access_policy::AccessPolicy;
-pub use attribute::{Attribute, Attributes};
+#[cfg(feature = "serde")]
+pub use attribute::attribute_serde;
+pub use attribute::{Attribute, AttributeRef, Attributes};
+pub use axis_name::AxisName;
+#[cfg(feature = "json")]
+pub use binary::ValueWidth;
+#[cfg(feature = "std")]
+pub use canonical::CanonicalAccessPolicy;
+pub use consistency::PolicyProblem;
+pub use diff::PolicyDiff;
 pub use error::Error;
-pub use policy::{EncryptionHint, LegacyPolicy, Policy, PolicyAxis};
+pub use events::{ObservedPolicy, PolicyEvent, PolicyObserver};
+pub use expression::ExpressionError;
+pub use lint::{LintConfig, PolicyLint};
+#[doc(hidden)]
+pub use macros::validation as __private;
+#[cfg(feature = "std")]
+pub use manager::PolicyManager;
+pub use naming_policy::{CharacterClass, NamingPolicy, TrimmingRule};
+pub use pattern::AttributePattern;
+pub use policy::{
+    AttributeId, AxisId, EncryptionHint, LegacyPolicy, Policy, PolicyAxis, RotationEpoch,
+};
+#[cfg(feature = "json")]
+pub use policy_ref::{AccessPolicyRef, PolicyRef, SerializedAttribute};
+#[cfg(feature = "random")]
+pub use random::RandomPolicyParameters;
+pub use storage::{AttributeView, AxisView};
+#[cfg(all(feature = "std", feature = "json"))]
+pub use store::FilePolicyStore;
+#[cfg(feature = "json")]
+pub use store::MemoryPolicyStore;
+pub use store::PolicyStore;
+
+#[cfg(any(test, feature = "fixtures"))]
+pub mod fixtures;
 
 #[cfg(feature = "interface")]
 pub mod interfaces;
 
+#[cfg(feature = "std")]
+pub(crate) use std::collections::{HashMap, HashSet};
+
+#[cfg(not(feature = "std"))]
+pub(crate) use hashbrown::{HashMap, HashSet};
+
+/// Map iterated in insertion order.
+#[cfg(feature = "std")]
+pub type IndexMap<K, V> = indexmap::IndexMap<K, V>;
+
+/// Map iterated in insertion order.
+#[cfg(not(feature = "std"))]
+pub type IndexMap<K, V> = indexmap::IndexMap<K, V, hashbrown::DefaultHashBuilder>;
+
+/// Evaluates both closures, in parallel with the `parallel` feature.
+#[cfg(feature = "parallel")]
+pub(crate) use rayon::join;
+
+/// Evaluates both closures, in parallel with the `parallel` feature.
+#[cfg(not(feature = "parallel"))]
+pub(crate) fn join<A, B, RA, RB>(a: A, b: B) -> (RA, RB)
+where
+    A: FnOnce() -> RA,
+    B: FnOnce() -> RB,
+{
+    (a(), b())
+}
+
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,167 @@
+use crate::{Attribute, AxisName, Policy};
+use alloc::vec::Vec;
+use core::fmt::Display;
+
+/// Thresholds used when linting a policy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LintConfig {
+    /// Number of attribute creations (additions + rotations) after which an
+    /// attribute that has never been rotated is reported
+    pub stale_after: u32,
+    /// Percentage of the attribute creation capacity above which the policy
+    /// is reported as nearly exhausted
+    pub capacity_threshold: u8,
+}
+
+impl Default for LintConfig {
+    fn default() -> Self {
+        Self {
+            stale_after: 100,
+            capacity_threshold: 90,
+        }
+    }
+}
+
+/// Warning about a policy that is valid but probably not what was intended.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[non_exhaustive]
+pub enum PolicyLint {
+    /// The axis has no attribute.
+    EmptyAxis { axis: AxisName },
+    /// The axis has a single attribute.
+    SingleAttributeAxis { axis: AxisName },
+    /// The attribute names of a hierarchical axis are not sorted.
+    UnsortedHierarchicalAxis { axis: AxisName },
+    /// The attribute has not been rotated during the last attribute creations.
+    NeverRotated {
+        attribute: Attribute,
+        creations_since: u32,
+    },
+    /// Most of the attribute creation capacity has been consumed.
+    CapacityNearlyExhausted { used: u32, max: u32 },
+}
+
+impl Display for PolicyLint {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::EmptyAxis { axis } => write!(f, "axis {axis} has no attribute"),
+            Self::SingleAttributeAxis { axis } => write!(f, "axis {axis} has a single attribute"),
+            Self::UnsortedHierarchicalAxis { axis } => {
+                write!(f, "hierarchical axis {axis} has non-sorted attribute names")
+            }
+            Self::NeverRotated {
+                attribute,
+                creations_since,
+            } => write!(
+                f,
+                "attribute {attribute} never rotated in {creations_since} attribute creations"
+            ),
+            Self::CapacityNearlyExhausted { used, max } => write!(
+                f,
+                "{}% of the attribute creation capacity consumed ({used}/{max})",
+                u64::from(*used) * 100 / u64::from(*max).max(1)
+            ),
+        }
+    }
+}
+
+impl Policy {
+    /// Returns the warnings about this policy, using the default lint
+    /// thresholds.
+    #[must_use]
+    pub fn lint(&self) -> Vec<PolicyLint> {
+        self.lint_with(&LintConfig::default())
+    }
+
+    /// Returns the warnings about this policy, using the given lint
+    /// thresholds.
+    ///
+    /// Warnings are sorted by kind.
+    #[must_use]
+    pub fn lint_with(&self, config: &LintConfig) -> Vec<PolicyLint> {
+        let mut lints = Vec::new();
+
+        for axis in self.axes() {
+            let name = axis.name();
+            match axis.len() {
+                0 => lints.push(PolicyLint::EmptyAxis { axis: name.clone() }),
+                1 => lints.push(PolicyLint::SingleAttributeAxis { axis: name.clone() }),
+                _ => {}
+            }
+            if axis.is_hierarchical()
+                && axis
+                    .attribute_names()
+                    .zip(axis.attribute_names().skip(1))
+                    .any(|(lower, higher)| lower > higher)
+            {
+                lints.push(PolicyLint::UnsortedHierarchicalAxis { axis: name.clone() });
+            }
+        }
+
+        for attribute in self.attribute_views() {
+            if let [value] = attribute.values()[..] {
+                let creations_since = self.last_attribute_value.saturating_sub(value);
+                if creations_since >= config.stale_after {
+                    lints.push(PolicyLint::NeverRotated {
+                        attribute: attribute.to_attribute(),
+                        creations_since,
+                    });
+                }
+            }
+        }
+
+        if u64::from(self.last_attribute_value) * 100
+            >= u64::from(self.max_attribute_creations) * u64::from(config.capacity_threshold)
+        {
+            lints.push(PolicyLint::CapacityNearlyExhausted {
+                used: self.last_attribute_value,
+                max: self.max_attribute_creations,
+            });
+        }
+
+        lints.sort();
+        lints
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{tests::policy, EncryptionHint, Error, PolicyAxis};
+
+    #[test]
+    fn test_lint() -> Result<(), Error> {
+        let mut policy = policy()?;
+        // "Protected", "Confidential", "Top Secret" are not sorted
+        assert_eq!(
+            vec![PolicyLint::UnsortedHierarchicalAxis {
+                axis: AxisName::new("Security Level")?
+            }],
+            policy.lint()
+        );
+
+        policy.add_axis(PolicyAxis::new(
+            "Country",
+            vec![("France", EncryptionHint::Classic)],
+            false,
+        ))?;
+        let france = Attribute::new("Country", "France");
+        let config = LintConfig {
+            stale_after: 3,
+            capacity_threshold: 10,
+        };
+        for _ in 0..3 {
+            policy.rotate(&Attribute::new("Department", "HR"))?;
+        }
+        let lints = policy.lint_with(&config);
+        assert!(lints.contains(&PolicyLint::SingleAttributeAxis {
+            axis: AxisName::new("Country")?
+        }));
+        assert!(lints.contains(&PolicyLint::NeverRotated {
+            attribute: france,
+            creations_since: 3,
+        }));
+        assert!(lints.contains(&PolicyLint::CapacityNearlyExhausted { used: 11, max: 100 }));
+        Ok(())
+    }
+}
@@ -0,0 +1,222 @@
+/// Builds an `Attribute` from a string literal written `Axis::Name`, or from
+/// an axis and a name literals. The attribute is validated at compile time.
+///
+/// ```
+/// use abe_policy::{attr, Attribute};
+///
+/// assert_eq!(Attribute::new("Department", "FIN"), attr!("Department::FIN"));
+/// assert_eq!(Attribute::new("Department", "FIN"), attr!("Department", "FIN"));
+/// ```
+///
+/// ```compile_fail
+/// let attribute = abe_policy::attr!("Department:FIN");
+/// ```
+#[macro_export]
+macro_rules! attr {
+    ($attribute:literal) => {{
+        const _: () = assert!(
+            $crate::__private::is_valid_attribute($attribute),
+            concat!("invalid attribute: ", $attribute)
+        );
+        <$crate::Attribute as ::core::str::FromStr>::from_str($attribute)
+            .expect("attribute validated at compile time")
+    }};
+    ($axis:literal, $name:literal) => {{
+        const _: () = assert!(
+            $crate::__private::is_valid_attribute_part($axis)
+                && $crate::__private::is_valid_attribute_part($name),
+            concat!("invalid attribute: ", $axis, "::", $name)
+        );
+        $crate::Attribute::try_new($axis, $name).expect("attribute validated at compile time")
+    }};
+}
+
+/// Builds an `AccessPolicy` from a boolean expression literal, e.g.
+/// `"(Department::HR || Department::FIN) && Security Level::Protected"`. The
+/// syntax of the expression is validated at compile time.
+///
+/// ```
+/// use abe_policy::{access_policy, attr, AccessPolicy};
+///
+/// assert_eq!(
+///     AccessPolicy::Attr(attr!("Department::HR")) & AccessPolicy::Attr(attr!("Level::1")),
+///     access_policy!("Department::HR && Level::1")
+/// );
+/// ```
+///
+/// ```compile_fail
+/// let access_policy = abe_policy::access_policy!("(Department::HR && Level::1");
+/// ```
+#[macro_export]
+macro_rules! access_policy {
+    ($expression:literal) => {{
+        const _: () = assert!(
+            $crate::__private::is_valid_expression($expression),
+            concat!("invalid boolean expression: ", $expression)
+        );
+        $crate::AccessPolicy::from_boolean_expression($expression)
+            .expect("boolean expression validated at compile time")
+    }};
+}
+
+/// Compile-time validation used by the macros.
+#[doc(hidden)]
+pub mod validation {
+    const fn is_blank(bytes: &[u8], start: usize, end: usize) -> bool {
+        let mut i = start;
+        while i < end {
+            if !bytes[i].is_ascii_whitespace() {
+                return false;
+            }
+            i += 1;
+        }
+        true
+    }
+
+    const fn is_separator(bytes: &[u8], i: usize) -> bool {
+        i + 1 < bytes.len() && bytes[i] == b':' && bytes[i + 1] == b':'
+    }
+
+    /// Returns `true` if `bytes[start..end]` is written `Axis::Name`.
+    const fn is_valid_range(bytes: &[u8], start: usize, end: usize) -> bool {
+        let mut separator = None;
+        let mut i = start;
+        while i < end {
+            if i + 1 < end && is_separator(bytes, i) {
+                if separator.is_some() {
+                    return false;
+                }
+                separator = Some(i);
+                i += 2;
+            } else {
+                i += 1;
+            }
+        }
+        match separator {
+            Some(separator) => {
+                !is_blank(bytes, start, separator) && !is_blank(bytes, separator + 2, end)
+            }
+            None => false,
+        }
+    }
+
+    #[must_use]
+    pub const fn is_valid_attribute(attribute: &str) -> bool {
+        let bytes = attribute.as_bytes();
+        is_valid_range(bytes, 0, bytes.len())
+    }
+
+    #[must_use]
+    pub const fn is_valid_attribute_part(part: &str) -> bool {
+        let bytes = part.as_bytes();
+        let mut i = 0;
+        while i < bytes.len() {
+            if is_separator(bytes, i) {
+                return false;
+            }
+            i += 1;
+        }
+        !is_blank(bytes, 0, bytes.len())
+    }
+
+    const fn is_operator(bytes: &[u8], i: usize) -> bool {
+        i + 1 < bytes.len()
+            && ((bytes[i] == b'&' && bytes[i + 1] == b'&')
+                || (bytes[i] == b'|' && bytes[i + 1] == b'|'))
+    }
+
+    #[must_use]
+    pub const fn is_valid_expression(expression: &str) -> bool {
+        let bytes = expression.as_bytes();
+        let mut depth = 0_usize;
+        let mut expect_operand = true;
+        let mut i = 0;
+        while i < bytes.len() {
+            let byte = bytes[i];
+            if byte.is_ascii_whitespace() {
+                i += 1;
+            } else if byte == b'(' {
+                if !expect_operand {
+                    return false;
+                }
+                depth += 1;
+                i += 1;
+            } else if byte == b')' {
+                if expect_operand || depth == 0 {
+                    return false;
+                }
+                depth -= 1;
+                i += 1;
+            } else if is_operator(bytes, i) {
+                if expect_operand {
+                    return false;
+                }
+                expect_operand = true;
+                i += 2;
+            } else {
+                if !expect_operand {
+                    return false;
+                }
+                let start = i;
+                while i < bytes.len()
+                    && bytes[i] != b'('
+                    && bytes[i] != b')'
+                    && !is_operator(bytes, i)
+                {
+                    i += 1;
+                }
+                if !is_valid_range(bytes, start, i) {
+                    return false;
+                }
+                expect_operand = false;
+            }
+        }
+        depth == 0 && !expect_operand
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::validation::*;
+    use crate::{AccessPolicy, Attribute, Error};
+
+    #[test]
+    fn test_validation() {
+        assert!(is_valid_attribute("Security Level::Top Secret"));
+        for invalid in ["Department", "Department::", " ::FIN", "A::B::C"] {
+            assert!(!is_valid_attribute(invalid), "{invalid}");
+        }
+        for valid in [
+            "Department::HR",
+            "(Department::R&D || Department::FIN) && Security Level::Top Secret",
+            "((Department::HR))",
+        ] {
+            assert!(is_valid_expression(valid), "{valid}");
+        }
+        for invalid in [
+            "",
+            "Department::HR &&",
+            "(Department::HR",
+            "Department::HR)",
+            "Department::HR Department::FIN",
+            "Department::HR && () ",
+        ] {
+            assert!(!is_valid_expression(invalid), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn test_macros() -> Result<(), Error> {
+        assert_eq!(
+            Attribute::new("Department", "R&D"),
+            attr!("Department::R&D")
+        );
+        assert_eq!(
+            AccessPolicy::from_boolean_expression(
+                "(Department::R&D || Department::FIN) && Security Level::Top Secret"
+            )?,
+            access_policy!("(Department::R&D || Department::FIN) && Security Level::Top Secret")
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,244 @@
+use crate::{Attribute, EncryptionHint, Error, Policy, PolicyAxis};
+use std::sync::{Arc, PoisonError, RwLock};
+
+#[derive(Debug)]
+struct VersionedPolicy {
+    policy: Policy,
+    version: u64,
+}
+
+/// A `Policy` shared between threads.
+///
+/// Each successful modification increments the version of the managed policy.
+/// Callers can use this version to make sure the policy has not been modified
+/// since they read it (optimistic concurrency control).
+///
+/// Cloning a `PolicyManager` returns a handle on the same policy.
+#[derive(Debug, Clone)]
+pub struct PolicyManager {
+    inner: Arc<RwLock<VersionedPolicy>>,
+}
+
+impl PolicyManager {
+    /// Manages the given policy, starting at version 0.
+    #[must_use]
+    pub fn new(policy: Policy) -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(VersionedPolicy { policy, version: 0 })),
+        }
+    }
+
+    /// Calls `f` on the managed policy.
+    pub fn read<T>(&self, f: impl FnOnce(&Policy) -> T) -> T {
+        let guard = self.inner.read().unwrap_or_else(PoisonError::into_inner);
+        f(&guard.policy)
+    }
+
+    /// Returns a copy of the managed policy along with its version.
+    #[must_use]
+    pub fn snapshot(&self) -> (Policy, u64) {
+        let guard = self.inner.read().unwrap_or_else(PoisonError::into_inner);
+        (guard.policy.clone(), guard.version)
+    }
+
+    /// Returns the current version of the managed policy.
+    #[must_use]
+    pub fn version(&self) -> u64 {
+        self.inner
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .version
+    }
+
+    /// Applies `f` to the managed policy and returns the new version.
+    ///
+    /// `f` is applied to a copy of the policy: the managed policy is left
+    /// untouched if `f` fails.
+    pub fn update(&self, f: impl FnOnce(&mut Policy) -> Result<(), Error>) -> Result<u64, Error> {
+        self.update_versioned(None, f)
+    }
+
+    /// Applies `f` to the managed policy if its version is still
+    /// `expected_version`, and returns the new version.
+    ///
+    /// `f` is applied to a copy of the policy: the managed policy is left
+    /// untouched if `f` fails.
+    pub fn update_if(
+        &self,
+        expected_version: u64,
+        f: impl FnOnce(&mut Policy) -> Result<(), Error>,
+    ) -> Result<u64, Error> {
+        self.update_versioned(Some(expected_version), f)
+    }
+
+    fn update_versioned(
+        &self,
+        expected_version: Option<u64>,
+        f: impl FnOnce(&mut Policy) -> Result<(), Error>,
+    ) -> Result<u64, Error> {
+        // the copy is made under the read lock so that readers are not blocked
+        let (mut policy, version) = self.snapshot();
+        if let Some(expected) = expected_version {
+            if expected != version {
+                return Err(Error::VersionMismatch {
+                    expected,
+                    actual: version,
+                });
+            }
+        }
+        let mut guard = self.inner.write().unwrap_or_else(PoisonError::into_inner);
+        if guard.version != version {
+            if let Some(expected) = expected_version {
+                return Err(Error::VersionMismatch {
+                    expected,
+                    actual: guard.version,
+                });
+            }
+            // the copy is stale: the policy was modified in the meantime
+            policy = guard.policy.clone();
+        }
+        f(&mut policy)?;
+        guard.policy = policy;
+        guard.version += 1;
+        Ok(guard.version)
+    }
+
+    /// Adds the given policy axis to the managed policy and returns the new
+    /// version.
+    pub fn add_axis(&self, axis: PolicyAxis) -> Result<u64, Error> {
+        self.update(|policy| policy.add_axis(axis))
+    }
+
+    /// Adds the given attribute to the managed policy and returns the new
+    /// version.
+    pub fn add_attribute(
+        &self,
+        attribute: &Attribute,
+        encryption_hint: EncryptionHint,
+    ) -> Result<u64, Error> {
+        self.update(|policy| policy.add_attribute(attribute, encryption_hint))
+    }
+
+    /// Removes the given attribute from the managed policy and returns the
+    /// new version.
+    pub fn remove_attribute(&self, attribute: &Attribute) -> Result<u64, Error> {
+        self.update(|policy| policy.remove_attribute(attribute))
+    }
+
+    /// Renames the given attribute of the managed policy and returns the new
+    /// version.
+    pub fn rename_attribute(&self, attribute: &Attribute, new_name: &str) -> Result<u64, Error> {
+        self.update(|policy| policy.rename_attribute(attribute, new_name))
+    }
+
+    /// Rotates the given attribute of the managed policy and returns the new
+    /// version.
+    pub fn rotate(&self, attr: &Attribute) -> Result<u64, Error> {
+        self.update(|policy| policy.rotate(attr))
+    }
+
+    /// Returns the list of attributes of the managed policy.
+    #[must_use]
+    pub fn attributes(&self) -> Vec<Attribute> {
+        self.read(Policy::attributes)
+    }
+
+    /// Returns the list of all values given to this attribute over rotations.
+    /// The current value is returned first
+    pub fn attribute_values(&self, attribute: &Attribute) -> Result<Vec<u32>, Error> {
+        self.read(|policy| policy.attribute_values(attribute))
+    }
+
+    /// Retrieves the current value of an attribute.
+    pub fn attribute_current_value(&self, attribute: &Attribute) -> Result<u32, Error> {
+        self.read(|policy| policy.attribute_current_value(attribute))
+    }
+
+    /// Returns the hybridization hint of the given attribute.
+    pub fn attribute_hybridization_hint(
+        &self,
+        attribute: &Attribute,
+    ) -> Result<EncryptionHint, Error> {
+        self.read(|policy| policy.attribute_hybridization_hint(attribute))
+    }
+}
+
+impl From<Policy> for PolicyManager {
+    fn from(policy: Policy) -> Self {
+        Self::new(policy)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::policy;
+
+    #[test]
+    fn test_policy_manager() -> Result<(), Error> {
+        let manager = PolicyManager::new(policy()?);
+        let attributes = manager.attributes();
+
+        let handles = attributes
+            .iter()
+            .cloned()
+            .map(|attribute| {
+                let manager = manager.clone();
+                std::thread::spawn(move || manager.rotate(&attribute))
+            })
+            .collect::<Vec<_>>();
+        for handle in handles {
+            handle.join().unwrap()?;
+        }
+        assert_eq!(attributes.len() as u64, manager.version());
+        for attribute in &attributes {
+            assert_eq!(2, manager.attribute_values(attribute)?.len());
+        }
+
+        // stale version
+        let (_, version) = manager.snapshot();
+        manager.rotate(&attributes[0])?;
+        assert!(matches!(
+            manager.update_if(version, |policy| policy.rotate(&attributes[0])),
+            Err(Error::VersionMismatch { .. })
+        ));
+
+        // failed updates leave the policy untouched
+        let (before, version) = manager.snapshot();
+        assert!(manager
+            .update(|policy| {
+                policy.rotate(&attributes[0])?;
+                policy.rotate(&Attribute::new("Department", "Unknown"))
+            })
+            .is_err());
+        assert_eq!((before, version), manager.snapshot());
+        Ok(())
+    }
+
+    #[test]
+    fn test_policy_manager_attributes() -> Result<(), Error> {
+        let manager = PolicyManager::new(policy()?);
+        let version = manager.version();
+        let sales = Attribute::new("Department", "Sales");
+        let marketing = Attribute::new("Department", "Marketing");
+
+        assert_eq!(
+            version + 1,
+            manager.add_attribute(&sales, EncryptionHint::Classic)?
+        );
+        assert!(manager.attributes().contains(&sales));
+        assert!(manager
+            .add_attribute(&sales, EncryptionHint::Classic)
+            .is_err());
+
+        assert_eq!(version + 2, manager.rename_attribute(&sales, "Marketing")?);
+        assert!(manager.attributes().contains(&marketing));
+        assert!(!manager.attributes().contains(&sales));
+
+        assert_eq!(version + 3, manager.remove_attribute(&marketing)?);
+        assert!(!manager.attributes().contains(&marketing));
+        assert!(manager.remove_attribute(&marketing).is_err());
+        assert_eq!(version + 3, manager.version());
+        Ok(())
+    }
+}
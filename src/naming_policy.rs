@@ -0,0 +1,143 @@
+use crate::Error;
+use alloc::{borrow::Cow, format, string::ToString, vec::Vec};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use unicode_normalization::{is_nfc_quick, IsNormalized, UnicodeNormalization};
+
+/// Returns the Unicode Normalization Form C of the given name.
+///
+/// Visually identical names written with different code point sequences
+/// (e.g. a precomposed `é` and an `e` followed by a combining acute accent)
+/// have the same normal form.
+pub(crate) fn normalize(name: &str) -> Cow<'_, str> {
+    if is_nfc_quick(name.chars()) == IsNormalized::Yes {
+        Cow::Borrowed(name)
+    } else {
+        Cow::Owned(name.nfc().collect())
+    }
+}
+
+/// Class of characters allowed in axis and attribute names.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum CharacterClass {
+    /// Alphabetic characters, including non-ASCII letters.
+    Alphabetic,
+    /// Numeric characters.
+    Numeric,
+    /// Whitespace characters.
+    Whitespace,
+    /// ASCII punctuation characters (`-`, `_`, `&`, ...).
+    Punctuation,
+}
+
+impl CharacterClass {
+    /// Returns `true` if the given character belongs to this class.
+    #[must_use]
+    pub fn contains(&self, c: char) -> bool {
+        match self {
+            Self::Alphabetic => c.is_alphabetic(),
+            Self::Numeric => c.is_numeric(),
+            Self::Whitespace => c.is_whitespace(),
+            Self::Punctuation => c.is_ascii_punctuation(),
+        }
+    }
+}
+
+/// Rule applied to the whitespaces surrounding a name.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum TrimmingRule {
+    /// Names are kept as given.
+    #[default]
+    Keep,
+    /// Surrounding whitespaces are removed.
+    Trim,
+    /// Names with surrounding whitespaces are rejected.
+    Reject,
+}
+
+/// Constraints enforced by a `Policy` on the axis and attribute names it
+/// accepts.
+///
+/// The default naming policy accepts any non-empty name.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct NamingPolicy {
+    /// Maximum number of characters of a name
+    pub max_length: Option<usize>,
+    /// Classes of characters allowed in a name, any character is allowed if
+    /// `None`
+    pub allowed_characters: Option<Vec<CharacterClass>>,
+    /// Rule applied to surrounding whitespaces
+    pub trimming: TrimmingRule,
+    /// If `true`, names differing only by their case designate the same axis
+    /// or attribute. The case used at creation is kept for display.
+    pub case_insensitive: bool,
+}
+
+impl NamingPolicy {
+    /// Returns `true` if this naming policy does not constrain names.
+    #[must_use]
+    pub fn is_unconstrained(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// Returns `true` if the two given names designate the same axis or
+    /// attribute under this naming policy.
+    #[must_use]
+    pub fn matches(&self, name: &str, other: &str) -> bool {
+        name == other || (self.case_insensitive && name.to_lowercase() == other.to_lowercase())
+    }
+
+    /// Checks the given name against this naming policy and returns the name
+    /// to use, in Unicode Normalization Form C.
+    ///
+    /// - `name`    : axis or attribute name
+    pub fn apply<'a>(&self, name: &'a str) -> Result<Cow<'a, str>, Error> {
+        let trimmed = name.trim();
+        let name = match self.trimming {
+            TrimmingRule::Keep => name,
+            TrimmingRule::Trim => trimmed,
+            TrimmingRule::Reject => {
+                if trimmed.len() != name.len() {
+                    return Err(Error::InvalidName(format!(
+                        "'{name}' has surrounding whitespaces"
+                    )));
+                }
+                name
+            }
+        };
+
+        if name.is_empty() {
+            return Err(Error::InvalidName("empty name".to_string()));
+        }
+
+        let name = normalize(name);
+
+        if let Some(max_length) = self.max_length {
+            if name.chars().count() > max_length {
+                return Err(Error::InvalidName(format!(
+                    "'{name}' is longer than {max_length} characters"
+                )));
+            }
+        }
+
+        if let Some(classes) = &self.allowed_characters {
+            if let Some(c) = name
+                .chars()
+                .find(|c| !classes.iter().any(|class| class.contains(*c)))
+            {
+                return Err(Error::InvalidName(format!(
+                    "'{name}' contains the forbidden character '{c}'"
+                )));
+            }
+        }
+
+        Ok(name)
+    }
+}
@@ -0,0 +1,127 @@
+use crate::{Attribute, Error, Policy};
+use alloc::{format, string::String, vec::Vec};
+use core::{fmt::Display, str::FromStr};
+
+/// A pattern matching attributes, written `Axis::Name` where both the axis
+/// and the name may contain the wildcard `*`, which matches any sequence of
+/// characters.
+///
+/// `Department::*` matches all attributes of the "Department" axis,
+/// `*::Top Secret` the "Top Secret" attributes of all axes and
+/// `Country::F*` the attributes of the "Country" axis starting with "F".
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AttributePattern {
+    axis: String,
+    name: String,
+}
+
+impl AttributePattern {
+    /// Creates a pattern from the given axis and name patterns.
+    pub fn new(axis: &str, name: &str) -> Result<Self, Error> {
+        if axis.is_empty() || name.is_empty() {
+            return Err(Error::InvalidAttribute(format!(
+                "empty axis or empty name in pattern {axis}::{name}"
+            )));
+        }
+        Ok(Self {
+            axis: axis.into(),
+            name: name.into(),
+        })
+    }
+
+    /// Returns `true` if the given attribute matches this pattern.
+    #[must_use]
+    pub fn matches(&self, attribute: &Attribute) -> bool {
+        glob_matches(&self.axis, &attribute.axis) && glob_matches(&self.name, &attribute.name)
+    }
+
+    /// Returns the attributes of the given policy matching this pattern,
+    /// sorted as in the policy.
+    #[must_use]
+    pub fn expand(&self, policy: &Policy) -> Vec<Attribute> {
+        let mut attributes = policy
+            .attribute_views()
+            .filter(|attribute| {
+                glob_matches(&self.axis, attribute.axis())
+                    && glob_matches(&self.name, attribute.name())
+            })
+            .map(|attribute| attribute.to_attribute())
+            .collect::<Vec<_>>();
+        policy.sort_attributes(&mut attributes);
+        attributes
+    }
+}
+
+impl FromStr for AttributePattern {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (axis, name) = s.trim().split_once("::").ok_or_else(|| {
+            Error::InvalidAttribute(format!("separator '::' expected in pattern {s}"))
+        })?;
+        Self::new(axis, name)
+    }
+}
+
+impl Display for AttributePattern {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}::{}", self.axis, self.name)
+    }
+}
+
+/// Returns `true` if `text` matches `pattern`, in which `*` matches any
+/// sequence of characters.
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let text = text.chars().collect::<Vec<_>>();
+    let (mut p, mut t) = (0, 0);
+    // position of the last `*` in the pattern and of the text it matched from
+    let mut backtrack = None;
+    while t < text.len() {
+        if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if p < pattern.len() && pattern[p] == text[t] {
+            p += 1;
+            t += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::policy;
+
+    #[test]
+    fn test_attribute_pattern() -> Result<(), Error> {
+        let policy = policy()?;
+
+        let pattern = "Department::*".parse::<AttributePattern>()?;
+        assert_eq!(4, pattern.expand(&policy).len());
+
+        let pattern = "*::*o*".parse::<AttributePattern>()?;
+        assert_eq!(
+            vec![
+                Attribute::new("Security Level", "Protected"),
+                Attribute::new("Security Level", "Confidential"),
+                Attribute::new("Security Level", "Top Secret"),
+            ],
+            pattern.expand(&policy)
+        );
+
+        let pattern = "*::Top Secret".parse::<AttributePattern>()?;
+        assert!(pattern.matches(&Attribute::new("Security Level", "Top Secret")));
+        assert!(!pattern.matches(&Attribute::new("Security Level", "Top Secrets")));
+
+        assert!("Department".parse::<AttributePattern>().is_err());
+        Ok(())
+    }
+}
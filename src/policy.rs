@@ -1,13 +1,38 @@
-use crate::{Attribute, Error};
-use serde::{Deserialize, Serialize};
-use std::{
-    collections::HashMap,
+#[cfg(feature = "json")]
+use crate::error::from_json_slice;
+use crate::{
+    consistency::PolicyProblem,
+    join,
+    naming_policy::normalize,
+    storage::{AttributeEntry, AttributeView, AxisEntry, AxisView, NameTable},
+    AccessPolicy, Attribute, AttributeRef, AxisName, Error, HashSet, IndexMap, NamingPolicy,
+};
+use alloc::{
+    borrow::Cow,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{
     fmt::{Debug, Display},
     ops::BitOr,
 };
+#[cfg(feature = "serde")]
+use core::{hash::Hash, marker::PhantomData};
+#[cfg(feature = "serde")]
+use serde::{
+    de::{self, DeserializeSeed, MapAccess, SeqAccess, Visitor},
+    ser::{SerializeMap, SerializeStruct},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+#[cfg(feature = "json")]
+use serde_json::{Map, Value};
+use smallvec::{smallvec, SmallVec};
 
 /// Hint the user about which kind of encryption to use.
-#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum EncryptionHint {
     /// Hybridized encryption should be used.
     Hybridized,
@@ -28,7 +53,8 @@ impl BitOr for EncryptionHint {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct AxisAttributePorperties {
     pub name: String,
     pub encryption_hint: EncryptionHint,
@@ -40,7 +66,8 @@ pub struct AxisAttributePorperties {
 ///
 /// If `hierarchical` is set to `true`, we assume a lexicographical order based
 /// on the attribute name.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct PolicyAxis {
     /// Axis name
     pub name: String,
@@ -89,19 +116,90 @@ impl PolicyAxis {
     }
 }
 
-#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct PolicyAxesParameters {
     pub attribute_names: Vec<String>,
     pub is_hierarchical: bool,
 }
 
-#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+/// Rotation index of an attribute value: the value given at the creation of
+/// the attribute has epoch 0, and each rotation increments the epoch.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RotationEpoch(pub u32);
+
+impl Display for RotationEpoch {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Identifier of an axis in a policy: its rank in the order the axes were
+/// added. Axes are never removed, so it does not change while the policy is
+/// modified, but reading a policy written with its axes in another order,
+/// e.g. sorted by name in the binary format, renumbers them.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AxisId(pub u16);
+
+impl AxisId {
+    /// Returns the identifier of the axis with the given index, which must
+    /// be below `MAX_AXES`.
+    pub(crate) fn from_index(index: usize) -> Self {
+        debug_assert!(index < MAX_AXES);
+        Self(index as u16)
+    }
+}
+
+impl Display for AxisId {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+/// Maximum number of axes of a policy, whose identifiers are 16-bit.
+pub(crate) const MAX_AXES: usize = 1 << 16;
+
+/// Identifier of an attribute in a policy: the identifier of its axis and the
+/// value given to the attribute at its creation, which is unique within the
+/// policy and does not change on rotation.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AttributeId {
+    pub axis: AxisId,
+    pub value: u32,
+}
+
+impl Display for AttributeId {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}.{}", self.axis, self.value)
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct PolicyAttributesParameters {
-    pub values: Vec<u32>,
+    /// Values taken by the attribute, the current one last. Most attributes
+    /// have one or two values, which are stored inline.
+    pub values: SmallVec<[u32; 2]>,
     pub encryption_hint: EncryptionHint,
+    /// Number of rotations whose values were reclaimed by
+    /// `Policy::reclaim_values`, and are no longer listed after the first
+    /// value. The epochs of the following values account for them.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "is_zero"))]
+    pub retired_epochs: u32,
+}
+
+#[cfg(feature = "serde")]
+fn is_zero(n: &u32) -> bool {
+    *n == 0
 }
 
-#[derive(Clone, Serialize, Deserialize, Debug)]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct LegacyPolicy {
     /// Last value taken by the attriute.
     pub(crate) last_attribute_value: u32,
@@ -110,19 +208,28 @@ pub struct LegacyPolicy {
     pub max_attribute_creations: u32,
     /// Policy axes: maps axes name to the list of associated attribute names
     /// and a boolean defining whether or not this axis is hierarchical.
-    pub axes: HashMap<String, PolicyAxesParameters>,
+    pub axes: IndexMap<AxisName, PolicyAxesParameters>,
     /// Maps an attribute to its values and its hybridization hint.
-    pub attributes: HashMap<Attribute, Vec<u32>>,
+    pub attributes: IndexMap<Attribute, Vec<u32>>,
 }
 
-#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum PolicyVersion {
     V1,
 }
 
 /// A policy is a set of policy axes. A fixed number of attribute creations
 /// (revocations + additions) is allowed.
-#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+///
+/// The attributes are keyed by their `AttributeId`: each axis lists the
+/// identifiers of its attributes, and the name of an attribute is only stored
+/// in its entry, next to its values. Use `axes` and `attribute_views` to read
+/// them. Each attribute takes about 150 bytes with a 30-byte name. Read-only
+/// users of large policies can deserialize a `PolicyRef` instead, which
+/// borrows the names from the serialized policy.
+#[derive(Clone, Debug)]
 pub struct Policy {
     /// Version number
     pub version: PolicyVersion,
@@ -131,15 +238,136 @@ pub struct Policy {
     /// Maximum attribute value. Defines a maximum number of attribute
     /// creations (revocations + addition).
     pub max_attribute_creations: u32,
+    /// Policy axes, whose index is their identifier: maps axes name to the
+    /// identifiers of their attributes and whether they are hierarchical.
+    pub(crate) axes: IndexMap<AxisName, AxisEntry>,
+    /// Maps the identifier of an attribute to its name, its values and its
+    /// hybridization hint, in the order the attributes were added.
+    pub(crate) attributes: IndexMap<AttributeId, AttributeEntry>,
+    /// Identifiers of the attributes, by axis and name.
+    pub(crate) names: NameTable,
+    /// Constraints enforced on the names of the axes and attributes added to
+    /// this policy.
+    pub naming_policy: NamingPolicy,
+    /// Values retired by `Policy::reclaim_values`, in increasing order. They
+    /// are given again once the maximum number of attribute creations is
+    /// reached.
+    pub(crate) reclaimed_values: Vec<u32>,
+    /// Fields not known by this version of the library. They are kept as is
+    /// so that policies written by newer versions are not altered by a
+    /// deserialization/serialization round-trip.
+    #[cfg(feature = "json")]
+    pub unknown_fields: Map<String, Value>,
+}
+
+/// Policy as it is serialized: its attributes are keyed by axis and name, and
+/// are listed by name in their axis. It may be inconsistent, e.g. to report
+/// all the problems of a corrupted policy, and converts into a `Policy` once
+/// its structure is checked.
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schema", schemars(rename = "Policy"))]
+pub(crate) struct UncheckedPolicy {
+    /// Version number
+    pub(crate) version: PolicyVersion,
+    /// Last value taken by the attriute.
+    pub(crate) last_attribute_value: u32,
+    /// Maximum attribute value. Defines a maximum number of attribute
+    /// creations (revocations + addition).
+    pub(crate) max_attribute_creations: u32,
     /// Policy axes: maps axes name to the list of associated attribute names
     /// and a boolean defining whether or not this axis is hierarchical.
-    pub axes: HashMap<String, PolicyAxesParameters>,
+    pub(crate) axes: IndexMap<AxisName, PolicyAxesParameters>,
     /// Maps an attribute to its values and its hybridization hint.
-    pub attributes: HashMap<Attribute, PolicyAttributesParameters>,
+    ///
+    /// Serialized as an array of `{ axis, name, values, encryption_hint }`
+    /// records. The map keyed by `Axis::Name` strings written by previous
+    /// versions is also read.
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_attributes"))]
+    #[cfg_attr(feature = "schema", schemars(with = "Vec<AttributeRecord<'static>>"))]
+    pub(crate) attributes: IndexMap<Attribute, PolicyAttributesParameters>,
+    /// Constraints enforced on the names of the axes and attributes added to
+    /// this policy.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "NamingPolicy::is_unconstrained")
+    )]
+    pub(crate) naming_policy: NamingPolicy,
+    /// Values retired by `Policy::reclaim_values`, in increasing order.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub(crate) reclaimed_values: Vec<u32>,
+    /// Fields not known by this version of the library.
+    #[cfg(feature = "json")]
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub(crate) unknown_fields: Map<String, Value>,
+}
+
+/// The schema of a policy is the one of its serialized form.
+#[cfg(feature = "schema")]
+impl schemars::JsonSchema for Policy {
+    fn schema_name() -> Cow<'static, str> {
+        UncheckedPolicy::schema_name()
+    }
+
+    fn json_schema(generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        UncheckedPolicy::json_schema(generator)
+    }
+}
+
+/// Policies are equal if they have the same axes and attributes, whatever
+/// their order and identifiers.
+impl PartialEq for Policy {
+    fn eq(&self, other: &Self) -> bool {
+        #[cfg(feature = "json")]
+        if self.unknown_fields != other.unknown_fields {
+            return false;
+        }
+        let same_axes = || {
+            self.axes().all(|axis| {
+                other
+                    .axes
+                    .get_index_of(axis.name().as_str())
+                    .and_then(|index| other.axis_by_id(AxisId::from_index(index)))
+                    .is_some_and(|other_axis| {
+                        axis.is_hierarchical() == other_axis.is_hierarchical()
+                            && axis.attribute_names().eq(other_axis.attribute_names())
+                    })
+            })
+        };
+        let same_attributes = || {
+            self.attribute_views().all(|attribute| {
+                other
+                    .exact_attribute(attribute.as_attribute_ref())
+                    .is_some_and(|other_attribute| {
+                        attribute.entry.parameters == other_attribute.entry.parameters
+                    })
+            })
+        };
+        self.version == other.version
+            && self.last_attribute_value == other.last_attribute_value
+            && self.max_attribute_creations == other.max_attribute_creations
+            && self.naming_policy == other.naming_policy
+            && self.reclaimed_values == other.reclaimed_values
+            && self.axes.len() == other.axes.len()
+            && self.attributes.len() == other.attributes.len()
+            && same_axes()
+            && same_attributes()
+    }
 }
 
+impl Eq for Policy {}
+
+/// Writes the policy as JSON, or as a table with the alternate flag (`{:#}`).
+#[cfg(feature = "json")]
 impl Display for Policy {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if f.alternate() {
+            return self.write_table(f);
+        }
         let json = serde_json::to_string(&self);
         match json {
             Ok(string) => write!(f, "{string}"),
@@ -148,40 +376,604 @@ impl Display for Policy {
     }
 }
 
+/// Serialized attribute of a policy. Its axis and name are written apart, so
+/// that they need no escaping.
+#[cfg(feature = "serde")]
+#[derive(Serialize)]
+struct AttributeRecordRef<'a> {
+    axis: &'a str,
+    name: &'a str,
+    values: &'a [u32],
+    encryption_hint: EncryptionHint,
+    #[serde(skip_serializing_if = "is_zero")]
+    retired_epochs: u32,
+}
+
+/// Deserialized `AttributeRecordRef`.
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+struct AttributeRecord<'a> {
+    #[serde(borrow)]
+    axis: Cow<'a, str>,
+    #[serde(borrow)]
+    name: Cow<'a, str>,
+    values: SmallVec<[u32; 2]>,
+    encryption_hint: EncryptionHint,
+    #[serde(default)]
+    retired_epochs: u32,
+}
+
+#[cfg(feature = "serde")]
+fn serialize_attributes<S: Serializer>(
+    attributes: &IndexMap<Attribute, PolicyAttributesParameters>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(
+        attributes
+            .iter()
+            .map(|(attribute, parameters)| AttributeRecordRef {
+                axis: &attribute.axis,
+                name: &attribute.name,
+                values: &parameters.values,
+                encryption_hint: parameters.encryption_hint,
+                retired_epochs: parameters.retired_epochs,
+            }),
+    )
+}
+
+/// Serialized axes of a `Policy`, as the `PolicyAxesParameters` of an
+/// `UncheckedPolicy`.
+#[cfg(feature = "serde")]
+struct AxesRecordRef<'a>(&'a Policy);
+
+#[cfg(feature = "serde")]
+impl Serialize for AxesRecordRef<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.0.axes().map(|axis| (axis.name(), AxisRecordRef(axis))))
+    }
+}
+
+#[cfg(feature = "serde")]
+struct AxisRecordRef<'a>(AxisView<'a>);
+
+#[cfg(feature = "serde")]
+impl Serialize for AxisRecordRef<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        struct AttributeNames<'a>(AxisView<'a>);
+
+        impl Serialize for AttributeNames<'_> {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_seq(self.0.attribute_names())
+            }
+        }
+
+        let mut axis = serializer.serialize_struct("PolicyAxesParameters", 2)?;
+        axis.serialize_field("attribute_names", &AttributeNames(self.0))?;
+        axis.serialize_field("is_hierarchical", &self.0.is_hierarchical())?;
+        axis.end()
+    }
+}
+
+/// Serialized attributes of a `Policy`, as those of an `UncheckedPolicy`.
+#[cfg(feature = "serde")]
+struct AttributesRecordRef<'a>(&'a Policy);
+
+#[cfg(feature = "serde")]
+impl Serialize for AttributesRecordRef<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(
+            self.0
+                .attribute_views()
+                .map(|attribute| AttributeRecordRef {
+                    axis: attribute.axis(),
+                    name: attribute.name(),
+                    values: attribute.values(),
+                    encryption_hint: attribute.encryption_hint(),
+                    retired_epochs: attribute.retired_epochs(),
+                }),
+        )
+    }
+}
+
+/// Writes the fields of the `UncheckedPolicy` this policy converts from,
+/// without copying its names.
+#[cfg(feature = "serde")]
+impl Serialize for Policy {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("version", &self.version)?;
+        map.serialize_entry("last_attribute_value", &self.last_attribute_value)?;
+        map.serialize_entry("max_attribute_creations", &self.max_attribute_creations)?;
+        map.serialize_entry("axes", &AxesRecordRef(self))?;
+        map.serialize_entry("attributes", &AttributesRecordRef(self))?;
+        if !self.naming_policy.is_unconstrained() {
+            map.serialize_entry("naming_policy", &self.naming_policy)?;
+        }
+        if !self.reclaimed_values.is_empty() {
+            map.serialize_entry("reclaimed_values", &self.reclaimed_values)?;
+        }
+        #[cfg(feature = "json")]
+        for (field, value) in &self.unknown_fields {
+            map.serialize_entry(field, value)?;
+        }
+        map.end()
+    }
+}
+
+/// Key of the attributes of a deserialized policy.
+#[cfg(feature = "serde")]
+pub(crate) trait AttributeRecordKey<'de>: Deserialize<'de> + Hash + Eq {
+    /// Creates the key of a record.
+    fn from_record(axis: Cow<'de, str>, name: Cow<'de, str>) -> Result<Self, Error>;
+}
+
+#[cfg(feature = "serde")]
+impl AttributeRecordKey<'_> for Attribute {
+    fn from_record(axis: Cow<'_, str>, name: Cow<'_, str>) -> Result<Self, Error> {
+        Self::from_serialized(&axis, &name)
+    }
+}
+
+/// Reads the attributes of a policy from an array of records, or from a map
+/// keyed by `Axis::Name` strings.
+#[cfg(feature = "serde")]
+pub(crate) struct AttributesVisitor<K>(PhantomData<K>);
+
+#[cfg(feature = "serde")]
+impl<K> Default for AttributesVisitor<K> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, K: AttributeRecordKey<'de>> Visitor<'de> for AttributesVisitor<K> {
+    type Value = IndexMap<K, PolicyAttributesParameters>;
+
+    fn expecting(&self, formatter: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        formatter.write_str("an array of attributes")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut attributes = IndexMap::default();
+        while let Some(record) = seq.next_element::<AttributeRecord<'de>>()? {
+            attributes.insert(
+                K::from_record(record.axis, record.name).map_err(de::Error::custom)?,
+                PolicyAttributesParameters {
+                    values: record.values,
+                    encryption_hint: record.encryption_hint,
+                    retired_epochs: record.retired_epochs,
+                },
+            );
+        }
+        Ok(attributes)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut attributes = IndexMap::default();
+        while let Some((attribute, parameters)) = map.next_entry()? {
+            attributes.insert(attribute, parameters);
+        }
+        Ok(attributes)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, K: AttributeRecordKey<'de>> DeserializeSeed<'de> for AttributesVisitor<K> {
+    type Value = IndexMap<K, PolicyAttributesParameters>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(self)
+        } else {
+            deserializer.deserialize_seq(self)
+        }
+    }
+}
+
+/// Deserializes the attributes of a policy as `AttributesVisitor` reads them.
+#[cfg(feature = "json")]
+pub(crate) fn deserialize_attributes<'de, D: Deserializer<'de>, K: AttributeRecordKey<'de>>(
+    deserializer: D,
+) -> Result<IndexMap<K, PolicyAttributesParameters>, D::Error> {
+    AttributesVisitor::default().deserialize(deserializer)
+}
+
+#[cfg(feature = "serde")]
+struct PolicyVisitor;
+
+#[cfg(feature = "serde")]
+impl<'de> Visitor<'de> for PolicyVisitor {
+    type Value = UncheckedPolicy;
+
+    fn expecting(&self, formatter: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        formatter.write_str("a policy")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        fn set<T, E: de::Error>(
+            field: &mut Option<T>,
+            name: &'static str,
+            value: T,
+        ) -> Result<(), E> {
+            if field.replace(value).is_some() {
+                return Err(E::duplicate_field(name));
+            }
+            Ok(())
+        }
+
+        let mut version = None;
+        let mut last_attribute_value = None;
+        let mut max_attribute_creations = None;
+        let mut axes = None;
+        let mut attributes = None;
+        let mut naming_policy = None;
+        let mut reclaimed_values = None;
+        #[cfg(feature = "json")]
+        let mut unknown_fields = Map::new();
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "version" => set(&mut version, "version", map.next_value()?)?,
+                "last_attribute_value" => set(
+                    &mut last_attribute_value,
+                    "last_attribute_value",
+                    map.next_value()?,
+                )?,
+                "max_attribute_creations" => set(
+                    &mut max_attribute_creations,
+                    "max_attribute_creations",
+                    map.next_value()?,
+                )?,
+                "axes" => set(&mut axes, "axes", map.next_value()?)?,
+                "attributes" => set(
+                    &mut attributes,
+                    "attributes",
+                    map.next_value_seed(AttributesVisitor::default())?,
+                )?,
+                "naming_policy" => set(&mut naming_policy, "naming_policy", map.next_value()?)?,
+                "reclaimed_values" => {
+                    set(&mut reclaimed_values, "reclaimed_values", map.next_value()?)?
+                }
+                #[cfg(feature = "json")]
+                _ => {
+                    unknown_fields.insert(key, map.next_value()?);
+                }
+                #[cfg(not(feature = "json"))]
+                _ => {
+                    map.next_value::<de::IgnoredAny>()?;
+                }
+            }
+        }
+        let last_attribute_value =
+            last_attribute_value.ok_or_else(|| de::Error::missing_field("last_attribute_value"))?;
+        let max_attribute_creations = max_attribute_creations
+            .ok_or_else(|| de::Error::missing_field("max_attribute_creations"))?;
+        Ok(UncheckedPolicy {
+            version: version.ok_or_else(|| de::Error::missing_field("version"))?,
+            last_attribute_value,
+            max_attribute_creations,
+            axes: axes.ok_or_else(|| de::Error::missing_field("axes"))?,
+            attributes: attributes.ok_or_else(|| de::Error::missing_field("attributes"))?,
+            naming_policy: naming_policy.unwrap_or_default(),
+            reclaimed_values: reclaimed_values.unwrap_or_default(),
+            #[cfg(feature = "json")]
+            unknown_fields,
+        })
+    }
+}
+
+/// Reads the fields one by one, keeping the unknown ones, so that a policy
+/// can be deserialized from a stream without buffering it.
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for UncheckedPolicy {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(PolicyVisitor)
+    }
+}
+
+/// Fails on the first structural problem reported by
+/// `UncheckedPolicy::check_consistency`.
+impl TryFrom<UncheckedPolicy> for Policy {
+    type Error = PolicyProblem;
+
+    fn try_from(policy: UncheckedPolicy) -> Result<Self, Self::Error> {
+        policy.check_structure()?;
+
+        let axis_count = policy.axes.len();
+        let axis_id = |index: usize| {
+            u16::try_from(index)
+                .map(AxisId)
+                .map_err(|_| PolicyProblem::TooManyAxes { count: axis_count })
+        };
+        let mut attributes = IndexMap::default();
+        attributes.reserve(policy.attributes.len());
+        let mut names = NameTable::default();
+        for (attribute, parameters) in policy.attributes {
+            let Some(index) = policy.axes.get_index_of(attribute.axis.as_str()) else {
+                return Err(PolicyProblem::OrphanAttribute { attribute });
+            };
+            let Some(value) = parameters.values.first().copied() else {
+                return Err(PolicyProblem::NoValue { attribute });
+            };
+            let id = AttributeId {
+                axis: axis_id(index)?,
+                value,
+            };
+            attributes.insert(
+                id,
+                AttributeEntry {
+                    name: attribute.name,
+                    parameters,
+                },
+            );
+            names.insert(&attributes, id);
+        }
+
+        let mut axes = IndexMap::default();
+        axes.reserve(policy.axes.len());
+        for (index, (axis, parameters)) in policy.axes.into_iter().enumerate() {
+            let id = axis_id(index)?;
+            let attribute_ids = parameters
+                .attribute_names
+                .into_iter()
+                .map(|name| {
+                    names.find(&attributes, id, &name).ok_or_else(|| {
+                        PolicyProblem::UndefinedAttribute {
+                            axis: axis.clone(),
+                            name,
+                        }
+                    })
+                })
+                .collect::<Result<_, _>>()?;
+            axes.insert(
+                axis,
+                AxisEntry {
+                    is_hierarchical: parameters.is_hierarchical,
+                    attributes: attribute_ids,
+                },
+            );
+        }
+
+        Ok(Self {
+            version: policy.version,
+            last_attribute_value: policy.last_attribute_value,
+            max_attribute_creations: policy.max_attribute_creations,
+            axes,
+            attributes,
+            names,
+            naming_policy: policy.naming_policy,
+            reclaimed_values: policy.reclaimed_values,
+            #[cfg(feature = "json")]
+            unknown_fields: policy.unknown_fields,
+        })
+    }
+}
+
+/// Fails on the structural problems of the serialized policy, e.g. on a value
+/// given to several attributes.
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Policy {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        UncheckedPolicy::deserialize(deserializer)?
+            .try_into()
+            .map_err(de::Error::custom)
+    }
+}
+
+/// Converts a policy from the legacy format: its attributes are hinted for
+/// classic encryption.
+impl From<LegacyPolicy> for UncheckedPolicy {
+    fn from(policy: LegacyPolicy) -> Self {
+        Self {
+            version: PolicyVersion::V1,
+            max_attribute_creations: policy.max_attribute_creations,
+            last_attribute_value: policy.last_attribute_value,
+            axes: policy.axes,
+            attributes: policy
+                .attributes
+                .into_iter()
+                .map(|(name, values)| {
+                    (
+                        name,
+                        PolicyAttributesParameters {
+                            values: SmallVec::from_vec(values),
+                            encryption_hint: EncryptionHint::Classic,
+                            retired_epochs: 0,
+                        },
+                    )
+                })
+                .collect(),
+            naming_policy: NamingPolicy::default(),
+            reclaimed_values: Vec::new(),
+            #[cfg(feature = "json")]
+            unknown_fields: Map::new(),
+        }
+    }
+}
+
+/// Converts a policy from the legacy format as `UncheckedPolicy` does,
+/// failing on its first structural problem.
+impl TryFrom<LegacyPolicy> for Policy {
+    type Error = PolicyProblem;
+
+    fn try_from(policy: LegacyPolicy) -> Result<Self, Self::Error> {
+        UncheckedPolicy::from(policy).try_into()
+    }
+}
+
+/// Writes a policy as a table.
+struct PolicyTable<'a>(&'a Policy);
+
+impl Display for PolicyTable<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.0.write_table(f)
+    }
+}
+
 impl Policy {
+    /// Returns a human-readable table of the axes and attributes of this
+    /// policy, with their current value, number of rotations and encryption
+    /// hint. Axes are sorted by name and attributes are listed in axis order.
+    #[must_use]
+    pub fn to_pretty_string(&self) -> String {
+        PolicyTable(self).to_string()
+    }
+
+    fn write_table(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        const HEADER: [&str; 5] = ["Axis", "Attribute", "Value", "Rotations", "Hint"];
+
+        let mut axes = self.axes().collect::<Vec<_>>();
+        axes.sort_by_key(AxisView::name);
+        let mut rows = Vec::with_capacity(self.attributes.len());
+        for axis in axes {
+            let label = if axis.is_hierarchical() {
+                format!("{} (hierarchical)", axis.name())
+            } else {
+                axis.name().to_string()
+            };
+            for attribute in axis.attributes() {
+                rows.push([
+                    label.clone(),
+                    attribute.name().to_string(),
+                    attribute.current_value().to_string(),
+                    attribute.current_epoch().to_string(),
+                    format!("{:?}", attribute.encryption_hint()),
+                ]);
+            }
+        }
+
+        let mut widths = HEADER.map(|title| title.chars().count());
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        writeln!(
+            f,
+            "Attribute creations: {}/{}",
+            self.last_attribute_value, self.max_attribute_creations
+        )?;
+        let write_row = |f: &mut core::fmt::Formatter<'_>, row: [&str; 5]| {
+            for (i, (cell, width)) in row.iter().zip(widths).enumerate() {
+                if i > 0 {
+                    write!(f, " | ")?;
+                }
+                // right-align numeric columns, do not pad the last one
+                match i {
+                    2 | 3 => write!(f, "{cell:>width$}")?,
+                    4 => write!(f, "{cell}")?,
+                    _ => write!(f, "{cell:<width$}")?,
+                }
+            }
+            writeln!(f)
+        };
+        write_row(f, HEADER)?;
+        writeln!(
+            f,
+            "{}",
+            widths
+                .iter()
+                .map(|width| "-".repeat(*width))
+                .collect::<Vec<_>>()
+                .join("-+-")
+        )?;
+        for row in &rows {
+            write_row(f, row.each_ref().map(String::as_str))?;
+        }
+        Ok(())
+    }
+
     /// Converts the given string into a Policy. Does not fail if the given
-    /// string uses the legacy format.
+    /// string uses the legacy format, but fails if the policy is corrupted,
+    /// e.g. if an attribute is missing from the list of its axis.
+    #[cfg(feature = "json")]
     pub fn parse_and_convert(bytes: &[u8]) -> Result<Self, Error> {
-        match serde_json::from_slice(bytes) {
-            Ok(policy) => Ok(policy),
-            Err(e) => {
-                if let Ok(policy) = serde_json::from_slice::<LegacyPolicy>(bytes) {
-                    // Convert the legacy format to the current one.
-                    Ok(Self {
-                        version: PolicyVersion::V1,
-                        max_attribute_creations: policy.max_attribute_creations,
-                        last_attribute_value: policy.last_attribute_value,
-                        axes: policy.axes,
-                        attributes: policy
-                            .attributes
-                            .into_iter()
-                            .map(|(name, values)| {
-                                (
-                                    name,
-                                    PolicyAttributesParameters {
-                                        values,
-                                        encryption_hint: EncryptionHint::Classic,
-                                    },
-                                )
-                            })
-                            .collect(),
-                    })
-                } else {
-                    // Return the `Policy` deserialization error message instead of the
-                    // `LegacyPolicy` one since this is the one that should be used.
-                    Err(Error::DeserializationError(e))
+        Self::parse_and_detect_legacy(bytes).map(|(policy, _)| policy)
+    }
+
+    /// Converts the given string into a Policy as `parse_and_convert` does,
+    /// but fails on unknown fields instead of keeping them, e.g. on a
+    /// misspelled `is_hierarchical` in an axis.
+    ///
+    /// The legacy format is read as well, also without unknown fields.
+    #[cfg(feature = "json")]
+    pub fn parse_strict(bytes: &[u8]) -> Result<Self, Error> {
+        crate::strict::parse_strict(bytes)
+    }
+
+    /// Same as `parse_and_convert`, also returning `true` if the given string
+    /// uses the legacy format.
+    ///
+    /// Public for the fuzz targets only.
+    #[cfg(feature = "json")]
+    #[doc(hidden)]
+    pub fn parse_and_detect_legacy(bytes: &[u8]) -> Result<(Self, bool), Error> {
+        match from_json_slice(bytes) {
+            Ok(policy) => Ok((policy, false)),
+            Err(e) => match from_json_slice::<LegacyPolicy>(bytes) {
+                Ok(policy) => {
+                    let policy = Self::try_from(policy).map_err(|problem| {
+                        Error::DeserializationError(de::Error::custom(problem))
+                    })?;
+                    Ok((policy, true))
                 }
-            }
+                // The `Policy` error comes first since this is the one that
+                // should be used.
+                Err(legacy) => Err(e.or_legacy(legacy)),
+            },
+        }
+    }
+
+    /// Writes this policy as JSON to the given writer, without building the
+    /// whole document in memory.
+    ///
+    /// Small writes are made: the writer should be buffered, e.g. by a
+    /// `BufWriter`.
+    #[cfg(all(feature = "std", feature = "json"))]
+    pub fn serialize_to_writer(&self, writer: impl std::io::Write) -> Result<(), Error> {
+        serde_json::to_writer(writer, self).map_err(Error::DeserializationError)
+    }
+
+    /// Reads a JSON policy from the given reader, without reading the whole
+    /// document in memory.
+    ///
+    /// Unlike `parse_and_convert`, the legacy format is not read: converting
+    /// it requires reading the document twice. The reader should be
+    /// buffered, e.g. by a `BufReader`.
+    #[cfg(all(feature = "std", feature = "json"))]
+    pub fn deserialize_from_reader(reader: impl std::io::Read) -> Result<Self, Error> {
+        serde_json::from_reader(reader).map_err(Error::DeserializationError)
+    }
+
+    /// Copies this policy into its serialized form.
+    #[cfg(test)]
+    pub(crate) fn to_unchecked(&self) -> UncheckedPolicy {
+        UncheckedPolicy {
+            version: self.version.clone(),
+            last_attribute_value: self.last_attribute_value,
+            max_attribute_creations: self.max_attribute_creations,
+            axes: self
+                .axes()
+                .map(|axis| {
+                    (
+                        axis.name().clone(),
+                        PolicyAxesParameters {
+                            attribute_names: axis.attribute_names().map(String::from).collect(),
+                            is_hierarchical: axis.is_hierarchical(),
+                        },
+                    )
+                })
+                .collect(),
+            attributes: self
+                .attribute_views()
+                .map(|attribute| (attribute.to_attribute(), attribute.entry.parameters.clone()))
+                .collect(),
+            naming_policy: self.naming_policy.clone(),
+            reclaimed_values: self.reclaimed_values.clone(),
+            #[cfg(feature = "json")]
+            unknown_fields: self.unknown_fields.clone(),
         }
     }
 
@@ -194,111 +986,600 @@ impl Policy {
             version: PolicyVersion::V1,
             last_attribute_value: 0,
             max_attribute_creations: nb_creations,
-            axes: HashMap::new(),
-            attributes: HashMap::new(),
+            axes: IndexMap::default(),
+            attributes: IndexMap::default(),
+            names: NameTable::default(),
+            naming_policy: NamingPolicy::default(),
+            reclaimed_values: Vec::new(),
+            #[cfg(feature = "json")]
+            unknown_fields: Map::new(),
         }
     }
 
-    /// Returns the remaining number of allowed attribute creations (additions + rotations).
+    /// Sets the naming policy enforced on the names of the axes and
+    /// attributes added to this policy.
+    #[inline]
+    #[must_use]
+    pub fn with_naming_policy(mut self, naming_policy: NamingPolicy) -> Self {
+        self.naming_policy = naming_policy;
+        self
+    }
+
+    /// Returns the remaining number of allowed attribute creations (additions + rotations),
+    /// including the reclaimed values.
+    ///
+    /// Counts no new value if the last attribute value is above the maximum
+    /// number of attribute creations, which deserialization rejects.
     #[inline]
     #[must_use]
     pub fn remaining_attribute_creations(&self) -> u32 {
-        self.max_attribute_creations - self.last_attribute_value
+        self.max_attribute_creations
+            .saturating_sub(self.last_attribute_value)
+            .saturating_add(self.reclaimed_values.len() as u32)
+    }
+
+    /// Returns the values retired by `Policy::reclaim_values` which have not
+    /// been given again, in increasing order.
+    #[must_use]
+    pub fn reclaimed_values(&self) -> &[u32] {
+        &self.reclaimed_values
+    }
+
+    /// Returns an unused value: a new one while the maximum number of
+    /// attribute creations is not reached, then a reclaimed one.
+    ///
+    /// The remaining number of attribute creations must have been checked.
+    fn next_value(&mut self) -> u32 {
+        if self.last_attribute_value < self.max_attribute_creations {
+            self.last_attribute_value += 1;
+            self.last_attribute_value
+        } else {
+            self.reclaimed_values
+                .pop()
+                .expect("remaining attribute creations checked")
+        }
+    }
+
+    /// Retires the values taken by the given attributes between their
+    /// creation and their current value, which may then be given again by
+    /// rotations and additions once the maximum number of attribute
+    /// creations is reached. Returns the number of reclaimed values.
+    ///
+    /// Only reclaim values once the keys derived from them are destroyed:
+    /// the attributes given them again would otherwise grant access to the
+    /// data encrypted under them. The first value of each attribute, its
+    /// identifier, and its current value are kept. The rotation epochs of the
+    /// kept values do not change, and the reclaimed ones become unknown.
+    ///
+    /// Fails, leaving this policy unchanged, if an attribute does not belong
+    /// to this policy.
+    pub fn reclaim_values(&mut self, attributes: &[Attribute]) -> Result<u32, Error> {
+        let ids = attributes
+            .iter()
+            .map(|attribute| self.attribute_id(attribute))
+            .collect::<Result<Vec<_>, _>>()?;
+        let reclaimed = self.reclaimed_values.len();
+        for id in ids {
+            if let Some(entry) = self.attributes.get_mut(&id) {
+                let parameters = &mut entry.parameters;
+                let len = parameters.values.len();
+                if len > 2 {
+                    self.reclaimed_values
+                        .extend(parameters.values.drain(1..len - 1));
+                    parameters.retired_epochs += (len - 2) as u32;
+                }
+            }
+        }
+        self.reclaimed_values.sort_unstable();
+        Ok((self.reclaimed_values.len() - reclaimed) as u32)
+    }
+
+    /// Retires the values of the attributes removed from this policy, as
+    /// `reclaim_values` does for the past values of the remaining ones.
+    /// Returns the number of reclaimed values.
+    ///
+    /// Only reclaim them once the keys derived from them are destroyed.
+    pub fn reclaim_removed_values(&mut self) -> u32 {
+        let used = self
+            .attributes
+            .values()
+            .flat_map(|entry| entry.parameters.values.iter().copied())
+            .chain(self.reclaimed_values.iter().copied())
+            .collect::<HashSet<_>>();
+        let reclaimed = self.reclaimed_values.len();
+        self.reclaimed_values
+            .extend((1..=self.last_attribute_value).filter(|value| !used.contains(value)));
+        self.reclaimed_values.sort_unstable();
+        (self.reclaimed_values.len() - reclaimed) as u32
     }
 
-    /// Returns the policy in the form of a Map where
-    ///  - the keys are the axis names
-    ///  - the values are a tuple of
-    ///     - list of attribute names for that axis
-    ///     - whether the axis hierarchical
     /// Adds the given policy axis to the policy.
+    ///
+    /// The names of the axis and of its attributes are checked against the
+    /// naming policy before any modification of the policy. Fails if the
+    /// policy already has 65536 axes.
     pub fn add_axis(&mut self, axis: PolicyAxis) -> Result<(), Error> {
-        if axis.len() > (self.max_attribute_creations - self.last_attribute_value) as usize {
+        if axis.len() > self.remaining_attribute_creations() as usize {
             return Err(Error::CapacityOverflow);
         }
-        if self.axes.get(&axis.name).is_some() {
-            return Err(Error::ExistingPolicy(axis.name));
+        let axis_name = AxisName::new_exact(&self.naming_policy.apply(&axis.name)?)?;
+        if self.axis(&axis_name).is_some() {
+            return Err(Error::ExistingPolicy(axis_name.into()));
         }
-        let mut axis_attributes = Vec::with_capacity(axis.attributes_properties.len());
+        let axis_id = u16::try_from(self.axes.len())
+            .map(AxisId)
+            .map_err(|_| Error::CapacityOverflow)?;
 
-        for properties in axis.attributes_properties {
-            self.last_attribute_value += 1;
-            axis_attributes.push(properties.name.clone());
-            let attribute = (axis.name.clone(), properties.name.clone()).into();
-            if self.attributes.get(&attribute).is_some() {
-                return Err(Error::ExistingPolicy(format!("{attribute:?}")));
+        let names = axis
+            .attributes_properties
+            .iter()
+            .map(|properties| self.naming_policy.apply(&properties.name))
+            .collect::<Result<Vec<_>, _>>()?;
+        // names matching under the naming policy share the same key
+        {
+            let mut keys = HashSet::with_capacity(names.len());
+            for name in &names {
+                let key = if self.naming_policy.case_insensitive {
+                    Cow::Owned(name.to_lowercase())
+                } else {
+                    Cow::Borrowed(&**name)
+                };
+                if !keys.insert(key) {
+                    return Err(Error::ExistingPolicy(format!(
+                        "{:?}",
+                        Attribute::new(&axis_name, name)
+                    )));
+                }
             }
-            self.attributes.insert(
-                attribute,
-                PolicyAttributesParameters {
-                    values: [self.last_attribute_value].into(),
-                    encryption_hint: properties.encryption_hint,
-                },
-            );
+        }
+
+        self.attributes.reserve(names.len());
+        let mut attribute_ids = Vec::with_capacity(names.len());
+        for (name, properties) in names.iter().zip(&axis.attributes_properties) {
+            let id = AttributeId {
+                axis: axis_id,
+                value: self.next_value(),
+            };
+            self.insert_attribute(id, name, properties.encryption_hint);
+            attribute_ids.push(id);
         }
 
         self.axes.insert(
-            axis.name,
-            PolicyAxesParameters {
-                attribute_names: axis_attributes,
+            axis_name,
+            AxisEntry {
                 is_hierarchical: axis.hierarchical,
+                attributes: attribute_ids,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Inserts an attribute whose only value is the one of its identifier.
+    /// It must then be listed by its axis.
+    fn insert_attribute(&mut self, id: AttributeId, name: &str, encryption_hint: EncryptionHint) {
+        self.attributes.insert(
+            id,
+            AttributeEntry {
+                name: name.into(),
+                parameters: PolicyAttributesParameters {
+                    values: smallvec![id.value],
+                    encryption_hint,
+                    retired_epochs: 0,
+                },
             },
         );
+        self.names.insert(&self.attributes, id);
+    }
+
+    /// Adds the given attribute to its axis, which must already belong to the
+    /// policy. The new attribute is the highest one of a hierarchical axis.
+    ///
+    /// The name of the attribute is checked against the naming policy.
+    pub fn add_attribute(
+        &mut self,
+        attribute: &Attribute,
+        encryption_hint: EncryptionHint,
+    ) -> Result<(), Error> {
+        if self.remaining_attribute_creations() == 0 {
+            return Err(Error::CapacityOverflow);
+        }
+        let name = self.naming_policy.apply(&attribute.name)?;
+        let axis = self
+            .axis(&attribute.axis)
+            .ok_or_else(|| Error::InvalidAxis(attribute.axis.to_string()))?;
+        if axis
+            .attribute_names()
+            .any(|other| self.naming_policy.matches(other, &name))
+        {
+            return Err(Error::ExistingPolicy(format!(
+                "{:?}",
+                AttributeRef::new(axis.name(), &name)
+            )));
+        }
+        let axis_id = axis.id();
 
+        let id = AttributeId {
+            axis: axis_id,
+            value: self.next_value(),
+        };
+        self.insert_attribute(id, &name, encryption_hint);
+        if let Some((_, axis)) = self.axes.get_index_mut(usize::from(axis_id.0)) {
+            axis.attributes.push(id);
+        }
+        Ok(())
+    }
+
+    /// Removes the given attribute from the policy and from its axis.
+    ///
+    /// The values given to the attribute are kept, and not given again, until
+    /// they are reclaimed by
+    /// [`reclaim_removed_values`](Self::reclaim_removed_values).
+    pub fn remove_attribute(&mut self, attribute: &Attribute) -> Result<(), Error> {
+        let id = self.attribute_id(attribute)?;
+        self.names.remove(&self.attributes, id);
+        self.attributes.shift_remove(&id);
+        if let Some((_, axis)) = self.axes.get_index_mut(usize::from(id.axis.0)) {
+            axis.attributes.retain(|other| *other != id);
+        }
+        Ok(())
+    }
+
+    /// Renames the given attribute, keeping its values, its encryption hint
+    /// and its position in its axis.
+    ///
+    /// The new name is checked against the naming policy.
+    pub fn rename_attribute(&mut self, attribute: &Attribute, new_name: &str) -> Result<(), Error> {
+        let id = self.attribute_id(attribute)?;
+        let new_name = self.naming_policy.apply(new_name)?;
+        let axis = self
+            .axis_by_id(id.axis)
+            .ok_or_else(|| Error::InvalidAxis(attribute.axis.to_string()))?;
+        if axis
+            .attributes()
+            .any(|other| other.id() != id && self.naming_policy.matches(other.name(), &new_name))
+        {
+            return Err(Error::ExistingPolicy(format!(
+                "{:?}",
+                AttributeRef::new(axis.name(), &new_name)
+            )));
+        }
+        self.names.remove(&self.attributes, id);
+        if let Some(entry) = self.attributes.get_mut(&id) {
+            entry.name = new_name.as_ref().into();
+        }
+        self.names.insert(&self.attributes, id);
         Ok(())
     }
 
+    /// Returns the axes of this policy, in the order they were added.
+    pub fn axes(&self) -> impl ExactSizeIterator<Item = AxisView<'_>> + '_ {
+        self.axes
+            .iter()
+            .enumerate()
+            .map(|(index, (name, entry))| AxisView {
+                id: AxisId::from_index(index),
+                name,
+                entry,
+                attributes: &self.attributes,
+            })
+    }
+
+    /// Retrieves the axis with the given name, ignoring case if the naming
+    /// policy is case-insensitive. The name is normalized before lookup.
+    #[must_use]
+    pub fn axis(&self, name: &str) -> Option<AxisView<'_>> {
+        let name = normalize(name);
+        let index = self.axes.get_index_of(name.as_ref()).or_else(|| {
+            if self.naming_policy.case_insensitive {
+                self.axes
+                    .keys()
+                    .position(|axis_name| self.naming_policy.matches(axis_name, &name))
+            } else {
+                None
+            }
+        })?;
+        self.axis_by_id(AxisId::from_index(index))
+    }
+
+    /// Retrieves the axis with the given identifier.
+    #[must_use]
+    pub fn axis_by_id(&self, id: AxisId) -> Option<AxisView<'_>> {
+        let (name, entry) = self.axes.get_index(usize::from(id.0))?;
+        Some(AxisView {
+            id,
+            name,
+            entry,
+            attributes: &self.attributes,
+        })
+    }
+
+    /// Returns the name of the axis with the given identifier, which must
+    /// belong to this policy.
+    fn axis_name(&self, id: AxisId) -> &AxisName {
+        self.axes
+            .get_index(usize::from(id.0))
+            .map(|(name, _)| name)
+            .expect("the axes of the attributes belong to their policy")
+    }
+
+    /// Returns the attributes of this policy, in the order they were added.
+    pub fn attribute_views(&self) -> impl ExactSizeIterator<Item = AttributeView<'_>> + '_ {
+        self.attributes.iter().map(|(id, entry)| AttributeView {
+            id: *id,
+            axis: self.axis_name(id.axis),
+            entry,
+        })
+    }
+
+    /// Retrieves the attribute with the given identifier.
+    #[must_use]
+    pub fn attribute_view(&self, id: AttributeId) -> Option<AttributeView<'_>> {
+        let entry = self.attributes.get(&id)?;
+        Some(AttributeView {
+            id,
+            axis: self.axis_name(id.axis),
+            entry,
+        })
+    }
+
+    /// Retrieves the attribute with the given axis and name, as they are
+    /// written in this policy.
+    pub(crate) fn exact_attribute(&self, attribute: AttributeRef<'_>) -> Option<AttributeView<'_>> {
+        let index = self.axes.get_index_of(attribute.axis)?;
+        let id = self
+            .names
+            .find(&self.attributes, AxisId::from_index(index), attribute.name)?;
+        self.attribute_view(id)
+    }
+
+    /// Retrieves the given attribute, ignoring case if the naming policy is
+    /// case-insensitive. The attribute is normalized before lookup.
+    pub(crate) fn find_attribute(&self, attribute: AttributeRef<'_>) -> Option<AttributeView<'_>> {
+        let (axis, name) = (normalize(attribute.axis), normalize(attribute.name));
+        self.exact_attribute(AttributeRef::new(&axis, &name))
+            .or_else(|| {
+                if self.naming_policy.case_insensitive {
+                    self.attribute_views().find(|attr| {
+                        self.naming_policy.matches(attr.axis(), &axis)
+                            && self.naming_policy.matches(attr.name(), &name)
+                    })
+                } else {
+                    None
+                }
+            })
+    }
+
+    /// Returns the given attribute as it is written in this policy.
+    ///
+    /// This is the attribute itself unless the naming policy is
+    /// case-insensitive.
+    pub fn canonical_attribute<'a>(
+        &self,
+        attribute: impl Into<AttributeRef<'a>>,
+    ) -> Result<Attribute, Error> {
+        let attribute = attribute.into();
+        self.find_attribute(attribute)
+            .map(|attr| attr.to_attribute())
+            .ok_or_else(|| Error::AttributeNotFound(attribute.to_string()))
+    }
+
+    /// Converts a boolean expression into an `AccessPolicy` whose attributes
+    /// are written as in this policy.
+    ///
+    /// All attributes of the expression must belong to this policy.
+    pub fn parse_access_policy(&self, boolean_expression: &str) -> Result<AccessPolicy, Error> {
+        self.canonicalize_access_policy(&AccessPolicy::from_boolean_expression(boolean_expression)?)
+    }
+
+    fn canonicalize_access_policy(
+        &self,
+        access_policy: &AccessPolicy,
+    ) -> Result<AccessPolicy, Error> {
+        Ok(match access_policy {
+            AccessPolicy::Attr(attr) => AccessPolicy::Attr(self.canonical_attribute(attr)?),
+            AccessPolicy::And(left, right) => {
+                self.canonicalize_access_policy(left)? & self.canonicalize_access_policy(right)?
+            }
+            AccessPolicy::Or(left, right) => {
+                self.canonicalize_access_policy(left)? | self.canonicalize_access_policy(right)?
+            }
+            AccessPolicy::All => AccessPolicy::All,
+        })
+    }
+
+    /// Returns the number of attribute combinations the given access policy
+    /// expands to, without building them. This is the length of the vector
+    /// returned by `AccessPolicy::to_attribute_combinations()`.
+    ///
+    /// The count saturates at `u64::MAX`.
+    ///
+    /// - `access_policy`               : access policy to expand
+    /// - `follow_hierarchical_axes`    : set to `true` to combine lower axis attributes
+    pub fn count_combinations(
+        &self,
+        access_policy: &AccessPolicy,
+        follow_hierarchical_axes: bool,
+    ) -> Result<u64, Error> {
+        match access_policy {
+            AccessPolicy::Attr(attr) => {
+                let axis = self
+                    .axis(&attr.axis)
+                    .ok_or_else(|| Error::InvalidAxis(attr.axis.to_string()))?;
+                if !(axis.is_hierarchical() && follow_hierarchical_axes) {
+                    return Ok(1);
+                }
+                let id = self
+                    .find_attribute(attr.as_attribute_ref())
+                    .map(|attr| attr.id());
+                // the attribute itself and all attributes below it
+                let lower_attributes = axis
+                    .attribute_ids()
+                    .iter()
+                    .take_while(|lower| Some(**lower) != id)
+                    .count();
+                Ok(1 + lower_attributes as u64)
+            }
+            AccessPolicy::And(left, right) => {
+                let (left, right) = self.count_operands(left, right, follow_hierarchical_axes)?;
+                Ok(left.saturating_mul(right))
+            }
+            AccessPolicy::Or(left, right) => {
+                let (left, right) = self.count_operands(left, right, follow_hierarchical_axes)?;
+                Ok(left.saturating_add(right))
+            }
+            AccessPolicy::All => Ok(1),
+        }
+    }
+
+    /// Counts the combinations of both operands of a binary access policy.
+    fn count_operands(
+        &self,
+        left: &AccessPolicy,
+        right: &AccessPolicy,
+        follow_hierarchical_axes: bool,
+    ) -> Result<(u64, u64), Error> {
+        let (left, right) = join(
+            || self.count_combinations(left, follow_hierarchical_axes),
+            || self.count_combinations(right, follow_hierarchical_axes),
+        );
+        Ok((left?, right?))
+    }
+
     /// Rotates an attribute, changing its underlying value with an unused
     /// value.
     pub fn rotate(&mut self, attr: &Attribute) -> Result<(), Error> {
-        if self.last_attribute_value == self.max_attribute_creations {
-            Err(Error::CapacityOverflow)
-        } else if let Some(attribute_parameters) = self.attributes.get_mut(attr) {
-            self.last_attribute_value += 1;
-            attribute_parameters.values.push(self.last_attribute_value);
-            Ok(())
-        } else {
-            Err(Error::AttributeNotFound(attr.to_string()))
+        if self.remaining_attribute_creations() == 0 {
+            return Err(Error::CapacityOverflow);
+        }
+        let id = self.attribute_id(attr)?;
+        let value = self.next_value();
+        if let Some(entry) = self.attributes.get_mut(&id) {
+            entry.parameters.values.push(value);
         }
+        Ok(())
+    }
+
+    /// Returns the identifier of the given attribute.
+    pub fn attribute_id<'a>(
+        &self,
+        attribute: impl Into<AttributeRef<'a>>,
+    ) -> Result<AttributeId, Error> {
+        let attribute = attribute.into();
+        self.find_attribute(attribute)
+            .map(|attribute| attribute.id())
+            .ok_or_else(|| Error::AttributeNotFound(attribute.to_string()))
+    }
+
+    /// Retrieves the attribute with the given identifier.
+    pub fn attribute_from_id(&self, id: AttributeId) -> Result<Attribute, Error> {
+        self.attribute_view(id)
+            .map(|attribute| attribute.to_attribute())
+            .ok_or_else(|| Error::AttributeNotFound(id.to_string()))
+    }
+
+    /// Returns the identifiers of all the attributes of this policy, in the
+    /// order they were added.
+    pub fn attribute_ids(&self) -> impl ExactSizeIterator<Item = AttributeId> + '_ {
+        self.attributes.keys().copied()
     }
 
     /// Returns the list of Attributes of this Policy.
     #[inline]
     #[must_use]
     pub fn attributes(&self) -> Vec<Attribute> {
-        self.attributes.keys().cloned().collect::<Vec<Attribute>>()
+        self.attribute_views()
+            .map(|attribute| attribute.to_attribute())
+            .collect()
+    }
+
+    /// Sorts the given attributes as in this policy: by axis, in the order
+    /// the axes were added, then by rank within their axis (from the lowest to
+    /// the highest attribute for a hierarchical axis).
+    ///
+    /// Attributes not belonging to this policy are placed last, sorted
+    /// lexicographically.
+    pub fn sort_attributes(&self, attributes: &mut [Attribute]) {
+        attributes.sort_by_cached_key(|attribute| {
+            match self.attribute_position(attribute.as_attribute_ref()) {
+                Some((axis_rank, rank)) => (false, axis_rank, rank, None),
+                None => (true, AxisId(0), 0, Some(attribute.clone())),
+            }
+        });
+    }
+
+    /// Returns the identifier of the axis of the given attribute and the
+    /// position of the attribute within its axis.
+    fn attribute_position(&self, attribute: AttributeRef<'_>) -> Option<(AxisId, usize)> {
+        let id = self.find_attribute(attribute)?.id();
+        let rank = self
+            .axis_by_id(id.axis)?
+            .attribute_ids()
+            .iter()
+            .position(|other| *other == id)?;
+        Some((id.axis, rank))
     }
 
     /// Returns the list of all values given to this attribute over rotations.
     /// The current value is returned first
     #[inline]
-    pub fn attribute_values(&self, attribute: &Attribute) -> Result<Vec<u32>, Error> {
-        self.attributes
-            .get(attribute)
-            .map(|attribute_parameters| attribute_parameters.values.iter().rev().copied().collect())
+    pub fn attribute_values<'a>(
+        &self,
+        attribute: impl Into<AttributeRef<'a>>,
+    ) -> Result<Vec<u32>, Error> {
+        let attribute = attribute.into();
+        self.find_attribute(attribute)
+            .map(|attribute| attribute.values().iter().rev().copied().collect())
             .ok_or_else(|| Error::AttributeNotFound(attribute.to_string()))
     }
 
+    /// Returns the list of all values given to this attribute over rotations,
+    /// together with the rotation epoch at which they were given. The current
+    /// value is returned first.
+    pub fn attribute_values_with_epochs<'a>(
+        &self,
+        attribute: impl Into<AttributeRef<'a>>,
+    ) -> Result<Vec<(u32, RotationEpoch)>, Error> {
+        let attribute = attribute.into();
+        self.find_attribute(attribute)
+            .map(|attribute| attribute.values_with_epochs().rev().collect())
+            .ok_or_else(|| Error::AttributeNotFound(attribute.to_string()))
+    }
+
+    /// Retrieves the value of an attribute at the given rotation epoch.
+    pub fn attribute_value_at_epoch<'a>(
+        &self,
+        attribute: impl Into<AttributeRef<'a>>,
+        epoch: RotationEpoch,
+    ) -> Result<u32, Error> {
+        let attribute = attribute.into();
+        self.find_attribute(attribute)
+            .ok_or_else(|| Error::AttributeNotFound(attribute.to_string()))?
+            .value_at_epoch(epoch)
+            .ok_or_else(|| Error::UnknownEpoch(attribute.to_string(), epoch.0))
+    }
+
     /// Returns the hybridization hint of the given attribute.
     #[inline]
-    pub fn attribute_hybridization_hint(
+    pub fn attribute_hybridization_hint<'a>(
         &self,
-        attribute: &Attribute,
+        attribute: impl Into<AttributeRef<'a>>,
     ) -> Result<EncryptionHint, Error> {
-        self.attributes
-            .get(attribute)
-            .map(|attribute_parameters| attribute_parameters.encryption_hint)
+        let attribute = attribute.into();
+        self.find_attribute(attribute)
+            .map(|attribute| attribute.encryption_hint())
             .ok_or_else(|| Error::AttributeNotFound(attribute.to_string()))
     }
 
     /// Retrieves the current value of an attribute.
     #[inline]
-    pub fn attribute_current_value(&self, attribute: &Attribute) -> Result<u32, Error> {
-        self.attributes
-            .get(attribute)
-            .map(|attribute_parameters| {
-                attribute_parameters.values[attribute_parameters.values.len() - 1]
-            })
+    pub fn attribute_current_value<'a>(
+        &self,
+        attribute: impl Into<AttributeRef<'a>>,
+    ) -> Result<u32, Error> {
+        let attribute = attribute.into();
+        self.find_attribute(attribute)
+            .map(|attribute| attribute.current_value())
             .ok_or_else(|| Error::AttributeNotFound(attribute.to_string()))
     }
 }
@@ -0,0 +1,465 @@
+use crate::{
+    attribute::AttributeKey,
+    consistency::check_capacity,
+    naming_policy::normalize,
+    policy::{
+        deserialize_attributes, AttributeRecordKey, PolicyAttributesParameters,
+        PolicyAxesParameters, PolicyVersion, UncheckedPolicy,
+    },
+    AccessPolicy, Attribute, AttributeRef, AxisName, EncryptionHint, Error, IndexMap, NamingPolicy,
+    Policy, PolicyProblem,
+};
+use alloc::{
+    borrow::{Cow, ToOwned},
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{
+    borrow::Borrow,
+    fmt::{Debug, Display},
+    hash::{Hash, Hasher},
+};
+use serde::{
+    de::{self, value::MapAccessDeserializer, MapAccess, Visitor},
+    Deserialize, Deserializer,
+};
+use serde_json::Map;
+
+/// Attribute read from serialized data, as an `{ axis, name }` record or
+/// written `Axis::Name`.
+///
+/// Its axis and name are borrowed from the serialized data unless they
+/// contain JSON or `Axis::Name` escapes, in which case they are copied.
+#[derive(Clone)]
+pub struct SerializedAttribute<'a> {
+    axis: Cow<'a, str>,
+    name: Cow<'a, str>,
+}
+
+impl<'a> SerializedAttribute<'a> {
+    /// Parses an attribute as `Attribute::parse_exact` does, borrowing the
+    /// given text if it contains no escape.
+    fn parse(text: Cow<'a, str>) -> Result<Self, Error> {
+        if text.contains('\\') {
+            return Ok(Self::from(&Attribute::parse_exact(&text)?));
+        }
+        let separator = text.find("::").ok_or_else(|| {
+            Error::InvalidAttribute(format!("at least one separator '::' expected in {text}"))
+        })?;
+        let (axis, name) = (&text[..separator], &text[separator + 2..]);
+        if name.contains("::") {
+            return Err(Error::InvalidAttribute(format!(
+                "separator '::' expected only once in {text}"
+            )));
+        }
+        if axis.trim().is_empty() || name.trim().is_empty() {
+            return Err(Error::InvalidAttribute(format!(
+                "empty axis or empty name in {text}"
+            )));
+        }
+        Ok(match text {
+            Cow::Borrowed(text) => Self {
+                axis: Cow::Borrowed(&text[..separator]),
+                name: Cow::Borrowed(&text[separator + 2..]),
+            },
+            Cow::Owned(_) => Self {
+                axis: Cow::Owned(axis.to_owned()),
+                name: Cow::Owned(name.to_owned()),
+            },
+        })
+    }
+
+    #[must_use]
+    pub fn axis(&self) -> &str {
+        &self.axis
+    }
+
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Borrows this attribute.
+    #[must_use]
+    pub fn as_attribute_ref(&self) -> AttributeRef<'_> {
+        AttributeRef::new(self.axis(), self.name())
+    }
+
+    /// Copies the axis and the name into an owned `Attribute`.
+    #[must_use]
+    pub fn to_attribute(&self) -> Attribute {
+        Attribute::new(self.axis(), self.name())
+    }
+}
+
+impl From<&Attribute> for SerializedAttribute<'_> {
+    fn from(attribute: &Attribute) -> Self {
+        Self {
+            axis: Cow::Owned(attribute.axis.to_string()),
+            name: Cow::Owned(attribute.name.to_string()),
+        }
+    }
+}
+
+impl<'de: 'a, 'a> AttributeRecordKey<'de> for SerializedAttribute<'a> {
+    fn from_record(axis: Cow<'de, str>, name: Cow<'de, str>) -> Result<Self, Error> {
+        if axis.trim().is_empty() || name.trim().is_empty() {
+            return Err(Error::InvalidAttribute(format!(
+                "empty axis or empty name in {axis}::{name}"
+            )));
+        }
+        Ok(Self { axis, name })
+    }
+}
+
+impl<'a> From<&'a SerializedAttribute<'_>> for AttributeRef<'a> {
+    fn from(attribute: &'a SerializedAttribute<'_>) -> Self {
+        attribute.as_attribute_ref()
+    }
+}
+
+impl AttributeKey for SerializedAttribute<'_> {
+    fn key(&self) -> AttributeRef<'_> {
+        self.as_attribute_ref()
+    }
+}
+
+impl<'a: 'b, 'b> Borrow<dyn AttributeKey + 'b> for SerializedAttribute<'a> {
+    fn borrow(&self) -> &(dyn AttributeKey + 'b) {
+        self
+    }
+}
+
+// Must hash as `Attribute` does: both hash the axis, then the name.
+impl Hash for SerializedAttribute<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_attribute_ref().hash(state);
+    }
+}
+
+impl PartialEq for SerializedAttribute<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_attribute_ref() == other.as_attribute_ref()
+    }
+}
+
+impl Eq for SerializedAttribute<'_> {}
+
+impl Debug for SerializedAttribute<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        Debug::fmt(&self.as_attribute_ref(), f)
+    }
+}
+
+/// Writes the attribute as `Axis::Name`, with the same escaping as
+/// `Attribute`.
+impl Display for SerializedAttribute<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        Display::fmt(&self.as_attribute_ref(), f)
+    }
+}
+
+struct SerializedAttributeVisitor;
+
+impl<'de> Visitor<'de> for SerializedAttributeVisitor {
+    type Value = SerializedAttribute<'de>;
+
+    fn expecting(&self, formatter: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        formatter.write_str("an `Axis::Name` string or an `{ axis, name }` structure")
+    }
+
+    fn visit_borrowed_str<E: de::Error>(self, v: &'de str) -> Result<Self::Value, E> {
+        SerializedAttribute::parse(Cow::Borrowed(v)).map_err(E::custom)
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        SerializedAttribute::parse(Cow::Owned(v.to_owned())).map_err(E::custom)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+        Attribute::deserialize(MapAccessDeserializer::new(map))
+            .map(|attribute| SerializedAttribute::from(&attribute))
+    }
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for SerializedAttribute<'a> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(SerializedAttributeVisitor)
+        } else {
+            deserializer.deserialize_str(SerializedAttributeVisitor)
+        }
+    }
+}
+
+/// Name borrowed from serialized data unless it contains escapes.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Name<'a>(Cow<'a, str>);
+
+impl Borrow<str> for Name<'_> {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+struct NameVisitor;
+
+impl<'de> Visitor<'de> for NameVisitor {
+    type Value = Name<'de>;
+
+    fn expecting(&self, formatter: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        formatter.write_str("a string")
+    }
+
+    fn visit_borrowed_str<E: de::Error>(self, v: &'de str) -> Result<Self::Value, E> {
+        Ok(Name(Cow::Borrowed(v)))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(Name(Cow::Owned(v.to_owned())))
+    }
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for Name<'a> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_str(NameVisitor)
+    }
+}
+
+/// Axis name, checked as `AxisName` is.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct AxisNameRef<'a>(Name<'a>);
+
+impl Borrow<str> for AxisNameRef<'_> {
+    fn borrow(&self) -> &str {
+        &self.0 .0
+    }
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for AxisNameRef<'a> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = Name::deserialize(deserializer)?;
+        if name.0.trim().is_empty() || name.0.contains("::") {
+            // reports the same error as `AxisName`
+            AxisName::new_exact(&name.0).map_err(de::Error::custom)?;
+        }
+        Ok(Self(name))
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct PolicyAxisRef<'a> {
+    #[serde(borrow)]
+    attribute_names: Vec<Name<'a>>,
+    is_hierarchical: bool,
+}
+
+/// Read-only view of a JSON policy, borrowing the names of its axes and
+/// attributes from the serialized policy instead of copying them.
+///
+/// Deserializing a view allocates much less than deserializing a `Policy`,
+/// for callers which only look attributes up. Only the current format is
+/// read: `Policy::parse_and_convert` also reads the legacy one.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PolicyRef<'a> {
+    version: PolicyVersion,
+    last_attribute_value: u32,
+    max_attribute_creations: u32,
+    #[serde(borrow)]
+    axes: IndexMap<AxisNameRef<'a>, PolicyAxisRef<'a>>,
+    #[serde(borrow, deserialize_with = "deserialize_attributes")]
+    attributes: IndexMap<SerializedAttribute<'a>, PolicyAttributesParameters>,
+    #[serde(default)]
+    naming_policy: NamingPolicy,
+    #[serde(default)]
+    reclaimed_values: Vec<u32>,
+}
+
+impl<'a> PolicyRef<'a> {
+    /// Reads a view of the given JSON policy.
+    ///
+    /// Unlike `Policy::parse_and_convert`, the structure of the policy is not
+    /// checked, only its capacity: `to_policy` checks it.
+    pub fn from_json(bytes: &'a [u8]) -> Result<Self, Error> {
+        let policy: Self = serde_json::from_slice(bytes).map_err(Error::DeserializationError)?;
+        check_capacity(policy.last_attribute_value, policy.max_attribute_creations)
+            .map_err(|problem| Error::DeserializationError(de::Error::custom(problem)))?;
+        Ok(policy)
+    }
+
+    /// Returns the maximum number of attribute creations (revocations +
+    /// additions) allowed.
+    #[must_use]
+    pub fn max_attribute_creations(&self) -> u32 {
+        self.max_attribute_creations
+    }
+
+    /// Returns the axes of the policy and whether they are hierarchical, in
+    /// the order of the serialized policy.
+    pub fn axes(&self) -> impl Iterator<Item = (&str, bool)> {
+        self.axes
+            .iter()
+            .map(|(axis, parameters)| (axis.borrow(), parameters.is_hierarchical))
+    }
+
+    /// Returns the attributes of the policy, in the order of the serialized
+    /// policy.
+    pub fn attributes(&self) -> impl Iterator<Item = AttributeRef<'_>> {
+        self.attributes
+            .keys()
+            .map(SerializedAttribute::as_attribute_ref)
+    }
+
+    /// Retrieves the given attribute as `Policy` does: ignoring case if the
+    /// naming policy is case-insensitive, after normalizing it.
+    fn find_attribute(
+        &self,
+        attribute: AttributeRef<'_>,
+    ) -> Option<(&SerializedAttribute<'a>, &PolicyAttributesParameters)> {
+        let (axis, name) = (normalize(attribute.axis), normalize(attribute.name));
+        let attribute = AttributeRef::new(&axis, &name);
+        self.attributes
+            .get_key_value(&attribute as &dyn AttributeKey)
+            .or_else(|| {
+                if self.naming_policy.case_insensitive {
+                    self.attributes.iter().find(|(attr, _)| {
+                        self.naming_policy.matches(attr.axis(), attribute.axis)
+                            && self.naming_policy.matches(attr.name(), attribute.name)
+                    })
+                } else {
+                    None
+                }
+            })
+    }
+
+    /// Returns the list of all values given to this attribute over rotations.
+    /// The current value is returned first.
+    pub fn attribute_values<'b>(
+        &self,
+        attribute: impl Into<AttributeRef<'b>>,
+    ) -> Result<Vec<u32>, Error> {
+        let attribute = attribute.into();
+        self.find_attribute(attribute)
+            .map(|(_, attribute_parameters)| {
+                attribute_parameters.values.iter().rev().copied().collect()
+            })
+            .ok_or_else(|| Error::AttributeNotFound(attribute.to_string()))
+    }
+
+    /// Retrieves the current value of an attribute.
+    pub fn attribute_current_value<'b>(
+        &self,
+        attribute: impl Into<AttributeRef<'b>>,
+    ) -> Result<u32, Error> {
+        let attribute = attribute.into();
+        self.find_attribute(attribute)
+            .and_then(|(_, attribute_parameters)| attribute_parameters.values.last().copied())
+            .ok_or_else(|| Error::AttributeNotFound(attribute.to_string()))
+    }
+
+    /// Returns the hybridization hint of the given attribute.
+    pub fn attribute_hybridization_hint<'b>(
+        &self,
+        attribute: impl Into<AttributeRef<'b>>,
+    ) -> Result<EncryptionHint, Error> {
+        let attribute = attribute.into();
+        self.find_attribute(attribute)
+            .map(|(_, attribute_parameters)| attribute_parameters.encryption_hint)
+            .ok_or_else(|| Error::AttributeNotFound(attribute.to_string()))
+    }
+
+    /// Copies this view into a `Policy`.
+    ///
+    /// Fails if the structure of the policy is corrupted, e.g. if an
+    /// attribute is missing from the list of its axis.
+    pub fn to_policy(&self) -> Result<Policy, Error> {
+        UncheckedPolicy {
+            version: self.version.clone(),
+            last_attribute_value: self.last_attribute_value,
+            max_attribute_creations: self.max_attribute_creations,
+            axes: self
+                .axes
+                .iter()
+                .map(|(axis, parameters)| {
+                    (
+                        AxisName::new_unchecked(axis.borrow()),
+                        PolicyAxesParameters {
+                            attribute_names: parameters
+                                .attribute_names
+                                .iter()
+                                .map(|name| String::from(&*name.0))
+                                .collect(),
+                            is_hierarchical: parameters.is_hierarchical,
+                        },
+                    )
+                })
+                .collect(),
+            attributes: self
+                .attributes
+                .iter()
+                .map(|(attribute, parameters)| (attribute.to_attribute(), parameters.clone()))
+                .collect(),
+            naming_policy: self.naming_policy.clone(),
+            reclaimed_values: self.reclaimed_values.clone(),
+            unknown_fields: Map::new(),
+        }
+        .try_into()
+        .map_err(|problem: PolicyProblem| Error::DeserializationError(de::Error::custom(problem)))
+    }
+}
+
+/// Read-only view of a JSON access policy, borrowing its attributes from the
+/// serialized access policy instead of copying them.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub enum AccessPolicyRef<'a> {
+    Attr(#[serde(borrow)] SerializedAttribute<'a>),
+    And(
+        #[serde(borrow)] Box<AccessPolicyRef<'a>>,
+        #[serde(borrow)] Box<AccessPolicyRef<'a>>,
+    ),
+    Or(
+        #[serde(borrow)] Box<AccessPolicyRef<'a>>,
+        #[serde(borrow)] Box<AccessPolicyRef<'a>>,
+    ),
+    All,
+}
+
+impl<'a> AccessPolicyRef<'a> {
+    /// Reads a view of the given JSON access policy.
+    pub fn from_json(bytes: &'a [u8]) -> Result<Self, Error> {
+        serde_json::from_slice(bytes).map_err(Error::DeserializationError)
+    }
+
+    /// Retrieves all the attributes present in this access policy, sorted.
+    #[must_use]
+    pub fn attributes(&self) -> Vec<AttributeRef<'_>> {
+        let mut attributes = Vec::new();
+        self.push_attributes(&mut attributes);
+        attributes.sort_unstable();
+        attributes
+    }
+
+    fn push_attributes<'b>(&'b self, attributes: &mut Vec<AttributeRef<'b>>) {
+        match self {
+            Self::Attr(attribute) => attributes.push(attribute.as_attribute_ref()),
+            Self::And(left, right) | Self::Or(left, right) => {
+                left.push_attributes(attributes);
+                right.push_attributes(attributes);
+            }
+            Self::All => {}
+        }
+    }
+
+    /// Copies this view into an `AccessPolicy`.
+    #[must_use]
+    pub fn to_access_policy(&self) -> AccessPolicy {
+        match self {
+            Self::Attr(attribute) => AccessPolicy::Attr(attribute.to_attribute()),
+            Self::And(left, right) => left.to_access_policy() & right.to_access_policy(),
+            Self::Or(left, right) => left.to_access_policy() | right.to_access_policy(),
+            Self::All => AccessPolicy::All,
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use crate::{EncryptionHint, Error, Policy, PolicyAxis};
+use alloc::{format, string::String, vec::Vec};
+use core::ops::RangeInclusive;
+use rand::Rng;
+
+/// Parameters of the policies generated by `Policy::random()`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RandomPolicyParameters {
+    /// Number of axes
+    pub axes: RangeInclusive<usize>,
+    /// Number of attributes per axis
+    pub attributes_per_axis: RangeInclusive<usize>,
+    /// Probability for an axis to be hierarchical
+    pub hierarchy_probability: f64,
+    /// Probability for an attribute to hint hybridized encryption
+    pub hybridization_probability: f64,
+    /// Number of attribute creations left once all axes are added
+    pub remaining_creations: u32,
+}
+
+impl Default for RandomPolicyParameters {
+    fn default() -> Self {
+        Self {
+            axes: 1..=5,
+            attributes_per_axis: 1..=10,
+            hierarchy_probability: 0.5,
+            hybridization_probability: 0.1,
+            remaining_creations: 100,
+        }
+    }
+}
+
+impl Policy {
+    /// Generates a random policy with the given parameters.
+    ///
+    /// Axes are named `Axis <i>` and attributes `Attribute <j>`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a range of the parameters is empty or a probability is not
+    /// in `[0, 1]`.
+    pub fn random(rng: &mut impl Rng, parameters: &RandomPolicyParameters) -> Result<Self, Error> {
+        let nb_axes = rng.random_range(parameters.axes.clone());
+        let axes = (0..nb_axes)
+            .map(|i| {
+                let nb_attributes = rng.random_range(parameters.attributes_per_axis.clone());
+                let attributes = (0..nb_attributes)
+                    .map(|j| {
+                        let hint = if rng.random_bool(parameters.hybridization_probability) {
+                            EncryptionHint::Hybridized
+                        } else {
+                            EncryptionHint::Classic
+                        };
+                        (format!("Attribute {j}"), hint)
+                    })
+                    .collect::<Vec<(String, EncryptionHint)>>();
+                let hierarchical = rng.random_bool(parameters.hierarchy_probability);
+                (format!("Axis {i}"), attributes, hierarchical)
+            })
+            .collect::<Vec<_>>();
+
+        let nb_attributes = axes
+            .iter()
+            .map(|(_, attributes, _)| attributes.len() as u32)
+            .sum::<u32>();
+        let mut policy = Self::new(
+            nb_attributes
+                .checked_add(parameters.remaining_creations)
+                .ok_or(Error::CapacityOverflow)?,
+        );
+        for (name, attributes, hierarchical) in &axes {
+            policy.add_axis(PolicyAxis::new(
+                name,
+                attributes
+                    .iter()
+                    .map(|(name, hint)| (name.as_str(), *hint))
+                    .collect(),
+                *hierarchical,
+            ))?;
+        }
+        Ok(policy)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_random_policy() -> Result<(), Error> {
+        let mut rng = StdRng::seed_from_u64(0);
+        let parameters = RandomPolicyParameters {
+            axes: 2..=4,
+            attributes_per_axis: 50..=100,
+            ..RandomPolicyParameters::default()
+        };
+        for _ in 0..10 {
+            let policy = Policy::random(&mut rng, &parameters)?;
+            assert!(parameters.axes.contains(&policy.axes.len()));
+            for axis in policy.axes() {
+                assert!(parameters.attributes_per_axis.contains(&axis.len()));
+            }
+            assert_eq!(
+                parameters.remaining_creations,
+                policy.remaining_attribute_creations()
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,298 @@
+//! Storage of the axes and attributes of a policy.
+//!
+//! Attributes are keyed by their `AttributeId`: an axis lists the identifiers
+//! of its attributes, and each name is stored once, in the entry of its
+//! attribute. The identifiers of the attributes are looked up by axis and name
+//! in a `NameTable`, which hashes the names without copying them.
+
+use alloc::{sync::Arc, vec::Vec};
+use core::{fmt::Debug, hash::BuildHasher};
+
+use hashbrown::{DefaultHashBuilder, HashTable};
+
+use crate::{
+    policy::{AttributeId, AxisId, PolicyAttributesParameters, RotationEpoch},
+    Attribute, AttributeRef, AxisName, EncryptionHint, IndexMap,
+};
+
+/// Axis of a policy.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) struct AxisEntry {
+    pub(crate) is_hierarchical: bool,
+    /// Attributes of the axis, from the lowest to the highest one for a
+    /// hierarchical axis.
+    pub(crate) attributes: Vec<AttributeId>,
+}
+
+/// Attribute of a policy.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) struct AttributeEntry {
+    pub(crate) name: Arc<str>,
+    pub(crate) parameters: PolicyAttributesParameters,
+}
+
+/// Identifiers of the attributes of a policy, looked up by axis and name.
+///
+/// The names are not copied: the table hashes them from the attribute
+/// entries, which must be given to each call.
+#[derive(Clone, Default)]
+pub(crate) struct NameTable {
+    ids: HashTable<AttributeId>,
+    hasher: DefaultHashBuilder,
+}
+
+impl NameTable {
+    fn hash(&self, axis: AxisId, name: &str) -> u64 {
+        self.hasher.hash_one((axis, name))
+    }
+
+    /// Returns the identifier of the attribute of the given axis with the
+    /// given name.
+    pub(crate) fn find(
+        &self,
+        attributes: &IndexMap<AttributeId, AttributeEntry>,
+        axis: AxisId,
+        name: &str,
+    ) -> Option<AttributeId> {
+        self.ids
+            .find(self.hash(axis, name), |id| {
+                id.axis == axis && attributes.get(id).is_some_and(|entry| *entry.name == *name)
+            })
+            .copied()
+    }
+
+    /// Adds the given attribute, whose entry must already be inserted.
+    pub(crate) fn insert(
+        &mut self,
+        attributes: &IndexMap<AttributeId, AttributeEntry>,
+        id: AttributeId,
+    ) {
+        let Some(entry) = attributes.get(&id) else {
+            return;
+        };
+        let hash = self.hash(id.axis, &entry.name);
+        let Self { ids, hasher } = self;
+        ids.insert_unique(hash, id, |id| {
+            let name = attributes.get(id).map_or("", |entry| &entry.name);
+            hasher.hash_one((id.axis, name))
+        });
+    }
+
+    /// Removes the given attribute, whose entry must not be modified yet.
+    pub(crate) fn remove(
+        &mut self,
+        attributes: &IndexMap<AttributeId, AttributeEntry>,
+        id: AttributeId,
+    ) {
+        let Some(entry) = attributes.get(&id) else {
+            return;
+        };
+        if let Ok(entry) = self
+            .ids
+            .find_entry(self.hash(id.axis, &entry.name), |other| *other == id)
+        {
+            entry.remove();
+        }
+    }
+}
+
+impl Debug for NameTable {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("NameTable")
+            .field("len", &self.ids.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Axis of a policy, as returned by `Policy::axes`.
+#[derive(Copy, Clone)]
+pub struct AxisView<'a> {
+    pub(crate) id: AxisId,
+    pub(crate) name: &'a AxisName,
+    pub(crate) entry: &'a AxisEntry,
+    pub(crate) attributes: &'a IndexMap<AttributeId, AttributeEntry>,
+}
+
+impl<'a> AxisView<'a> {
+    /// Returns the identifier of this axis.
+    #[must_use]
+    pub fn id(&self) -> AxisId {
+        self.id
+    }
+
+    /// Returns the name of this axis.
+    #[must_use]
+    pub fn name(&self) -> &'a AxisName {
+        self.name
+    }
+
+    /// Returns `true` if this axis is hierarchical.
+    #[must_use]
+    pub fn is_hierarchical(&self) -> bool {
+        self.entry.is_hierarchical
+    }
+
+    /// Returns the number of attributes of this axis.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entry.attributes.len()
+    }
+
+    /// Returns `true` if this axis has no attribute.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entry.attributes.is_empty()
+    }
+
+    /// Returns the identifiers of the attributes of this axis, from the
+    /// lowest to the highest one for a hierarchical axis.
+    #[must_use]
+    pub fn attribute_ids(&self) -> &'a [AttributeId] {
+        &self.entry.attributes
+    }
+
+    /// Returns the attributes of this axis, from the lowest to the highest
+    /// one for a hierarchical axis.
+    pub fn attributes(&self) -> impl ExactSizeIterator<Item = AttributeView<'a>> + 'a {
+        let (name, attributes) = (self.name, self.attributes);
+        self.entry.attributes.iter().map(move |id| AttributeView {
+            id: *id,
+            axis: name,
+            entry: &attributes[id],
+        })
+    }
+
+    /// Returns the names of the attributes of this axis, in the order of
+    /// `attributes`.
+    pub fn attribute_names(&self) -> impl ExactSizeIterator<Item = &'a str> + 'a {
+        self.attributes().map(|attribute| attribute.name())
+    }
+}
+
+impl Debug for AxisView<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("AxisView")
+            .field("id", &self.id)
+            .field("name", self.name)
+            .field("is_hierarchical", &self.entry.is_hierarchical)
+            .field("attributes", &self.attribute_names().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+/// Attribute of a policy, as returned by `Policy::attribute_views`.
+#[derive(Copy, Clone)]
+pub struct AttributeView<'a> {
+    pub(crate) id: AttributeId,
+    pub(crate) axis: &'a AxisName,
+    pub(crate) entry: &'a AttributeEntry,
+}
+
+impl<'a> AttributeView<'a> {
+    /// Returns the identifier of this attribute.
+    #[must_use]
+    pub fn id(&self) -> AttributeId {
+        self.id
+    }
+
+    /// Returns the name of the axis of this attribute.
+    #[must_use]
+    pub fn axis(&self) -> &'a AxisName {
+        self.axis
+    }
+
+    /// Returns the name of this attribute within its axis.
+    #[must_use]
+    pub fn name(&self) -> &'a str {
+        &self.entry.name
+    }
+
+    /// Borrows the axis and the name of this attribute.
+    #[must_use]
+    pub fn as_attribute_ref(&self) -> AttributeRef<'a> {
+        AttributeRef::new(self.axis, &self.entry.name)
+    }
+
+    /// Returns this attribute, sharing the strings of the policy.
+    #[must_use]
+    pub fn to_attribute(&self) -> Attribute {
+        Attribute {
+            axis: self.axis.clone(),
+            name: self.entry.name.clone(),
+        }
+    }
+
+    /// Returns the values given to this attribute, from the first one to
+    /// the current one.
+    #[must_use]
+    pub fn values(&self) -> &'a [u32] {
+        &self.entry.parameters.values
+    }
+
+    /// Returns the current value of this attribute.
+    #[must_use]
+    pub fn current_value(&self) -> u32 {
+        self.values().last().copied().unwrap_or_default()
+    }
+
+    /// Returns the number of rotations of this attribute whose values were
+    /// reclaimed.
+    #[must_use]
+    pub fn retired_epochs(&self) -> u32 {
+        self.entry.parameters.retired_epochs
+    }
+
+    /// Returns the rotation epoch of the current value of this attribute,
+    /// which is its number of rotations.
+    #[must_use]
+    pub fn current_epoch(&self) -> RotationEpoch {
+        match self.values().len() {
+            0 | 1 => RotationEpoch(0),
+            len => RotationEpoch(len as u32 - 1 + self.retired_epochs()),
+        }
+    }
+
+    /// Returns the values given to this attribute with their rotation
+    /// epoch, from the first one to the current one. The epochs of the
+    /// reclaimed values are skipped.
+    pub fn values_with_epochs(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = (u32, RotationEpoch)> + ExactSizeIterator + 'a {
+        let retired = self.retired_epochs();
+        self.values().iter().enumerate().map(move |(i, value)| {
+            let epoch = if i == 0 { 0 } else { i as u32 + retired };
+            (*value, RotationEpoch(epoch))
+        })
+    }
+
+    /// Returns the value of this attribute at the given rotation epoch, if
+    /// it is still listed.
+    #[must_use]
+    pub fn value_at_epoch(&self, epoch: RotationEpoch) -> Option<u32> {
+        let index = match epoch.0 {
+            0 => 0,
+            epoch => epoch
+                .checked_sub(self.retired_epochs())
+                .filter(|i| *i > 0)?,
+        };
+        self.values().get(index as usize).copied()
+    }
+
+    /// Returns the encryption hint of this attribute.
+    #[must_use]
+    pub fn encryption_hint(&self) -> EncryptionHint {
+        self.entry.parameters.encryption_hint
+    }
+}
+
+impl Debug for AttributeView<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("AttributeView")
+            .field("id", &self.id)
+            .field("attribute", &self.as_attribute_ref())
+            .field("values", &self.entry.parameters.values)
+            .field("retired_epochs", &self.entry.parameters.retired_epochs)
+            .field("encryption_hint", &self.entry.parameters.encryption_hint)
+            .finish()
+    }
+}
@@ -0,0 +1,268 @@
+//! Versioned storage of policies.
+
+#[cfg(feature = "json")]
+use crate::HashMap;
+use crate::{Error, Policy};
+#[cfg(feature = "json")]
+use alloc::{borrow::ToOwned, string::String};
+use alloc::{format, string::ToString, vec::Vec};
+
+/// Storage of the successive versions of named policies.
+///
+/// Versions are numbered from 1 in the order in which they are saved.
+pub trait PolicyStore {
+    /// Saves a new version of the policy stored under the given name and
+    /// returns its version number.
+    fn save(&mut self, name: &str, policy: &Policy) -> Result<u32, Error>;
+
+    /// Returns all the versions of the policy stored under the given name,
+    /// oldest first.
+    fn history(&self, name: &str) -> Result<Vec<Policy>, Error>;
+
+    /// Returns the number of the last version of the policy stored under the
+    /// given name, 0 if there is none.
+    fn last_version(&self, name: &str) -> Result<u32, Error>;
+
+    /// Reads the given version of the policy stored under the given name,
+    /// without reading the other versions. Returns `None` if there is no such
+    /// version.
+    fn read_version(&self, name: &str, version: u32) -> Result<Option<Policy>, Error>;
+
+    /// Loads the last version of the policy stored under the given name.
+    fn load(&self, name: &str) -> Result<Policy, Error> {
+        match self.last_version(name)? {
+            0 => Err(Error::PolicyNotFound(name.to_string())),
+            version => self.load_version(name, version),
+        }
+    }
+
+    /// Loads the given version of the policy stored under the given name.
+    fn load_version(&self, name: &str, version: u32) -> Result<Policy, Error> {
+        self.read_version(name, version)?
+            .ok_or_else(|| Error::PolicyNotFound(format!("{name} (version {version})")))
+    }
+}
+
+/// In-memory key-value store of policies, keyed by policy name.
+///
+/// Policies are kept in their serialized form.
+#[cfg(feature = "json")]
+#[derive(Debug, Default, Clone)]
+pub struct MemoryPolicyStore {
+    policies: HashMap<String, Vec<Vec<u8>>>,
+}
+
+#[cfg(feature = "json")]
+impl MemoryPolicyStore {
+    /// Creates an empty store.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[cfg(feature = "json")]
+impl PolicyStore for MemoryPolicyStore {
+    fn save(&mut self, name: &str, policy: &Policy) -> Result<u32, Error> {
+        let bytes = serde_json::to_vec(policy).map_err(Error::DeserializationError)?;
+        let versions = self.policies.entry(name.to_owned()).or_default();
+        versions.push(bytes);
+        Ok(versions.len() as u32)
+    }
+
+    fn history(&self, name: &str) -> Result<Vec<Policy>, Error> {
+        self.policies
+            .get(name)
+            .ok_or_else(|| Error::PolicyNotFound(name.to_string()))?
+            .iter()
+            .map(|bytes| Policy::parse_and_convert(bytes))
+            .collect()
+    }
+
+    fn last_version(&self, name: &str) -> Result<u32, Error> {
+        Ok(self
+            .policies
+            .get(name)
+            .map_or(0, |versions| versions.len() as u32))
+    }
+
+    fn read_version(&self, name: &str, version: u32) -> Result<Option<Policy>, Error> {
+        version
+            .checked_sub(1)
+            .and_then(|index| self.policies.get(name)?.get(index as usize))
+            .map(|bytes| Policy::parse_and_convert(bytes))
+            .transpose()
+    }
+}
+
+/// Filesystem store of policies.
+///
+/// Each policy is stored in its own directory under the store root, one JSON
+/// file per version: `<root>/<name>/<version>.json`.
+///
+/// A new version is numbered after the last existing one, and saving fails
+/// instead of overwriting a version saved concurrently under the same number.
+#[cfg(all(feature = "std", feature = "json"))]
+#[derive(Debug, Clone)]
+pub struct FilePolicyStore {
+    root: std::path::PathBuf,
+}
+
+#[cfg(all(feature = "std", feature = "json"))]
+impl FilePolicyStore {
+    /// Creates a store rooted at the given directory. The directory is
+    /// created if it does not exist.
+    pub fn new(root: impl Into<std::path::PathBuf>) -> Result<Self, Error> {
+        let root = root.into();
+        std::fs::create_dir_all(&root)
+            .map_err(|e| Error::StorageError(format!("{}: {e}", root.display())))?;
+        Ok(Self { root })
+    }
+
+    /// Returns the directory holding the versions of the given policy.
+    fn policy_dir(&self, name: &str) -> Result<std::path::PathBuf, Error> {
+        if name.is_empty() || name == "." || name == ".." || name.contains(['/', '\\', '\0']) {
+            return Err(Error::StorageError(format!(
+                "'{name}' cannot be used as a policy name"
+            )));
+        }
+        Ok(self.root.join(name))
+    }
+
+    /// Returns the last version of the policy stored in the given directory,
+    /// 0 if there is none.
+    fn last_version_in(dir: &std::path::Path) -> Result<u32, Error> {
+        let entries = std::fs::read_dir(dir)
+            .map_err(|e| Error::StorageError(format!("{}: {e}", dir.display())))?;
+        let mut last = 0;
+        for entry in entries {
+            let entry =
+                entry.map_err(|e| Error::StorageError(format!("{}: {e}", dir.display())))?;
+            if let Some(version) = entry
+                .file_name()
+                .to_str()
+                .and_then(|file_name| file_name.strip_suffix(".json"))
+                .and_then(|version| version.parse::<u32>().ok())
+            {
+                last = last.max(version);
+            }
+        }
+        Ok(last)
+    }
+}
+
+#[cfg(all(feature = "std", feature = "json"))]
+impl PolicyStore for FilePolicyStore {
+    fn save(&mut self, name: &str, policy: &Policy) -> Result<u32, Error> {
+        let dir = self.policy_dir(name)?;
+        std::fs::create_dir_all(&dir)
+            .map_err(|e| Error::StorageError(format!("{}: {e}", dir.display())))?;
+        let version = Self::last_version_in(&dir)?
+            .checked_add(1)
+            .ok_or_else(|| Error::StorageError(format!("{}: too many versions", dir.display())))?;
+        let bytes = serde_json::to_vec(policy).map_err(Error::DeserializationError)?;
+        let path = dir.join(format!("{version}.json"));
+        // a version saved concurrently is not overwritten
+        std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .and_then(|mut file| std::io::Write::write_all(&mut file, &bytes))
+            .map_err(|e| Error::StorageError(format!("{}: {e}", path.display())))?;
+        Ok(version)
+    }
+
+    fn history(&self, name: &str) -> Result<Vec<Policy>, Error> {
+        let dir = self.policy_dir(name)?;
+        if !dir.is_dir() {
+            return Err(Error::PolicyNotFound(name.to_string()));
+        }
+        (1..=Self::last_version_in(&dir)?)
+            .map(|version| {
+                let path = dir.join(format!("{version}.json"));
+                let bytes = std::fs::read(&path)
+                    .map_err(|e| Error::StorageError(format!("{}: {e}", path.display())))?;
+                Policy::parse_and_convert(&bytes)
+            })
+            .collect()
+    }
+
+    fn last_version(&self, name: &str) -> Result<u32, Error> {
+        let dir = self.policy_dir(name)?;
+        if !dir.is_dir() {
+            return Ok(0);
+        }
+        Self::last_version_in(&dir)
+    }
+
+    fn read_version(&self, name: &str, version: u32) -> Result<Option<Policy>, Error> {
+        let path = self.policy_dir(name)?.join(format!("{version}.json"));
+        match std::fs::read(&path) {
+            Ok(bytes) => Policy::parse_and_convert(&bytes).map(Some),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(Error::StorageError(format!("{}: {e}", path.display()))),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "json"))]
+mod tests {
+    use super::*;
+    use crate::tests::policy;
+
+    fn check_store(store: &mut impl PolicyStore) -> Result<(), Error> {
+        let mut policy = policy()?;
+        assert!(store.load("demo").is_err());
+
+        assert_eq!(1, store.save("demo", &policy)?);
+        let attribute = policy.attributes()[0].clone();
+        policy.rotate(&attribute)?;
+        assert_eq!(2, store.save("demo", &policy)?);
+
+        assert_eq!(policy, store.load("demo")?);
+        let history = store.history("demo")?;
+        assert_eq!(2, history.len());
+        assert_eq!(1, history[0].attribute_values(&attribute)?.len());
+        assert_eq!(history[0], store.load_version("demo", 1)?);
+        assert!(store.load_version("demo", 0).is_err());
+        assert!(store.load_version("demo", 3).is_err());
+        assert_eq!(2, store.last_version("demo")?);
+        assert_eq!(0, store.last_version("other")?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_memory_store() -> Result<(), Error> {
+        check_store(&mut MemoryPolicyStore::new())
+    }
+
+    #[test]
+    fn test_file_store() -> Result<(), Error> {
+        let root = std::env::temp_dir().join(format!("abe_policy_store_{}", std::process::id()));
+        let mut store = FilePolicyStore::new(&root)?;
+        assert!(store.save("../demo", &policy()?).is_err());
+        let res = check_store(&mut store).and_then(|()| {
+            // a missing version does not make the next one overwrite the last
+            std::fs::remove_file(root.join("demo").join("1.json")).unwrap();
+            assert_eq!(3, store.save("demo", &policy()?)?);
+            assert!(matches!(store.history("demo"), Err(Error::StorageError(_))));
+            // single versions are read without the rest of the history
+            assert!(matches!(
+                store.load_version("demo", 1),
+                Err(Error::PolicyNotFound(_))
+            ));
+            assert_eq!(policy()?, store.load("demo")?);
+            assert_eq!(
+                2,
+                Policy::parse_and_convert(
+                    &std::fs::read(root.join("demo").join("2.json")).unwrap()
+                )?
+                .attribute_values(&policy()?.attributes()[0])?
+                .len()
+            );
+            Ok(())
+        });
+        std::fs::remove_dir_all(&root).unwrap();
+        res
+    }
+}
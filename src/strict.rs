@@ -0,0 +1,176 @@
+//! Strict parsing of JSON policies, rejecting the unknown fields that
+//! `Policy::parse_and_convert` ignores or keeps.
+//!
+//! The types of this module mirror the serialized ones with
+//! `deny_unknown_fields`: the remote ones deserialize the policy types
+//! directly.
+
+use alloc::{string::String, vec::Vec};
+
+use serde::{
+    de::{self, MapAccess, SeqAccess, Visitor},
+    Deserialize, Deserializer,
+};
+use serde_json::Map;
+use smallvec::SmallVec;
+
+use crate::{
+    error::from_json_slice,
+    policy::{PolicyAttributesParameters, PolicyAxesParameters, PolicyVersion, UncheckedPolicy},
+    Attribute, AxisName, CharacterClass, EncryptionHint, Error, IndexMap, LegacyPolicy,
+    NamingPolicy, Policy, TrimmingRule,
+};
+
+#[derive(Deserialize)]
+#[serde(remote = "PolicyAxesParameters", deny_unknown_fields)]
+struct StrictAxesParameters {
+    attribute_names: Vec<String>,
+    is_hierarchical: bool,
+}
+
+#[derive(Deserialize)]
+struct Axis(#[serde(with = "StrictAxesParameters")] PolicyAxesParameters);
+
+#[derive(Deserialize)]
+#[serde(remote = "PolicyAttributesParameters", deny_unknown_fields)]
+struct StrictAttributesParameters {
+    values: SmallVec<[u32; 2]>,
+    encryption_hint: EncryptionHint,
+    #[serde(default)]
+    retired_epochs: u32,
+}
+
+#[derive(Deserialize)]
+struct AttributeParameters(
+    #[serde(with = "StrictAttributesParameters")] PolicyAttributesParameters,
+);
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AttributeRecord {
+    axis: String,
+    name: String,
+    values: SmallVec<[u32; 2]>,
+    encryption_hint: EncryptionHint,
+    #[serde(default)]
+    retired_epochs: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(remote = "NamingPolicy", deny_unknown_fields)]
+struct StrictNamingPolicy {
+    #[serde(default)]
+    max_length: Option<usize>,
+    #[serde(default)]
+    allowed_characters: Option<Vec<CharacterClass>>,
+    #[serde(default)]
+    trimming: TrimmingRule,
+    #[serde(default)]
+    case_insensitive: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StrictPolicy {
+    version: PolicyVersion,
+    last_attribute_value: u32,
+    max_attribute_creations: u32,
+    #[serde(deserialize_with = "deserialize_axes")]
+    axes: IndexMap<AxisName, PolicyAxesParameters>,
+    #[serde(deserialize_with = "deserialize_attributes")]
+    attributes: IndexMap<Attribute, PolicyAttributesParameters>,
+    #[serde(default, with = "StrictNamingPolicy")]
+    naming_policy: NamingPolicy,
+    #[serde(default)]
+    reclaimed_values: Vec<u32>,
+}
+
+#[derive(Deserialize)]
+#[serde(remote = "LegacyPolicy", deny_unknown_fields)]
+struct StrictLegacyPolicy {
+    last_attribute_value: u32,
+    max_attribute_creations: u32,
+    #[serde(deserialize_with = "deserialize_axes")]
+    axes: IndexMap<AxisName, PolicyAxesParameters>,
+    attributes: IndexMap<Attribute, Vec<u32>>,
+}
+
+#[derive(Deserialize)]
+struct Legacy(#[serde(with = "StrictLegacyPolicy")] LegacyPolicy);
+
+fn deserialize_axes<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<IndexMap<AxisName, PolicyAxesParameters>, D::Error> {
+    let axes = IndexMap::<AxisName, Axis>::deserialize(deserializer)?;
+    Ok(axes
+        .into_iter()
+        .map(|(name, axis)| (name, axis.0))
+        .collect())
+}
+
+/// Reads the attributes as `AttributesVisitor` does, from an array of
+/// records or from a map keyed by `Axis::Name` strings.
+struct AttributesVisitor;
+
+impl<'de> Visitor<'de> for AttributesVisitor {
+    type Value = IndexMap<Attribute, PolicyAttributesParameters>;
+
+    fn expecting(&self, formatter: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        formatter.write_str("an array of attributes")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut attributes = IndexMap::default();
+        while let Some(record) = seq.next_element::<AttributeRecord>()? {
+            attributes.insert(
+                Attribute::from_serialized(&record.axis, &record.name)
+                    .map_err(de::Error::custom)?,
+                PolicyAttributesParameters {
+                    values: record.values,
+                    encryption_hint: record.encryption_hint,
+                    retired_epochs: record.retired_epochs,
+                },
+            );
+        }
+        Ok(attributes)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut attributes = IndexMap::default();
+        while let Some((attribute, parameters)) = map.next_entry::<_, AttributeParameters>()? {
+            attributes.insert(attribute, parameters.0);
+        }
+        Ok(attributes)
+    }
+}
+
+fn deserialize_attributes<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<IndexMap<Attribute, PolicyAttributesParameters>, D::Error> {
+    deserializer.deserialize_any(AttributesVisitor)
+}
+
+/// Parses a policy as `Policy::parse_and_convert` does, rejecting unknown
+/// fields. The legacy format is read if the policy does not parse in the
+/// current one, also rejecting unknown fields: a policy in the current
+/// format with a mistyped field is not read as a legacy one.
+pub(crate) fn parse_strict(bytes: &[u8]) -> Result<Policy, Error> {
+    let policy = match from_json_slice::<StrictPolicy>(bytes) {
+        Ok(policy) => UncheckedPolicy {
+            version: policy.version,
+            last_attribute_value: policy.last_attribute_value,
+            max_attribute_creations: policy.max_attribute_creations,
+            axes: policy.axes,
+            attributes: policy.attributes,
+            naming_policy: policy.naming_policy,
+            reclaimed_values: policy.reclaimed_values,
+            unknown_fields: Map::new(),
+        },
+        Err(e) => match from_json_slice::<Legacy>(bytes) {
+            Ok(policy) => policy.0.into(),
+            Err(legacy) => return Err(e.or_legacy(legacy)),
+        },
+    };
+    Policy::try_from(policy)
+        .map_err(|problem| Error::DeserializationError(de::Error::custom(problem)))
+}
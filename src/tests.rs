@@ -1,30 +1,14 @@
-use crate::{error::Error, policy::Policy, Attribute, EncryptionHint, PolicyAxis};
+use crate::{
+    error::Error, policy::Policy, AccessPolicy, Attribute, AttributeId, AttributeRef, Attributes,
+    AxisId, AxisName, CharacterClass, EncryptionHint, NamingPolicy, PolicyAxis, PolicyDiff,
+    PolicyProblem, RotationEpoch, TrimmingRule,
+};
+#[cfg(feature = "json")]
+use crate::{AccessPolicyRef, PolicyRef, ValueWidth};
 
 /// Creates the policy object used in tests.
 pub fn policy() -> Result<Policy, Error> {
-    let sec_level = PolicyAxis::new(
-        "Security Level",
-        vec![
-            ("Protected", EncryptionHint::Classic),
-            ("Confidential", EncryptionHint::Classic),
-            ("Top Secret", EncryptionHint::Hybridized),
-        ],
-        true,
-    );
-    let department = PolicyAxis::new(
-        "Department",
-        vec![
-            ("R&D", EncryptionHint::Classic),
-            ("HR", EncryptionHint::Classic),
-            ("MKG", EncryptionHint::Classic),
-            ("FIN", EncryptionHint::Classic),
-        ],
-        false,
-    );
-    let mut policy = Policy::new(100);
-    policy.add_axis(sec_level)?;
-    policy.add_axis(department)?;
-    Ok(policy)
+    crate::fixtures::demo_policy()
 }
 
 #[test]
@@ -85,3 +69,1478 @@ fn test_rotate_policy_attributes() -> Result<(), Error> {
     }
     Ok(())
 }
+
+#[test]
+fn test_attribute_value_epochs() -> Result<(), Error> {
+    let mut policy = policy()?;
+    let attribute = Attribute::new("Department", "MKG");
+    policy.rotate(&attribute)?;
+    policy.rotate(&attribute)?;
+    let values = policy.attribute_values_with_epochs(&attribute)?;
+    assert_eq!(3, values.len());
+    assert_eq!(RotationEpoch(2), values[0].1);
+    for (value, epoch) in values {
+        assert_eq!(value, policy.attribute_value_at_epoch(&attribute, epoch)?);
+    }
+    assert!(policy
+        .attribute_value_at_epoch(&attribute, RotationEpoch(3))
+        .is_err());
+    Ok(())
+}
+
+#[test]
+fn test_naming_policy() -> Result<(), Error> {
+    let mut policy = Policy::new(100).with_naming_policy(NamingPolicy {
+        max_length: Some(12),
+        allowed_characters: Some(vec![CharacterClass::Alphabetic, CharacterClass::Whitespace]),
+        trimming: TrimmingRule::Trim,
+        ..NamingPolicy::default()
+    });
+
+    // forbidden character
+    assert!(policy
+        .add_axis(PolicyAxis::new(
+            "Department",
+            vec![("R&D", EncryptionHint::Classic)],
+            false
+        ))
+        .is_err());
+    // name too long
+    assert!(policy
+        .add_axis(PolicyAxis::new(
+            "Security Level Axis",
+            vec![("Protected", EncryptionHint::Classic)],
+            true
+        ))
+        .is_err());
+    // failed additions do not modify the policy
+    assert!(policy.attributes.is_empty());
+    assert_eq!(100, policy.remaining_attribute_creations());
+
+    // names are trimmed
+    policy.add_axis(PolicyAxis::new(
+        " Department ",
+        vec![("HR ", EncryptionHint::Classic)],
+        false,
+    ))?;
+    assert_eq!(
+        1,
+        policy.attribute_values(&Attribute::new("Department", "HR"))?[0]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_case_insensitive_policy() -> Result<(), Error> {
+    let mut policy = Policy::new(100).with_naming_policy(NamingPolicy {
+        case_insensitive: true,
+        ..NamingPolicy::default()
+    });
+    policy.add_axis(PolicyAxis::new(
+        "Department",
+        vec![
+            ("HR", EncryptionHint::Classic),
+            ("FIN", EncryptionHint::Classic),
+        ],
+        false,
+    ))?;
+    // names differing only by their case are duplicates
+    assert!(policy
+        .add_axis(PolicyAxis::new(
+            "DEPARTMENT",
+            vec![("MKG", EncryptionHint::Classic)],
+            false
+        ))
+        .is_err());
+    for names in [["MKG", "MKG"], ["MKG", "mkg"]] {
+        assert!(matches!(
+            policy.add_axis(PolicyAxis::new(
+                "Marketing",
+                names
+                    .into_iter()
+                    .map(|name| (name, EncryptionHint::Classic))
+                    .collect(),
+                false
+            )),
+            Err(Error::ExistingPolicy(_))
+        ));
+    }
+
+    let attribute = Attribute::new("department", "fin");
+    assert_eq!(
+        Attribute::new("Department", "FIN"),
+        policy.canonical_attribute(&attribute)?
+    );
+    policy.rotate(&attribute)?;
+    assert_eq!(2, policy.attribute_values(&attribute)?.len());
+
+    let access_policy = policy.parse_access_policy("department::fin || DEPARTMENT::hr")?;
+    assert_eq!(
+        AccessPolicy::new("Department", "FIN") | AccessPolicy::new("Department", "HR"),
+        access_policy
+    );
+    assert!(policy.parse_access_policy("Department::MKG").is_err());
+    Ok(())
+}
+
+#[test]
+fn test_unicode_normalization() -> Result<(), Error> {
+    // precomposed `é` and `e` followed by a combining acute accent
+    let precomposed = "Comptabilit\u{e9}";
+    let decomposed = "Comptabilite\u{301}";
+
+    let mut policy = Policy::new(100);
+    policy.add_axis(PolicyAxis::new(
+        "D\u{e9}partement",
+        vec![
+            (decomposed, EncryptionHint::Classic),
+            ("RH", EncryptionHint::Classic),
+        ],
+        false,
+    ))?;
+    assert!(policy
+        .add_axis(PolicyAxis::new(
+            "De\u{301}partement",
+            vec![("Juridique", EncryptionHint::Classic)],
+            false
+        ))
+        .is_err());
+
+    let attribute = Attribute::new("De\u{301}partement", precomposed);
+    assert_eq!(
+        Attribute::new("D\u{e9}partement", precomposed),
+        policy.canonical_attribute(&attribute)?
+    );
+    assert_eq!(1, policy.attribute_current_value(&attribute)?);
+    Ok(())
+}
+
+#[test]
+fn test_count_combinations() -> Result<(), Error> {
+    let policy = policy()?;
+    for expression in [
+        "Department::HR",
+        "Security Level::Top Secret",
+        "(Department::HR || Department::FIN) && Security Level::Confidential",
+        "Security Level::Top Secret && Security Level::Confidential",
+    ] {
+        let access_policy = AccessPolicy::from_boolean_expression(expression)?;
+        for follow_hierarchical_axes in [true, false] {
+            assert_eq!(
+                access_policy
+                    .to_attribute_combinations(&policy, follow_hierarchical_axes)?
+                    .len() as u64,
+                policy.count_combinations(&access_policy, follow_hierarchical_axes)?
+            );
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_attribute_combinations() -> Result<(), Error> {
+    let policy = policy()?;
+    let access_policy = AccessPolicy::from_boolean_expression(
+        "Security Level::Confidential && (Department::HR || Department::FIN) && Department::MKG",
+    )?;
+    let combinations = |follow_hierarchical_axes| -> Result<Vec<Vec<String>>, Error> {
+        Ok(access_policy
+            .to_attribute_combinations(&policy, follow_hierarchical_axes)?
+            .iter()
+            .map(|combination| combination.iter().map(ToString::to_string).collect())
+            .collect())
+    };
+    assert_eq!(
+        vec![
+            vec![
+                "Security Level::Confidential",
+                "Department::HR",
+                "Department::MKG"
+            ],
+            vec![
+                "Security Level::Confidential",
+                "Department::FIN",
+                "Department::MKG"
+            ],
+            vec![
+                "Security Level::Protected",
+                "Department::HR",
+                "Department::MKG"
+            ],
+            vec![
+                "Security Level::Protected",
+                "Department::FIN",
+                "Department::MKG"
+            ],
+        ],
+        combinations(true)?
+    );
+    assert_eq!(2, combinations(false)?.len());
+    Ok(())
+}
+
+#[test]
+fn test_many_attribute_combinations() -> Result<(), Error> {
+    // enough combinations to build them in parallel with the `parallel` feature
+    let mut policy = Policy::new(150);
+    let names = (0..50).map(|i| format!("A{i}")).collect::<Vec<_>>();
+    for axis in ["X", "Y", "Z"] {
+        policy.add_axis(PolicyAxis::new(
+            axis,
+            names
+                .iter()
+                .map(|name| (name.as_str(), EncryptionHint::Classic))
+                .collect(),
+            axis == "Y",
+        ))?;
+    }
+    let disjunction = |axis| {
+        names
+            .iter()
+            .map(|name| format!("{axis}::{name}"))
+            .collect::<Vec<_>>()
+            .join(" || ")
+    };
+    let access_policy = AccessPolicy::from_boolean_expression(&format!(
+        "({}) && Y::A1 && ({})",
+        disjunction("X"),
+        disjunction("Z")
+    ))?;
+
+    let mut expected = Vec::new();
+    for x in &names {
+        for y in ["A1", "A0"] {
+            for z in &names {
+                expected.push(vec![
+                    Attribute::new("X", x),
+                    Attribute::new("Y", y),
+                    Attribute::new("Z", z),
+                ]);
+            }
+        }
+    }
+    assert_eq!(
+        expected,
+        access_policy.to_attribute_combinations(&policy, true)?
+    );
+    assert_eq!(
+        expected.len() as u64,
+        policy.count_combinations(&access_policy, true)?
+    );
+    Ok(())
+}
+
+#[test]
+fn test_fixtures() -> Result<(), Error> {
+    use crate::fixtures::{enterprise_policy, hospital_policy};
+
+    assert_eq!(5 + 12 + 27, enterprise_policy()?.attributes().len());
+    assert_eq!(4 + 5 + 8, hospital_policy()?.attributes().len());
+    Ok(())
+}
+
+#[test]
+fn test_add_attribute() -> Result<(), Error> {
+    let mut policy = policy()?;
+    let attribute = Attribute::new("Security Level", "Ultra Secret");
+    policy.add_attribute(&attribute, EncryptionHint::Hybridized)?;
+    assert_eq!(8, policy.attribute_current_value(&attribute)?);
+    assert_eq!(
+        EncryptionHint::Hybridized,
+        policy.attribute_hybridization_hint(&attribute)?
+    );
+    // the new attribute is the highest one of the hierarchical axis
+    assert_eq!(
+        4,
+        AccessPolicy::Attr(attribute.clone())
+            .to_attribute_combinations(&policy, true)?
+            .len()
+    );
+
+    assert!(policy
+        .add_attribute(&attribute, EncryptionHint::Classic)
+        .is_err());
+    assert!(policy
+        .add_attribute(
+            &Attribute::new("Country", "France"),
+            EncryptionHint::Classic
+        )
+        .is_err());
+    Ok(())
+}
+
+#[test]
+fn test_remove_attribute() -> Result<(), Error> {
+    let mut policy = policy()?;
+    let attribute = Attribute::new("Department", "MKG");
+    policy.remove_attribute(&attribute)?;
+    assert!(policy.attribute_values(&attribute).is_err());
+    assert_eq!(6, policy.attributes().len());
+    assert_eq!(
+        vec!["R&D", "HR", "FIN"],
+        policy
+            .axis("Department")
+            .unwrap()
+            .attribute_names()
+            .collect::<Vec<_>>()
+    );
+    assert!(policy.parse_access_policy("Department::MKG").is_err());
+    // the values of the removed attribute are not reused
+    policy.add_attribute(&attribute, EncryptionHint::Classic)?;
+    assert_eq!(8, policy.attribute_current_value(&attribute)?);
+
+    assert!(matches!(
+        policy.remove_attribute(&Attribute::new("Department", "Sales")),
+        Err(Error::AttributeNotFound(_))
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_rename_attribute() -> Result<(), Error> {
+    let mut policy = policy()?;
+    let hr = Attribute::new("Department", "HR");
+    policy.rotate(&hr)?;
+    let values = policy.attribute_values(&hr)?;
+    let hint = policy.attribute_hybridization_hint(&hr)?;
+
+    let human_resources = Attribute::new("Department", "Human Resources");
+    policy.rename_attribute(&hr, "Human Resources")?;
+    assert!(policy.attribute_values(&hr).is_err());
+    assert_eq!(values, policy.attribute_values(&human_resources)?);
+    assert_eq!(hint, policy.attribute_hybridization_hint(&human_resources)?);
+    assert_eq!(
+        vec!["R&D", "Human Resources", "MKG", "FIN"],
+        policy
+            .axis("Department")
+            .unwrap()
+            .attribute_names()
+            .collect::<Vec<_>>()
+    );
+    assert!(policy.check_consistency().is_empty());
+
+    assert!(matches!(
+        policy.rename_attribute(&human_resources, "FIN"),
+        Err(Error::ExistingPolicy(_))
+    ));
+    assert!(matches!(
+        policy.rename_attribute(&hr, "HR"),
+        Err(Error::AttributeNotFound(_))
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_insertion_order() -> Result<(), Error> {
+    let mut policy = policy()?;
+    let axes = || {
+        policy
+            .axes()
+            .map(|axis| axis.name().as_str())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(vec!["Security Level", "Department"], axes());
+
+    policy.remove_attribute(&Attribute::new("Security Level", "Confidential"))?;
+    policy.rename_attribute(&Attribute::new("Department", "HR"), "People")?;
+    assert_eq!(
+        vec![
+            "Security Level::Protected",
+            "Security Level::Top Secret",
+            "Department::R&D",
+            "Department::People",
+            "Department::MKG",
+            "Department::FIN",
+        ],
+        policy
+            .attribute_views()
+            .map(|attribute| attribute.as_attribute_ref().to_string())
+            .collect::<Vec<_>>()
+    );
+
+    // serialization follows insertion order and is stable across round-trips
+    #[cfg(feature = "json")]
+    {
+        let json = policy.to_string();
+        assert!(json.find("Security Level") < json.find("Department"));
+        assert_eq!(
+            json,
+            Policy::parse_and_convert(json.as_bytes())?.to_string()
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn test_pretty_string() -> Result<(), Error> {
+    let mut policy = policy()?;
+    policy.rotate(&Attribute::new("Department", "HR"))?;
+    assert_eq!(
+        "Attribute creations: 8/100
+Axis                          | Attribute    | Value | Rotations | Hint
+------------------------------+--------------+-------+-----------+-----------
+Department                    | R&D          |     4 |         0 | Classic
+Department                    | HR           |     8 |         1 | Classic
+Department                    | MKG          |     6 |         0 | Classic
+Department                    | FIN          |     7 |         0 | Classic
+Security Level (hierarchical) | Protected    |     1 |         0 | Classic
+Security Level (hierarchical) | Confidential |     2 |         0 | Classic
+Security Level (hierarchical) | Top Secret   |     3 |         0 | Hybridized
+",
+        policy.to_pretty_string()
+    );
+    Ok(())
+}
+
+#[test]
+fn test_attribute_try_new() {
+    assert_eq!(
+        Attribute::new("Department", "HR"),
+        Attribute::try_new("Department", "HR").unwrap()
+    );
+    for (axis, name) in [("", "HR"), ("Department", " "), ("Depart::ment", "HR")] {
+        assert!(matches!(
+            Attribute::try_new(axis, name),
+            Err(Error::InvalidAttribute(_))
+        ));
+    }
+    assert!(AccessPolicy::from_boolean_expression("Department::HR::FIN").is_err());
+}
+
+#[test]
+fn test_attribute_string_round_trip() -> Result<(), Error> {
+    assert_eq!(
+        Attribute::new("Security Level", "Top Secret"),
+        "Security Level::Top Secret".parse()?
+    );
+    for attribute in [
+        Attribute::new("Security Level", "Top Secret"),
+        Attribute::new("Axis::With", "Separators::"),
+        Attribute::new("C:\\", ":\\:"),
+    ] {
+        assert_eq!(attribute, attribute.to_string().parse()?);
+        #[cfg(feature = "json")]
+        assert_eq!(
+            attribute,
+            serde_json::from_str::<Attribute>(&serde_json::to_string(&attribute).unwrap()).unwrap()
+        );
+    }
+    assert_eq!(
+        r"Axis\:\:With::Name",
+        Attribute::new("Axis::With", "Name").to_string()
+    );
+    // a backslash that does not escape anything is kept
+    assert_eq!(Attribute::new(r"A\B", "C"), r"A\B::C".parse()?);
+    for invalid in ["Axis", "Axis::", "::Name", "A::B::C"] {
+        assert!(invalid.parse::<Attribute>().is_err());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_attribute_ref_lookup() -> Result<(), Error> {
+    let policy = policy()?;
+    let attribute = Attribute::new("Security Level", "Top Secret");
+    assert_eq!(
+        policy.attribute_current_value(&attribute)?,
+        policy.attribute_current_value(AttributeRef::new("Security Level", "Top Secret"))?
+    );
+    assert_eq!(
+        EncryptionHint::Hybridized,
+        policy.attribute_hybridization_hint(("Security Level", "Top Secret"))?
+    );
+    assert!(policy
+        .attribute_values(AttributeRef::new("Security Level", "Unknown"))
+        .is_err());
+    assert_eq!(
+        attribute.to_string(),
+        attribute.as_attribute_ref().to_string()
+    );
+    Ok(())
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_shared_axis_names() -> Result<(), Error> {
+    let mut policy = Policy::parse_and_convert(policy()?.to_string().as_bytes())?;
+    policy.add_attribute(
+        &Attribute::new("Department", "Legal"),
+        EncryptionHint::Classic,
+    )?;
+    let department = policy
+        .attributes()
+        .into_iter()
+        .filter(|attribute| &*attribute.axis == "Department")
+        .map(|attribute| attribute.axis)
+        .collect::<Vec<_>>();
+    assert_eq!(5, department.len());
+    assert!(department
+        .iter()
+        .all(|axis| std::ptr::eq(axis.as_str(), department[0].as_str())));
+    Ok(())
+}
+
+#[test]
+fn test_attributes_set_operations() -> Result<(), Error> {
+    let left = Attributes::try_from("Department::HR,Department::FIN,Department::HR")?;
+    assert_eq!(2, left.len());
+    let right = Attributes::try_from("Department::FIN,Security Level::Protected")?;
+
+    let fin = Attribute::new("Department", "FIN");
+    let hr = Attribute::new("Department", "HR");
+    let protected = Attribute::new("Security Level", "Protected");
+    assert_eq!(
+        vec![hr.clone(), fin.clone(), protected.clone()],
+        left.union(&right).into_vec()
+    );
+    assert_eq!(vec![fin.clone()], left.intersection(&right).into_vec());
+    assert_eq!(vec![hr.clone()], left.difference(&right).into_vec());
+    assert!(left.contains(("Department", "HR")));
+
+    let union = left.union(&right);
+    let by_axis = union.by_axis();
+    assert_eq!(
+        vec!["Department", "Security Level"],
+        by_axis.keys().copied().collect::<Vec<_>>()
+    );
+    assert_eq!(vec![&hr, &fin], by_axis["Department"]);
+
+    let mut attributes = Attributes::new();
+    assert!(attributes.insert(protected.clone()));
+    assert!(!attributes.insert(protected.clone()));
+    assert!(attributes.remove(&protected));
+    assert!(attributes.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_parse_attribute_list() -> Result<(), Error> {
+    let attributes: Attributes = " Department::FIN ,Security Level::Top Secret, "
+        .trim_end_matches([',', ' '])
+        .parse()?;
+    assert_eq!(
+        vec![
+            Attribute::new("Department", "FIN"),
+            Attribute::new("Security Level", "Top Secret")
+        ],
+        *attributes
+    );
+    assert_eq!(attributes, attributes.to_string().parse()?);
+    for (invalid, message) in [
+        ("", "empty list"),
+        ("Department::FIN,,Department::HR", "attribute 2 of"),
+        ("Department::FIN, Department", "attribute 2 of"),
+    ] {
+        match invalid.parse::<Attributes>() {
+            Err(Error::InvalidAttribute(e)) => assert!(e.contains(message), "{e}"),
+            res => panic!("unexpected result for {invalid:?}: {res:?}"),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_attribute_serde_representations() {
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct Structured {
+        #[serde(with = "crate::attribute_serde::structured")]
+        attribute: Attribute,
+    }
+
+    let attribute = Attribute::new("Security Level", "Top Secret");
+    assert_eq!(
+        r#""Security Level::Top Secret""#,
+        serde_json::to_string(&attribute).unwrap()
+    );
+    assert_eq!(
+        attribute,
+        serde_json::from_str::<Attribute>(r#"{"axis":"Security Level","name":"Top Secret"}"#)
+            .unwrap()
+    );
+
+    let json = serde_json::to_string(&Structured {
+        attribute: attribute.clone(),
+    })
+    .unwrap();
+    assert_eq!(
+        r#"{"attribute":{"axis":"Security Level","name":"Top Secret"}}"#,
+        json
+    );
+    assert_eq!(
+        attribute,
+        serde_json::from_str::<Structured>(r#"{"attribute":"Security Level::Top Secret"}"#)
+            .unwrap()
+            .attribute
+    );
+    assert!(serde_json::from_str::<Attribute>(r#"{"axis":"Security Level"}"#).is_err());
+}
+
+#[test]
+fn test_sort_attributes() -> Result<(), Error> {
+    let policy = policy()?;
+    let mut attributes = vec![
+        Attribute::new("Department", "HR"),
+        Attribute::new("Unknown", "B"),
+        Attribute::new("Security Level", "Top Secret"),
+        Attribute::new("Department", "R&D"),
+        Attribute::new("Unknown", "A"),
+        Attribute::new("Security Level", "Protected"),
+    ];
+    policy.sort_attributes(&mut attributes);
+    assert_eq!(
+        vec![
+            Attribute::new("Security Level", "Protected"),
+            Attribute::new("Security Level", "Top Secret"),
+            Attribute::new("Department", "R&D"),
+            Attribute::new("Department", "HR"),
+            Attribute::new("Unknown", "A"),
+            Attribute::new("Unknown", "B"),
+        ],
+        attributes
+    );
+    Ok(())
+}
+
+#[test]
+fn test_attribute_canonicalization() -> Result<(), Error> {
+    let attribute = Attribute::new("Security Level", "Top Secret");
+    for input in [
+        "Security Level::Top Secret",
+        " Security Level :: Top Secret ",
+        "Security\tLevel::Top   Secret",
+    ] {
+        assert_eq!(attribute, input.parse()?);
+    }
+    assert_eq!(
+        attribute,
+        Attribute::try_new(" Security  Level", "Top Secret\n")?
+    );
+    assert_eq!(
+        Attribute::new("Security Level ", " Top Secret"),
+        Attribute::parse_exact("Security Level :: Top Secret")?
+    );
+    assert_eq!(
+        AccessPolicy::Attr(attribute),
+        AccessPolicy::from_boolean_expression("Security Level :: Top Secret")?
+    );
+    Ok(())
+}
+
+#[test]
+fn test_attribute_ids() -> Result<(), Error> {
+    let mut policy = policy()?;
+    let hr = Attribute::new("Department", "HR");
+    let id = policy.attribute_id(&hr)?;
+    policy.rotate(&hr)?;
+    assert_eq!(id, policy.attribute_id(&hr)?);
+    assert_eq!(hr, policy.attribute_from_id(id)?);
+    assert_eq!(hr, policy.attribute_view(id).unwrap().to_attribute());
+
+    assert_eq!(policy.attributes().len(), policy.attribute_ids().len());
+    assert!(policy.attribute_ids().any(|other| other == id));
+    assert!(policy
+        .attribute_from_id(AttributeId {
+            axis: id.axis,
+            value: 1000
+        })
+        .is_err());
+
+    // renaming keeps the identifier, removing invalidates it
+    let people = Attribute::new("Department", "People");
+    policy.rename_attribute(&hr, "People")?;
+    assert_eq!(id, policy.attribute_id(&people)?);
+    assert_eq!(people, policy.attribute_from_id(id)?);
+    let axis = policy.axis_by_id(id.axis).unwrap();
+    assert_eq!("Department", axis.name().as_str());
+    assert_eq!(
+        Some(1),
+        axis.attribute_ids().iter().position(|other| *other == id)
+    );
+    policy.remove_attribute(&people)?;
+    assert!(policy.attribute_view(id).is_none());
+    assert!(policy.attribute_id(&people).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_attribute_conversions() -> Result<(), Error> {
+    let attribute = Attribute::new("Department", "FIN");
+    assert_eq!(attribute, Attribute::try_from(("Department", "FIN"))?);
+    assert_eq!(
+        attribute,
+        Attribute::try_from(("Department".to_string(), "FIN".to_string()))?
+    );
+    assert_eq!(attribute, Attribute::try_from(["Department", "FIN"])?);
+    assert_eq!(attribute, Attribute::try_from(&["Department", "FIN"][..])?);
+    assert!(Attribute::try_from(&["Department"][..]).is_err());
+    for [axis, name] in [["", "FIN"], ["Depart::ment", "FIN"], ["Department", " "]] {
+        assert!(Attribute::try_from([axis, name]).is_err());
+        assert!(Attribute::try_from((axis, name)).is_err());
+        assert!(Attribute::try_from(&[axis, name][..]).is_err());
+    }
+    assert_eq!(attribute, Attribute::try_from("Department::FIN")?);
+
+    assert_eq!(("Department", "FIN"), <(&str, &str)>::from(&attribute));
+    assert_eq!(
+        ("Department".to_string(), "FIN".to_string()),
+        <(String, String)>::from(attribute.clone())
+    );
+    assert_eq!("Department::FIN", String::from(attribute));
+    Ok(())
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_attributes_deserialization() {
+    let expected = Attributes::from(vec![
+        Attribute::new("Department", "FIN"),
+        Attribute::new("Security Level", "Top Secret"),
+    ]);
+    for json in [
+        r#"["Department::FIN", "Security Level::Top Secret"]"#,
+        r#"[{"axis": "Department", "name": "FIN"}, "Security Level::Top Secret"]"#,
+        r#""Department::FIN, Security Level::Top Secret""#,
+    ] {
+        assert_eq!(
+            expected,
+            serde_json::from_str::<Attributes>(json).unwrap(),
+            "{json}"
+        );
+    }
+    assert_eq!(
+        r#"["Department::FIN","Security Level::Top Secret"]"#,
+        serde_json::to_string(&expected).unwrap()
+    );
+    assert!(serde_json::from_str::<Attributes>("42").is_err());
+}
+
+#[test]
+fn test_axis_name() -> Result<(), Error> {
+    assert_eq!(
+        "Security Level",
+        AxisName::new(" Security  Level ")?.as_str()
+    );
+    assert_eq!(" Level", AxisName::new_exact(" Level")?.as_str());
+    for invalid in ["", " ", "Security::Level"] {
+        assert!(matches!(AxisName::new(invalid), Err(Error::InvalidAxis(_))));
+    }
+    assert!(Policy::new(10)
+        .add_axis(PolicyAxis::new(
+            "Security::Level",
+            vec![("Protected", EncryptionHint::Classic)],
+            false
+        ))
+        .is_err());
+
+    let policy = policy()?;
+    assert!(policy.axes.contains_key("Department"));
+    assert!(policy.axes.keys().any(|axis| axis == "Security Level"));
+    Ok(())
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_binary_serialization() -> Result<(), Error> {
+    let mut policy = policy()?;
+    policy.rotate(&Attribute::new("Department", "HR"))?;
+    policy.naming_policy.case_insensitive = true;
+
+    let bytes = policy.to_bytes()?;
+    assert!(bytes.len() < serde_json::to_vec(&policy).unwrap().len() / 2);
+    assert_eq!(policy, Policy::from_bytes(&bytes)?);
+    // The encoding does not depend on the map iteration order.
+    assert_eq!(bytes, Policy::from_bytes(&bytes)?.to_bytes()?);
+
+    for invalid in [
+        &b"ABEQ"[..],
+        &bytes[..bytes.len() - 1],
+        &[&bytes[..], &[0]].concat(),
+        b"ABEP\x02\x03",
+    ] {
+        assert!(matches!(
+            Policy::from_bytes(invalid),
+            Err(Error::InvalidBinaryPolicy(_))
+        ));
+    }
+
+    assert_eq!(bytes, policy.to_bytes_with(ValueWidth::Varint)?);
+    for width in [ValueWidth::U16, ValueWidth::U32] {
+        let bytes = policy.to_bytes_with(width)?;
+        assert_eq!(policy, Policy::from_bytes(&bytes)?);
+    }
+    // the 8 values take a single byte as varints
+    assert_eq!(
+        bytes.len() + 1 + 8,
+        policy.to_bytes_with(ValueWidth::U16)?.len()
+    );
+    policy.attributes[0].parameters.values[0] = 70_000;
+    assert!(matches!(
+        policy.to_bytes_with(ValueWidth::U16),
+        Err(Error::InvalidBinaryPolicy(_))
+    ));
+    Ok(())
+}
+
+#[cfg(all(feature = "std", feature = "json"))]
+#[test]
+fn test_streaming_serialization() -> Result<(), Error> {
+    let mut policy = policy()?;
+    policy.rotate(&Attribute::new("Department", "HR"))?;
+    policy.naming_policy.case_insensitive = true;
+    policy
+        .unknown_fields
+        .insert("comment".to_string(), "kept as is".into());
+
+    let mut json = Vec::new();
+    policy.serialize_to_writer(&mut json)?;
+    assert_eq!(serde_json::to_vec(&policy).unwrap(), json);
+    assert_eq!(policy, Policy::deserialize_from_reader(&json[..])?);
+    assert!(Policy::deserialize_from_reader(&json[..json.len() - 1]).is_err());
+    let legacy_policy = include_bytes!("../tests/legacy_policy.json");
+    assert!(Policy::deserialize_from_reader(&legacy_policy[..]).is_err());
+
+    for width in [ValueWidth::Varint, ValueWidth::U16, ValueWidth::U32] {
+        let mut bytes = Vec::new();
+        policy.serialize_bytes_to_writer(&mut bytes, width)?;
+        assert_eq!(policy.to_bytes_with(width)?, bytes);
+
+        // the bytes following the policy are not read
+        bytes.extend_from_slice(b"next");
+        let mut reader = &bytes[..];
+        assert_eq!(policy, Policy::deserialize_bytes_from_reader(&mut reader)?);
+        assert_eq!(b"next", reader);
+        assert!(matches!(
+            Policy::deserialize_bytes_from_reader(&bytes[..bytes.len() - 5]),
+            Err(Error::InvalidBinaryPolicy(_))
+        ));
+    }
+
+    // a corrupted length is not allocated in advance
+    assert!(matches!(
+        Policy::deserialize_bytes_from_reader(&b"ABEP\x01\x00\x00\xff\xff\xff\xff\x0f"[..]),
+        Err(Error::InvalidBinaryPolicy(_))
+    ));
+    assert!(matches!(
+        policy.serialize_bytes_to_writer(&mut [0; 16][..], ValueWidth::Varint),
+        Err(Error::IoError(_))
+    ));
+    Ok(())
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_policy_ref() -> Result<(), Error> {
+    let mut policy = policy()?;
+    policy.rotate(&Attribute::new("Department", "HR"))?;
+    policy.add_axis(PolicyAxis::new(
+        "Quote",
+        vec![(r#"Say "Hi""#, EncryptionHint::Hybridized)],
+        false,
+    ))?;
+    policy.naming_policy.case_insensitive = true;
+    let json = serde_json::to_vec(&policy).unwrap();
+
+    let policy_ref = PolicyRef::from_json(&json)?;
+    assert_eq!(policy, policy_ref.to_policy()?);
+    assert_eq!(
+        policy.attributes(),
+        policy_ref
+            .attributes()
+            .map(|attribute| Attribute::new(attribute.axis, attribute.name))
+            .collect::<Vec<_>>()
+    );
+    for attribute in policy.attributes() {
+        assert_eq!(
+            policy.attribute_values(&attribute)?,
+            policy_ref.attribute_values(&attribute)?
+        );
+        assert_eq!(
+            policy.attribute_hybridization_hint(&attribute)?,
+            policy_ref.attribute_hybridization_hint(&attribute)?
+        );
+    }
+    assert_eq!(
+        policy.attribute_current_value(&Attribute::new("Department", "HR"))?,
+        policy_ref.attribute_current_value(AttributeRef::new("department", "hr"))?
+    );
+    assert!(matches!(
+        policy_ref.attribute_current_value(AttributeRef::new("Department", "Sales")),
+        Err(Error::AttributeNotFound(_))
+    ));
+    assert!(matches!(
+        PolicyRef::from_json(br#"{"version":"V2"}"#),
+        Err(Error::DeserializationError(_))
+    ));
+
+    let access_policy = AccessPolicy::from_boolean_expression(
+        r#"Department::HR && (Quote::Say "Hi" || Security Level::Protected)"#,
+    )?;
+    let json = serde_json::to_vec(&access_policy).unwrap();
+    let access_policy_ref = AccessPolicyRef::from_json(&json)?;
+    assert_eq!(access_policy, access_policy_ref.to_access_policy());
+    assert_eq!(
+        access_policy.attributes(),
+        access_policy_ref
+            .attributes()
+            .into_iter()
+            .map(|attribute| Attribute::new(attribute.axis, attribute.name))
+            .collect::<Vec<_>>()
+    );
+    Ok(())
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_attribute_records() -> Result<(), Error> {
+    let mut policy = policy()?;
+    policy.add_axis(PolicyAxis::new(
+        "Path",
+        vec![("a::b", EncryptionHint::Classic)],
+        false,
+    ))?;
+    let json = serde_json::to_value(&policy).unwrap();
+    assert_eq!(
+        json["attributes"][7],
+        serde_json::json!({
+            "axis": "Path",
+            "name": "a::b",
+            "values": [8],
+            "encryption_hint": "Classic",
+        })
+    );
+    let bytes = serde_json::to_vec(&json).unwrap();
+    assert_eq!(policy, Policy::parse_and_convert(&bytes)?);
+    let policy_ref = PolicyRef::from_json(&bytes)?;
+    assert_eq!(policy, policy_ref.to_policy()?);
+    assert_eq!(
+        policy_ref.attribute_current_value(AttributeRef::new("Path", "a::b"))?,
+        8
+    );
+
+    // policies keyed by `Axis::Name` strings are still read
+    let bytes = include_bytes!("../tests/policy.json");
+    let policy = Policy::parse_and_convert(bytes)?;
+    assert!(serde_json::from_slice::<serde_json::Value>(bytes).unwrap()["attributes"].is_object());
+    assert_eq!(policy, PolicyRef::from_json(bytes)?.to_policy()?);
+    assert_eq!(
+        policy,
+        Policy::parse_and_convert(&serde_json::to_vec(&policy).unwrap())?
+    );
+
+    for attributes in [
+        r#"[{"axis":"","name":"HR","values":[1],"encryption_hint":"Classic"}]"#,
+        r#"[{"axis":"Department","values":[1],"encryption_hint":"Classic"}]"#,
+        r#"{"Department":{"values":[1],"encryption_hint":"Classic"}}"#,
+    ] {
+        let mut json = serde_json::to_value(&policy).unwrap();
+        json["attributes"] = serde_json::from_str(attributes).unwrap();
+        let bytes = serde_json::to_vec(&json).unwrap();
+        assert!(Policy::parse_and_convert(&bytes).is_err(), "{attributes}");
+        assert!(PolicyRef::from_json(&bytes).is_err(), "{attributes}");
+    }
+    Ok(())
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_parse_strict() -> Result<(), Error> {
+    let mut policy = policy()?;
+    policy.naming_policy.case_insensitive = true;
+    let json = serde_json::to_vec(&policy).unwrap();
+    assert_eq!(policy, Policy::parse_strict(&json)?);
+    for json in [
+        &include_bytes!("../tests/policy.json")[..],
+        include_bytes!("../tests/legacy_policy.json"),
+    ] {
+        assert_eq!(
+            Policy::parse_and_convert(json)?,
+            Policy::parse_strict(json)?
+        );
+    }
+
+    // each policy is read by `parse_and_convert`, the last one as a legacy
+    // policy, ignoring its version and naming policy
+    for (object, field, value) in [
+        ("", "naming_polcy", serde_json::json!({})),
+        ("/axes/Department", "heirarchical", true.into()),
+        ("/attributes/0", "encryption_hnt", "Hybridized".into()),
+        ("/naming_policy", "case_insensitve", false.into()),
+        (
+            "",
+            "attributes",
+            policy
+                .attribute_views()
+                .map(|attribute| {
+                    (
+                        attribute.as_attribute_ref().to_string(),
+                        serde_json::json!(attribute.values()),
+                    )
+                })
+                .collect(),
+        ),
+    ] {
+        let mut json = serde_json::to_value(&policy).unwrap();
+        json.pointer_mut(object)
+            .and_then(serde_json::Value::as_object_mut)
+            .unwrap()
+            .insert(field.into(), value);
+        let json = serde_json::to_vec(&json).unwrap();
+        assert!(Policy::parse_and_convert(&json).is_ok(), "{field}");
+        assert!(
+            matches!(
+                Policy::parse_strict(&json),
+                Err(Error::DeserializationError(_))
+            ),
+            "{field}"
+        );
+    }
+    Ok(())
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_deserialization_error_location() -> Result<(), Error> {
+    let mut policy = policy()?;
+    policy.add_axis(PolicyAxis::new(
+        "a/b~c",
+        vec![("A", EncryptionHint::Classic)],
+        false,
+    ))?;
+    let error = |make: fn(&mut serde_json::Value)| {
+        let mut json = serde_json::to_value(&policy).unwrap();
+        make(&mut json);
+        let json = serde_json::to_vec(&json).unwrap();
+        assert_eq!(
+            Policy::parse_and_convert(&json).unwrap_err().to_string(),
+            Policy::parse_strict(&json).unwrap_err().to_string()
+        );
+        Policy::parse_and_convert(&json).unwrap_err().to_string()
+    };
+
+    let message = error(|json| json["axes"]["Department"]["attribute_names"][3] = 3.into());
+    assert!(
+        message.starts_with(
+            "deserialization error: /axes/Department/attribute_names/3: invalid type: integer `3`"
+        ),
+        "{message}"
+    );
+    // the legacy format expects a map of attributes
+    assert!(
+        message.contains("; not a legacy policy either: /attributes: invalid type: sequence"),
+        "{message}"
+    );
+
+    let message = error(|json| json["axes"]["a/b~c"]["is_hierarchical"] = "no".into());
+    assert!(
+        message.starts_with("deserialization error: /axes/a~1b~0c/is_hierarchical: "),
+        "{message}"
+    );
+    let message = error(|json| json["attributes"][1]["values"] = serde_json::json!([-1]));
+    assert!(
+        message.starts_with("deserialization error: /attributes/1/values/0: "),
+        "{message}"
+    );
+
+    let mut json = serde_json::to_vec(&policy).unwrap();
+    json.push(b'x');
+    let message = Policy::parse_and_convert(&json).unwrap_err().to_string();
+    assert!(
+        message.starts_with("deserialization error: trailing characters"),
+        "{message}"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_reclaim_values() -> Result<(), Error> {
+    let mut policy = Policy::new(4);
+    policy.add_axis(PolicyAxis::new(
+        "Department",
+        vec![
+            ("HR", EncryptionHint::Classic),
+            ("FIN", EncryptionHint::Classic),
+        ],
+        false,
+    ))?;
+    let hr = Attribute::new("Department", "HR");
+    let fin = Attribute::new("Department", "FIN");
+    policy.rotate(&hr)?;
+    policy.rotate(&hr)?;
+    assert!(matches!(policy.rotate(&fin), Err(Error::CapacityOverflow)));
+
+    // nothing changes if an attribute is unknown
+    assert!(matches!(
+        policy.reclaim_values(&[hr.clone(), Attribute::new("Department", "MKG")]),
+        Err(Error::AttributeNotFound(_))
+    ));
+    assert_eq!(vec![4, 3, 1], policy.attribute_values(&hr)?);
+
+    // the identifier and the current value are kept
+    assert_eq!(1, policy.reclaim_values(&[hr.clone(), fin.clone()])?);
+    assert_eq!(&[3], policy.reclaimed_values());
+    assert_eq!(1, policy.remaining_attribute_creations());
+    assert_eq!(vec![4, 1], policy.attribute_values(&hr)?);
+    assert_eq!(
+        AttributeId {
+            axis: AxisId(0),
+            value: 1
+        },
+        policy.attribute_id(&hr)?
+    );
+    // the epochs of the kept values do not change
+    assert_eq!(4, policy.attribute_value_at_epoch(&hr, RotationEpoch(2))?);
+    assert!(matches!(
+        policy.attribute_value_at_epoch(&hr, RotationEpoch(1)),
+        Err(Error::UnknownEpoch(_, 1))
+    ));
+    assert_eq!(0, policy.reclaim_values(core::slice::from_ref(&hr))?);
+
+    // the reclaimed value is given again, after the new ones
+    policy.rotate(&hr)?;
+    assert_eq!(vec![3, 4, 1], policy.attribute_values(&hr)?);
+    assert!(policy.reclaimed_values().is_empty());
+    assert!(matches!(policy.rotate(&fin), Err(Error::CapacityOverflow)));
+    assert!(policy.check_consistency().is_empty());
+    let mut larger = policy.clone();
+    larger.max_attribute_creations = 10;
+    assert!(larger.check_consistency().is_empty());
+
+    assert_eq!(1, policy.reclaim_values(core::slice::from_ref(&hr))?);
+    assert_eq!(
+        vec![(3, RotationEpoch(3)), (1, RotationEpoch(0))],
+        policy.attribute_values_with_epochs(&hr)?
+    );
+    #[cfg(feature = "json")]
+    {
+        let json = serde_json::to_vec(&policy).unwrap();
+        assert_eq!(policy, Policy::parse_and_convert(&json)?);
+        assert_eq!(policy, Policy::parse_strict(&json)?);
+        assert_eq!(policy, PolicyRef::from_json(&json)?.to_policy()?);
+        for width in [ValueWidth::Varint, ValueWidth::U16] {
+            let bytes = policy.to_bytes_with(width)?;
+            assert_eq!(3, bytes[4]);
+            assert_eq!(policy, Policy::from_bytes(&bytes)?);
+        }
+    }
+
+    let mut copy = policy.clone();
+    copy.merge(&policy)?;
+    assert_eq!(policy, copy);
+
+    // the retired epochs are kept once the reclaimed values are given again
+    policy.rotate(&hr)?;
+    assert!(policy.reclaimed_values().is_empty());
+    assert_eq!(4, policy.attribute_value_at_epoch(&hr, RotationEpoch(4))?);
+    #[cfg(feature = "json")]
+    {
+        let bytes = policy.to_bytes()?;
+        assert_eq!(3, bytes[4]);
+        assert_eq!(policy, Policy::from_bytes(&bytes)?);
+    }
+
+    // the values of removed attributes are reclaimed on request
+    policy.remove_attribute(&fin)?;
+    assert_eq!(1, policy.reclaim_removed_values());
+    assert_eq!(&[2], policy.reclaimed_values());
+    assert_eq!(0, policy.reclaim_removed_values());
+    let legal = Attribute::new("Department", "Legal");
+    policy.add_attribute(&legal, EncryptionHint::Classic)?;
+    assert_eq!(2, policy.attribute_current_value(&legal)?);
+    assert!(policy.check_consistency().is_empty());
+
+    policy.reclaimed_values.push(1);
+    assert_eq!(
+        vec![PolicyProblem::InvalidReclaimedValue { value: 1 }],
+        policy.check_consistency()
+    );
+    Ok(())
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_fingerprint() -> Result<(), Error> {
+    let mut policy = policy()?;
+    let fingerprint = policy.fingerprint()?;
+    let json = serde_json::to_vec(&policy).unwrap();
+    assert_eq!(
+        fingerprint,
+        Policy::parse_and_convert(&json)?.fingerprint()?
+    );
+
+    policy.rotate(&Attribute::new("Department", "HR"))?;
+    assert_ne!(fingerprint, policy.fingerprint()?);
+    Ok(())
+}
+
+#[cfg(feature = "schema")]
+#[test]
+fn test_json_schema() {
+    let schema = serde_json::to_value(schemars::schema_for!(Policy)).unwrap();
+    for field in [
+        "version",
+        "last_attribute_value",
+        "max_attribute_creations",
+        "axes",
+        "attributes",
+        "naming_policy",
+    ] {
+        assert!(schema["properties"].get(field).is_some(), "{field}");
+    }
+    assert_eq!(schema["properties"]["attributes"]["type"], "array");
+    assert!(schema["required"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|field| field != "naming_policy"));
+}
+
+#[test]
+fn test_access_policy_attributes() -> Result<(), Error> {
+    let access_policy = AccessPolicy::from_boolean_expression(
+        "(Department::MKG || Department::FIN) && Level::Low",
+    )?;
+    assert_eq!(
+        vec![
+            Attribute::new("Department", "FIN"),
+            Attribute::new("Department", "MKG"),
+            Attribute::new("Level", "Low"),
+        ],
+        access_policy.attributes()
+    );
+
+    let mut buffer = vec![Attribute::new("Department", "HR")];
+    access_policy.attributes_into(&mut buffer);
+    (AccessPolicy::All & AccessPolicy::new("Level", "High")).attributes_into(&mut buffer);
+    assert_eq!(
+        vec![
+            Attribute::new("Department", "HR"),
+            Attribute::new("Department", "MKG"),
+            Attribute::new("Department", "FIN"),
+            Attribute::new("Level", "Low"),
+            Attribute::new("Level", "High"),
+        ],
+        buffer
+    );
+
+    let deep = (0..10_000).fold(AccessPolicy::All, |access_policy, i| {
+        access_policy | AccessPolicy::new("Department", &format!("D{i}"))
+    });
+    assert_eq!(10_000, deep.attributes().len());
+    Ok(())
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_canonical_access_policy() -> Result<(), Error> {
+    use crate::CanonicalAccessPolicy;
+    use std::collections::HashSet;
+
+    let canonical = |expression| -> Result<CanonicalAccessPolicy, Error> {
+        Ok(AccessPolicy::from_boolean_expression(expression)?.into())
+    };
+    let access_policy =
+        canonical("Level::Low && (Department::MKG || (Department::FIN || Department::HR))")?;
+    assert_eq!(
+        "(Department::FIN || Department::HR || Department::MKG) && Level::Low",
+        access_policy.boolean_expression()
+    );
+    assert_eq!(
+        &AccessPolicy::from_boolean_expression(access_policy.boolean_expression())?,
+        access_policy.access_policy()
+    );
+    assert_eq!(
+        vec![
+            Attribute::new("Department", "FIN"),
+            Attribute::new("Department", "HR"),
+            Attribute::new("Department", "MKG"),
+            Attribute::new("Level", "Low"),
+        ],
+        access_policy.attributes()
+    );
+
+    let mut cache = HashSet::new();
+    cache.insert(access_policy);
+    for equivalent in [
+        "(Department::HR || Department::FIN || Department::MKG) && Level::Low",
+        "((Department::MKG || Department::HR) || Department::FIN) && Level::Low",
+    ] {
+        assert!(cache.contains(&canonical(equivalent)?));
+    }
+    for different in [
+        "Department::FIN || Department::HR || Department::MKG && Level::Low",
+        "(Department::FIN || Department::HR) && Level::Low",
+        "(Department::FIN || Department::HR || Department::MKG) && Level::Low && Level::Low",
+    ] {
+        assert!(!cache.contains(&canonical(different)?));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_access_policy_to_boolean_expression() -> Result<(), Error> {
+    for expression in [
+        "Department::HR",
+        "(Department::HR || Department::FIN) && Security Level::Confidential",
+        "Department::HR && (Department::FIN || Security Level::Top Secret)",
+        "Department::HR || Department::FIN || Department::MKG",
+        "(Department::HR && Department::FIN) || (Department::MKG && Department::R&D)",
+    ] {
+        let access_policy = AccessPolicy::from_boolean_expression(expression)?;
+        assert_eq!(expression, access_policy.to_string());
+        assert_eq!(
+            access_policy,
+            AccessPolicy::from_boolean_expression(&access_policy.to_string())?
+        );
+    }
+    assert_eq!(
+        "Department::HR && (Department::FIN || Department::MKG)",
+        AccessPolicy::from_boolean_expression("Department::HR&&Department::FIN||Department::MKG")?
+            .to_string()
+    );
+    Ok(())
+}
+
+#[test]
+fn test_escaped_boolean_expression() -> Result<(), Error> {
+    let access_policy = AccessPolicy::new("Project::Code", "Apollo (11)")
+        & (AccessPolicy::new("Team", "Ops||Dev") | AccessPolicy::new("Team", "R&&D\\"));
+    let expression = access_policy.to_string();
+    assert_eq!(
+        r"Project\:\:Code::Apollo \(11\) && (Team::Ops\||Dev || Team::R\&&D\\)",
+        expression
+    );
+    assert_eq!(
+        Ok(()),
+        AccessPolicy::validate_boolean_expression(&expression)
+    );
+    assert_eq!(
+        access_policy,
+        AccessPolicy::from_boolean_expression(&expression)?
+    );
+    // a single `&` or `|` is not an operator and needs no escaping
+    assert_eq!(
+        AccessPolicy::new("Department", "R&D"),
+        AccessPolicy::from_boolean_expression("Department::R&D")?
+    );
+    Ok(())
+}
+
+#[test]
+fn test_parse_long_boolean_expression() -> Result<(), Error> {
+    let expression = (0..2000)
+        .map(|i| format!("Department::D{i}"))
+        .collect::<Vec<_>>()
+        .join(" || ");
+    let access_policy = AccessPolicy::from_boolean_expression(&expression)?;
+    assert_eq!(2000, access_policy.attributes().len());
+    assert_eq!(expression, access_policy.to_string());
+
+    // comparing large conjunctions does not overflow
+    let expression = expression.replace("||", "&&");
+    let access_policy = AccessPolicy::from_boolean_expression(&expression)?;
+    assert_eq!(access_policy, access_policy.clone());
+
+    let nested = format!("{}Department::HR{}", "(".repeat(5000), ")".repeat(5000));
+    assert_eq!(
+        AccessPolicy::new("Department", "HR"),
+        AccessPolicy::from_boolean_expression(&nested)?
+    );
+
+    // the parser accepts the same expressions as the validation
+    for invalid in [
+        "Department::HR)",
+        "&&Department::HR",
+        "Department::HR(",
+        "(Department::HR",
+        "Department::HR ||",
+        "()",
+    ] {
+        assert!(AccessPolicy::validate_boolean_expression(invalid).is_err());
+        assert!(
+            matches!(
+                AccessPolicy::from_boolean_expression(invalid),
+                Err(Error::InvalidBooleanExpression(_))
+            ),
+            "{invalid}"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn test_access_policy_is_satisfied_by() -> Result<(), Error> {
+    let policy = policy()?;
+    let access_policy = policy.parse_access_policy(
+        "(Department::HR || Department::FIN) && Security Level::Confidential",
+    )?;
+    for (attributes, expected) in [
+        (vec!["Department::HR", "Security Level::Confidential"], true),
+        // Higher levels of a hierarchical axis grant access to lower ones.
+        (vec!["Department::FIN", "Security Level::Top Secret"], true),
+        (vec!["Department::HR", "Security Level::Protected"], false),
+        (vec!["Department::MKG", "Security Level::Top Secret"], false),
+        (vec!["Department::HR"], false),
+        (vec![], false),
+    ] {
+        let attributes = attributes
+            .into_iter()
+            .map(str::parse)
+            .collect::<Result<Vec<Attribute>, _>>()?;
+        assert_eq!(
+            expected,
+            access_policy.is_satisfied_by(&policy, &attributes)?,
+            "{attributes:?}"
+        );
+    }
+    assert!(AccessPolicy::All.is_satisfied_by(&policy, &[])?);
+    assert!(access_policy
+        .is_satisfied_by(&policy, &[Attribute::new("Department", "Sales")])
+        .is_err());
+    Ok(())
+}
+
+#[test]
+fn test_diff_and_merge() -> Result<(), Error> {
+    let base = policy()?;
+    let hr = Attribute::new("Department", "HR");
+    let fin = Attribute::new("Department", "FIN");
+
+    let mut ahead = base.clone();
+    ahead.rotate(&hr)?;
+    ahead.add_axis(PolicyAxis::new(
+        "Country",
+        vec![("France", EncryptionHint::Classic)],
+        false,
+    ))?;
+    assert!(base.diff(&base).is_empty());
+    assert_eq!(
+        PolicyDiff {
+            added_axes: vec![AxisName::new("Country")?],
+            added_attributes: vec![Attribute::new("Country", "France")],
+            rotated_attributes: vec![hr],
+            ..PolicyDiff::default()
+        },
+        base.diff(&ahead)
+    );
+
+    // Merging a policy with one of its later versions gives the later version.
+    let mut merged = base.clone();
+    merged.merge(&ahead)?;
+    assert!(merged.diff(&ahead).is_empty());
+    let mut merged = ahead.clone();
+    merged.merge(&base)?;
+    assert_eq!(ahead, merged);
+
+    // Rotations made independently give the same value to different
+    // attributes.
+    let mut diverging = base;
+    diverging.rotate(&fin)?;
+    let mut merged = ahead.clone();
+    assert!(matches!(
+        merged.merge(&diverging),
+        Err(Error::MergeConflict(_))
+    ));
+    assert_eq!(ahead, merged);
+    Ok(())
+}
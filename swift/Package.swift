@@ -0,0 +1,17 @@
+// swift-tools-version:5.7
+
+import PackageDescription
+
+// Swift wrapper of the C interface. Build the library with
+// `cargo build --release --features ffi` and make `libabe_policy` available
+// to the linker, e.g. with `-Xlinker -L../target/release`.
+let package = Package(
+    name: "AbePolicy",
+    products: [
+        .library(name: "AbePolicy", targets: ["AbePolicy"]),
+    ],
+    targets: [
+        .systemLibrary(name: "CAbePolicy", path: "Sources/CAbePolicy"),
+        .target(name: "AbePolicy", dependencies: ["CAbePolicy"]),
+    ]
+)
@@ -0,0 +1,168 @@
+import CAbePolicy
+import Foundation
+
+/// Error reported by the ABE policy library.
+public struct AbePolicyError: Error, CustomStringConvertible {
+    /// Status code returned by the failing call, e.g. `FFI_ERROR`.
+    public let code: Int32
+    public let description: String
+}
+
+/// Returns the error recorded by the last failing call of this thread.
+private func lastError(code: Int32) -> AbePolicyError {
+    var message = [CChar](repeating: 0, count: 4096)
+    var length = Int32(message.count)
+    guard get_last_error(&message, &length) == FFI_OK else {
+        return AbePolicyError(code: code, description: "unknown error")
+    }
+    return AbePolicyError(code: code, description: String(cString: message))
+}
+
+/// Calls an `_alloc` function of the library, taking ownership of the buffer
+/// it allocates: the buffer is copied and released before returning.
+private func allocating(
+    _ call: (UnsafeMutablePointer<UnsafeMutablePointer<CChar>?>, UnsafeMutablePointer<Int32>) -> Int32
+) throws -> Data {
+    var buffer: UnsafeMutablePointer<CChar>?
+    var length: Int32 = 0
+    let code = call(&buffer, &length)
+    guard code == FFI_OK else {
+        throw lastError(code: code)
+    }
+    guard let buffer = buffer else {
+        return Data()
+    }
+    defer { h_free_buffer(buffer, length) }
+    return Data(bytes: buffer, count: Int(length))
+}
+
+/// Calls `body` with a pointer to the bytes of `data` and their length.
+private func withBytes<T>(
+    of data: Data,
+    _ body: (UnsafePointer<CChar>?, Int32) throws -> T
+) rethrows -> T {
+    try data.withUnsafeBytes { bytes in
+        try body(bytes.bindMemory(to: CChar.self).baseAddress, Int32(bytes.count))
+    }
+}
+
+/// Attribute of a policy axis, given with its encryption hint.
+public struct AxisAttribute: Encodable {
+    public let name: String
+    public let isHybridized: Bool
+
+    public init(_ name: String, isHybridized: Bool = false) {
+        self.name = name
+        self.isHybridized = isHybridized
+    }
+
+    private enum CodingKeys: String, CodingKey {
+        case name
+        case encryptionHint = "encryption_hint"
+    }
+
+    public func encode(to encoder: Encoder) throws {
+        var container = encoder.container(keyedBy: CodingKeys.self)
+        try container.encode(name, forKey: .name)
+        try container.encode(isHybridized ? "Hybridized" : "Classic", forKey: .encryptionHint)
+    }
+}
+
+/// Policy axis: a name and attributes, ordered from the lowest to the highest
+/// if the axis is hierarchical.
+public struct PolicyAxis: Encodable {
+    public let name: String
+    public let attributes: [AxisAttribute]
+    public let isHierarchical: Bool
+
+    public init(name: String, attributes: [AxisAttribute], isHierarchical: Bool) {
+        self.name = name
+        self.attributes = attributes
+        self.isHierarchical = isHierarchical
+    }
+
+    private enum CodingKeys: String, CodingKey {
+        case name
+        case attributes = "attributes_properties"
+        case isHierarchical = "hierarchical"
+    }
+}
+
+/// Policy, held as its JSON serialization.
+public struct Policy {
+    /// JSON serialization of the policy.
+    public private(set) var json: Data
+
+    /// Creates an empty policy allowing the given number of attribute
+    /// creations (additions + rotations).
+    public init(maxAttributeCreations: Int32) throws {
+        json = try allocating { h_policy_new_alloc($0, $1, maxAttributeCreations) }
+    }
+
+    /// Reads a JSON policy, converting it from a legacy format if needed.
+    public init(json: Data) throws {
+        self.json = try withBytes(of: json) { policy, policyLength in
+            try allocating { h_policy_to_json_alloc($0, $1, policy, policyLength) }
+        }
+    }
+
+    public mutating func addAxis(_ axis: PolicyAxis) throws {
+        let axis = String(decoding: try JSONEncoder().encode(axis), as: UTF8.self)
+        json = try withBytes(of: json) { policy, policyLength in
+            try allocating { h_policy_add_axis_alloc($0, $1, policy, policyLength, axis) }
+        }
+    }
+
+    /// Rotates an attribute, written `Axis::Name`.
+    public mutating func rotate(attribute: String) throws {
+        json = try withBytes(of: json) { policy, policyLength in
+            try allocating {
+                h_policy_rotate_attribute_alloc($0, $1, policy, policyLength, attribute)
+            }
+        }
+    }
+
+    /// Returns the values of an attribute, written `Axis::Name`, the current
+    /// one first.
+    public func attributeValues(of attribute: String) throws -> [UInt32] {
+        let values = try withBytes(of: json) { policy, policyLength in
+            try allocating {
+                h_policy_attribute_values_alloc($0, $1, policy, policyLength, attribute)
+            }
+        }
+        return try JSONDecoder().decode([UInt32].self, from: values)
+    }
+
+    /// Returns the attributes of the policy, or of one of its axes.
+    public func attributes(axis: String? = nil) throws -> [String] {
+        let attributes = try withBytes(of: json) { policy, policyLength in
+            try allocating { buffer, length in
+                if let axis = axis {
+                    return h_policy_attributes_alloc(buffer, length, policy, policyLength, axis)
+                }
+                return h_policy_attributes_alloc(buffer, length, policy, policyLength, nil)
+            }
+        }
+        return try JSONDecoder().decode([String].self, from: attributes)
+    }
+}
+
+/// Access policies, handled as JSON.
+public enum AccessPolicy {
+    /// Converts a boolean expression such as `Department::HR && Level::Low`
+    /// into a JSON access policy.
+    public static func parse(_ booleanExpression: String) throws -> String {
+        let accessPolicy = try allocating {
+            h_parse_boolean_access_policy_alloc($0, $1, booleanExpression)
+        }
+        return String(decoding: accessPolicy, as: UTF8.self)
+    }
+
+    /// Converts a JSON access policy into a boolean expression.
+    public static func booleanExpression(of accessPolicy: String) throws -> String {
+        let booleanExpression = try allocating {
+            h_access_policy_to_boolean_expression_alloc($0, $1, accessPolicy)
+        }
+        return String(decoding: booleanExpression, as: UTF8.self)
+    }
+}
@@ -1,4 +1,4 @@
-use abe_policy::{EncryptionHint, Error, LegacyPolicy, Policy, PolicyAxis};
+use abe_policy::{Attribute, EncryptionHint, Error, LegacyPolicy, Policy, PolicyAxis};
 
 /// Generate a new policy.
 fn policy() -> Result<Policy, Error> {
@@ -48,3 +48,17 @@ fn read_policy() {
     // read legacy policy as current policy
     let _policy = Policy::parse_and_convert(legacy_policy_str).unwrap();
 }
+
+/// Unknown fields are kept through a deserialization/serialization round-trip.
+#[test]
+fn preserve_unknown_fields() {
+    let mut policy_json =
+        serde_json::from_slice::<serde_json::Value>(include_bytes!("policy.json")).unwrap();
+    policy_json["signature"] = serde_json::json!({ "algorithm": "Ed25519", "value": "00ff" });
+
+    let mut policy = Policy::parse_and_convert(&serde_json::to_vec(&policy_json).unwrap()).unwrap();
+    policy.rotate(&Attribute::new("Department", "HR")).unwrap();
+
+    let rotated_json = serde_json::to_value(&policy).unwrap();
+    assert_eq!(policy_json["signature"], rotated_json["signature"]);
+}